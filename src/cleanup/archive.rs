@@ -0,0 +1,862 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::index::focus::{self, Focus};
+use crate::index::journal::{self, ArchiveState, Target};
+use crate::index::store::{self, atomic_write, OrbitIndex};
+use crate::index::whitelist::{self, Whitelist};
+use crate::model::project::{ProjectEntry, ProjectKind};
+use crate::scan::similarity;
+
+pub const ARCHIVE_FILE: &str = "archive.tar.gz";
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// `<root>/.orbit/archive`, one sub-directory per archive run
+pub fn archive_root(root: &Path) -> PathBuf {
+    root.join(".orbit").join("archive")
+}
+
+/// Which projects to archive
+pub enum Selection {
+    Paths(Vec<String>),
+    /// Every project classified `backup_duplicate` that has a near-duplicate copy
+    /// in the index, except the newest copy of each group (so at least one copy
+    /// always stays on disk)
+    AllDuplicates {
+        similarity_threshold: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanItem {
+    pub path: String,
+    pub size_bytes: Option<u64>,
+    /// Why the project won't be archived; `None` means it will be
+    pub refused: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivePlan {
+    pub id: String,
+    pub dest: PathBuf,
+    pub items: Vec<PlanItem>,
+}
+
+impl ArchivePlan {
+    pub fn accepted(&self) -> impl Iterator<Item = &PlanItem> {
+        self.items.iter().filter(|i| i.refused.is_none())
+    }
+
+    pub fn refused(&self) -> impl Iterator<Item = &PlanItem> {
+        self.items.iter().filter(|i| i.refused.is_some())
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.accepted().filter_map(|i| i.size_bytes).sum()
+    }
+}
+
+/// Restore manifest written next to the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub version: u32,
+    pub id: String,
+    pub created_at: DateTime<Local>,
+    pub root: String,
+    /// Archive file name, relative to the manifest
+    pub archive: String,
+    /// blake3 of the archive file, checked before restoring
+    pub blake3: String,
+    pub projects: Vec<ArchivedProject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedProject {
+    pub path: String,
+    pub files: u64,
+    pub size_bytes: u64,
+    /// Index entry at archive time, re-inserted on restore
+    pub entry: ProjectEntry,
+}
+
+/// Decide what would be archived. Nothing is touched on disk.
+pub fn plan(
+    root: &Path,
+    idx: &OrbitIndex,
+    focus: &Focus,
+    wl: &Whitelist,
+    selection: &Selection,
+) -> ArchivePlan {
+    let id = unused_id(
+        &archive_root(root),
+        &Local::now().format("%Y-%m-%d_%H-%M-%S").to_string(),
+    );
+    // The backup class is a name heuristic; in bulk only a project with a
+    // near-duplicate copy in the index is safe to archive
+    let groups = match selection {
+        Selection::AllDuplicates {
+            similarity_threshold,
+        } => similarity::group_similar(&idx.projects, *similarity_threshold),
+        Selection::Paths(_) => vec![],
+    };
+    let grouped: Vec<&str> = groups
+        .iter()
+        .flatten()
+        .map(|i| idx.projects[*i].path.as_str())
+        .collect();
+    let keep: Vec<&str> = groups
+        .iter()
+        .filter_map(|g| {
+            g.iter()
+                .map(|i| &idx.projects[*i])
+                .max_by_key(|p| p.latest_mtime)
+                .map(|p| p.path.as_str())
+        })
+        .collect();
+    let paths: Vec<String> = match selection {
+        Selection::Paths(ps) => ps
+            .iter()
+            .map(|p| p.trim_end_matches('/').to_string())
+            .collect(),
+        Selection::AllDuplicates { .. } => idx
+            .projects
+            .iter()
+            .filter(|p| matches!(p.kind, ProjectKind::BackupDuplicate))
+            .map(|p| p.path.clone())
+            .collect(),
+    };
+    let bulk = matches!(selection, Selection::AllDuplicates { .. });
+
+    let items = paths
+        .into_iter()
+        .map(|path| {
+            let entry = idx.projects.iter().find(|p| p.path == path);
+            let refused = if bulk && !grouped.contains(&path.as_str()) {
+                Some("no near-duplicate copy in the index (kept)".into())
+            } else if keep.contains(&path.as_str()) {
+                Some("newest copy in its duplicate group (kept)".into())
+            } else {
+                refusal(root, idx, focus, wl, &path, entry)
+            };
+            PlanItem {
+                size_bytes: entry.and_then(|e| e.size_bytes),
+                refused,
+                path,
+            }
+        })
+        .collect();
+
+    ArchivePlan {
+        dest: archive_root(root).join(&id),
+        id,
+        items,
+    }
+}
+
+/// `stamp`, or `stamp-2`, `stamp-3`… when runs in the same second already used it
+fn unused_id(dir: &Path, stamp: &str) -> String {
+    let mut id = stamp.to_string();
+    let mut n = 1;
+    while dir.join(&id).exists() {
+        n += 1;
+        id = format!("{}-{}", stamp, n);
+    }
+    id
+}
+
+fn refusal(
+    root: &Path,
+    idx: &OrbitIndex,
+    focus: &Focus,
+    wl: &Whitelist,
+    path: &str,
+    entry: Option<&ProjectEntry>,
+) -> Option<String> {
+    let Some(entry) = entry else {
+        return Some("not in index (run `orbit census`)".into());
+    };
+    if path == "." {
+        return Some("is the workspace root".into());
+    }
+    if !matches!(entry.kind, ProjectKind::BackupDuplicate) {
+        return Some(format!(
+            "classified {:?}, not a backup/duplicate",
+            entry.kind
+        ));
+    }
+    let pinned_inside = || {
+        focus
+            .pinned
+            .iter()
+            .map(|p| p.as_str())
+            .find(|p| Path::new(p).starts_with(path))
+    };
+    if let Some(p) = focus.pin_covering(path).or_else(pinned_inside) {
+        return Some(format!("pinned in focus ({})", p));
+    }
+    let full = root.join(path);
+    if whitelist::is_protected(root, &full, wl)
+        || wl.paths.iter().any(|w| root.join(w).starts_with(&full))
+    {
+        return Some("protected by whitelist".into());
+    }
+    if let Some(child) = idx
+        .projects
+        .iter()
+        .find(|p| p.parent.as_deref() == Some(path))
+    {
+        return Some(format!("contains nested project {}", child.path));
+    }
+    if !full.is_dir() {
+        return Some("missing on disk".into());
+    }
+    None
+}
+
+/// Pack every accepted project into `<dest>/archive.tar.gz`, verify it, write
+/// the restore manifest, and only then remove the originals.
+pub fn execute(root: &Path, idx: &OrbitIndex, plan: &ArchivePlan) -> Result<ArchiveManifest> {
+    let accepted: Vec<&PlanItem> = plan.accepted().collect();
+    if accepted.is_empty() {
+        bail!("Nothing to archive");
+    }
+    let parent = archive_root(root);
+    fs::create_dir_all(&parent)
+        .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    // create_dir, not create_dir_all: a concurrent run that planned the same id loses here
+    match fs::create_dir(&plan.dest) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            bail!("Archive {} already exists", plan.dest.display())
+        }
+        r => r.with_context(|| format!("Failed to create directory {}", plan.dest.display()))?,
+    }
+
+    let mut projects = Vec::new();
+    let mut expected = BTreeMap::new();
+    for item in &accepted {
+        let sizes = file_sizes(root, &item.path)?;
+        let (files, size_bytes) = (sizes.len() as u64, sizes.values().sum());
+        expected.extend(sizes);
+        let entry = idx
+            .projects
+            .iter()
+            .find(|p| p.path == item.path)
+            .cloned()
+            .context("Planned project vanished from index")?;
+        projects.push(ArchivedProject {
+            path: item.path.clone(),
+            files,
+            size_bytes,
+            entry,
+        });
+    }
+
+    let archive_path = plan.dest.join(ARCHIVE_FILE);
+    write_tarball(root, &archive_path, &projects)?;
+    verify_tarball(&archive_path, &expected)?;
+
+    let manifest = ArchiveManifest {
+        version: 1,
+        id: plan.id.clone(),
+        created_at: Local::now(),
+        root: root.to_string_lossy().to_string(),
+        archive: ARCHIVE_FILE.into(),
+        blake3: hash_file(&archive_path)?,
+        projects,
+    };
+    let content =
+        serde_json::to_string_pretty(&manifest).context("Failed to serialize archive manifest")?;
+    atomic_write(&plan.dest.join(MANIFEST_FILE), &content)?;
+
+    for p in &manifest.projects {
+        let full = root.join(&p.path);
+        fs::remove_dir_all(&full)
+            .with_context(|| format!("Failed to remove archived project {}", full.display()))?;
+    }
+    Ok(manifest)
+}
+
+/// Size of every regular file under `<root>/<path>`, keyed by its path in the archive
+fn file_sizes(root: &Path, path: &str) -> Result<BTreeMap<PathBuf, u64>> {
+    let dir = root.join(path);
+    let mut out = BTreeMap::new();
+    for e in walkdir::WalkDir::new(&dir).follow_links(false) {
+        let e = e.with_context(|| format!("Failed to read entry in {}", dir.display()))?;
+        if e.file_type().is_file() {
+            let len = e
+                .metadata()
+                .with_context(|| format!("Failed to read metadata for {}", e.path().display()))?
+                .len();
+            let rel = e.path().strip_prefix(&dir).unwrap_or(e.path());
+            out.insert(Path::new(path).join(rel), len);
+        }
+    }
+    Ok(out)
+}
+
+fn write_tarball(root: &Path, archive_path: &Path, projects: &[ArchivedProject]) -> Result<()> {
+    let file = File::create(archive_path)
+        .with_context(|| format!("Failed to create {}", archive_path.display()))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    tar.follow_symlinks(false);
+    for p in projects {
+        tar.append_dir_all(&p.path, root.join(&p.path))
+            .with_context(|| format!("Failed to archive {}", p.path))?;
+    }
+    let file = tar
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .with_context(|| format!("Failed to write {}", archive_path.display()))?;
+    file.sync_all()
+        .with_context(|| format!("Failed to sync {}", archive_path.display()))
+}
+
+/// Re-read the archive and check that it holds exactly the expected files, each
+/// with the size it had on disk, before deleting anything
+fn verify_tarball(archive_path: &Path, expected: &BTreeMap<PathBuf, u64>) -> Result<()> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut found = BTreeMap::new();
+    for entry in archive
+        .entries()
+        .with_context(|| format!("Failed to read {}", archive_path.display()))?
+    {
+        let mut entry =
+            entry.with_context(|| format!("Corrupt archive {}", archive_path.display()))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry
+            .path()
+            .context("Invalid path in archive")?
+            .into_owned();
+        let len = io::copy(&mut entry, &mut io::sink())
+            .with_context(|| format!("Corrupt archive {}", archive_path.display()))?;
+        found.insert(path, len);
+    }
+    if let Some((path, len)) = expected.iter().find(|(p, l)| found.get(*p) != Some(*l)) {
+        bail!(
+            "Archive verification failed for {}: expected {} bytes, found {}",
+            path.display(),
+            len,
+            found
+                .get(path)
+                .map_or("no such file".into(), |n| format!("{} bytes", n))
+        );
+    }
+    if let Some(path) = found.keys().find(|p| !expected.contains_key(*p)) {
+        bail!(
+            "Archive verification failed: unexpected file {}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn hash_file(p: &Path) -> Result<String> {
+    let mut f = File::open(p).with_context(|| format!("Failed to open {}", p.display()))?;
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut f, &mut hasher).with_context(|| format!("Failed to read {}", p.display()))?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+pub fn load_manifest(root: &Path, id: &str) -> Result<ArchiveManifest> {
+    let p = archive_root(root).join(id).join(MANIFEST_FILE);
+    let content =
+        fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", p.display()))
+}
+
+/// All archives under `.orbit/archive`, oldest first
+pub fn list_archives(root: &Path) -> Result<Vec<ArchiveManifest>> {
+    let dir = archive_root(root);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let ids: BTreeSet<String> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().join(MANIFEST_FILE).is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    ids.iter().map(|id| load_manifest(root, id)).collect()
+}
+
+/// Unpack archive `id` back into the workspace and delete it.
+/// Refuses to overwrite paths that exist again, and checks the archive hash first.
+pub fn restore(root: &Path, id: &str) -> Result<ArchiveManifest> {
+    let manifest = load_manifest(root, id)?;
+    for p in &manifest.projects {
+        let full = root.join(&p.path);
+        if full.exists() {
+            bail!("Refusing to restore over existing path {}", full.display());
+        }
+    }
+    let dir = archive_root(root).join(id);
+    let archive_path = dir.join(&manifest.archive);
+    if hash_file(&archive_path)? != manifest.blake3 {
+        bail!(
+            "Archive {} does not match its manifest hash",
+            archive_path.display()
+        );
+    }
+    let file = File::open(&archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(root)
+        .with_context(|| format!("Failed to unpack {}", archive_path.display()))?;
+    fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    Ok(manifest)
+}
+
+/// Drop archived projects from the index
+pub fn remove_from_index(idx: &mut OrbitIndex, manifest: &ArchiveManifest) {
+    idx.projects
+        .retain(|p| !manifest.projects.iter().any(|a| a.path == p.path));
+}
+
+/// Re-insert restored projects into the index (replacing stale entries)
+pub fn add_to_index(idx: &mut OrbitIndex, manifest: &ArchiveManifest) {
+    remove_from_index(idx, manifest);
+    idx.projects
+        .extend(manifest.projects.iter().map(|a| a.entry.clone()));
+    idx.projects.sort_by(|a, b| a.path.cmp(&b.path));
+}
+
+/// Journal an archive (`archived`) or restore so `orbit undo` can reverse it
+pub fn record_change(root: &Path, manifest: &ArchiveManifest, archived: bool) -> Result<()> {
+    let paths: Vec<String> = manifest.projects.iter().map(|p| p.path.clone()).collect();
+    let stored = ArchiveState {
+        archive_id: Some(manifest.id.clone()),
+        paths: paths.clone(),
+    };
+    let live = ArchiveState {
+        archive_id: None,
+        paths: paths.clone(),
+    };
+    let (op, before, after) = if archived {
+        ("archive", live, stored)
+    } else {
+        ("archive restore", stored, live)
+    };
+    journal::record(
+        root,
+        op,
+        Target::Archive,
+        format!("{} ({})", paths.join(", "), manifest.id),
+        &before,
+        &after,
+    )?;
+    Ok(())
+}
+
+pub fn handle_archive(
+    root_str: &str,
+    paths: Vec<String>,
+    all: bool,
+    restore_id: Option<String>,
+    list: bool,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+
+    if list {
+        let archives = list_archives(root)?;
+        if json_output {
+            println!("{}", serde_json::json!({ "archives": archives }));
+        } else if archives.is_empty() {
+            println!("No archives.");
+        } else {
+            for a in &archives {
+                let bytes: u64 = a.projects.iter().map(|p| p.size_bytes).sum();
+                println!(
+                    "{}  {} projects  {} bytes  ({})",
+                    a.id,
+                    a.projects.len(),
+                    bytes,
+                    a.projects
+                        .iter()
+                        .map(|p| p.path.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(id) = restore_id {
+        if dry_run {
+            let m = load_manifest(root, &id)?;
+            if json_output {
+                println!("{}", serde_json::json!({ "dry_run": true, "restore": m }));
+            } else {
+                println!("[dry-run] would restore archive {}:", id);
+                for p in &m.projects {
+                    println!(
+                        "  - {} ({} files, {} bytes)",
+                        root.join(&p.path).display(),
+                        p.files,
+                        p.size_bytes
+                    );
+                }
+            }
+            return Ok(());
+        }
+        let m = restore(root, &id)?;
+        let _lock = store::lock(root)?;
+        let mut idx = store::load(root)?;
+        add_to_index(&mut idx, &m);
+        store::save(root, &idx)?;
+        record_change(root, &m, false)?;
+        if json_output {
+            println!(
+                "{}",
+                serde_json::json!({ "status": "restored", "manifest": m })
+            );
+        } else {
+            println!("Restored {} projects from archive {}", m.projects.len(), id);
+        }
+        return Ok(());
+    }
+
+    let selection = if all {
+        Selection::AllDuplicates {
+            similarity_threshold: config::load_config(root)?.similarity.threshold(),
+        }
+    } else if !paths.is_empty() {
+        Selection::Paths(paths)
+    } else {
+        bail!("Specify project paths to archive, or --all for every backup/duplicate");
+    };
+    let idx = store::load(root)?;
+    let focus = focus::load_focus(root).unwrap_or_default();
+    let wl = whitelist::load_whitelist(root)?;
+    let plan = plan(root, &idx, &focus, &wl, &selection);
+
+    if dry_run {
+        print_plan(&plan, true, json_output);
+        return Ok(());
+    }
+    if plan.accepted().next().is_none() {
+        print_plan(&plan, false, json_output);
+        if !json_output {
+            println!("Nothing to archive.");
+        }
+        return Ok(());
+    }
+    if !json_output {
+        print_plan(&plan, false, false);
+    }
+
+    let manifest = execute(root, &idx, &plan)?;
+    // Re-read under the lock: another process may have saved while archiving
+    let _lock = store::lock(root)?;
+    let mut idx = store::load(root)?;
+    remove_from_index(&mut idx, &manifest);
+    store::save(root, &idx)?;
+    record_change(root, &manifest, true)?;
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "archived",
+                "archive": plan.dest.join(ARCHIVE_FILE).display().to_string(),
+                "manifest": manifest,
+                "refused": plan.refused().collect::<Vec<_>>(),
+            })
+        );
+    } else {
+        println!(
+            "Archived {} projects to {}. Restore with `orbit archive --restore {}`",
+            manifest.projects.len(),
+            plan.dest.join(ARCHIVE_FILE).display(),
+            manifest.id
+        );
+    }
+    Ok(())
+}
+
+fn print_plan(plan: &ArchivePlan, dry_run: bool, json_output: bool) {
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "dry_run": dry_run, "plan": plan })
+        );
+        return;
+    }
+    let prefix = if dry_run {
+        "[dry-run] would archive"
+    } else {
+        "Archiving"
+    };
+    println!(
+        "{} {} projects ({} bytes) into {}",
+        prefix,
+        plan.accepted().count(),
+        plan.total_bytes(),
+        plan.dest.join(ARCHIVE_FILE).display()
+    );
+    for i in plan.accepted() {
+        println!(
+            "  + {} ({} bytes)",
+            i.path,
+            i.size_bytes
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "?".into())
+        );
+    }
+    for i in plan.refused() {
+        println!(
+            "  ! {} refused: {}",
+            i.path,
+            i.refused.as_deref().unwrap_or("")
+        );
+    }
+    if dry_run {
+        println!("  manifest -> {}", plan.dest.join(MANIFEST_FILE).display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_to_index, execute, file_sizes, list_archives, plan, remove_from_index, restore,
+        unused_id, verify_tarball, write_tarball, ArchivedProject, Selection,
+    };
+    use crate::index::focus::Focus;
+    use crate::index::store::OrbitIndex;
+    use crate::index::whitelist::Whitelist;
+    use crate::model::project::{ProjectEntry, ProjectKind};
+    use chrono::{Local, TimeZone};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn entry(path: &str, kind: ProjectKind) -> ProjectEntry {
+        ProjectEntry {
+            kind,
+            size_bytes: Some(3),
+            ..ProjectEntry::stub(path)
+        }
+    }
+
+    fn index(projects: Vec<ProjectEntry>) -> OrbitIndex {
+        OrbitIndex {
+            projects,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn plan_refuses_pinned_protected_and_non_duplicates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for p in ["a_copy", "b_copy", "c_copy", "app"] {
+            fs::create_dir_all(root.join(p)).unwrap();
+        }
+        let mut idx = index(vec![
+            entry("a_copy", ProjectKind::BackupDuplicate),
+            entry("b_copy", ProjectKind::BackupDuplicate),
+            entry("c_copy", ProjectKind::BackupDuplicate),
+            entry("gone_copy", ProjectKind::BackupDuplicate),
+            entry("app", ProjectKind::ActiveStandalone),
+        ]);
+        for p in &mut idx.projects {
+            p.fingerprint = Some("fp".into());
+        }
+        idx.projects[4].latest_mtime = Some(Local.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap());
+        let focus = Focus {
+            pinned: vec!["b_copy".into()],
+        };
+        let wl = Whitelist {
+            version: "0.1".into(),
+            paths: vec!["c_copy/keep".into()],
+        };
+
+        let all = Selection::AllDuplicates {
+            similarity_threshold: 0.85,
+        };
+        let p = plan(root, &idx, &focus, &wl, &all);
+        let accepted: Vec<_> = p.accepted().map(|i| i.path.as_str()).collect();
+        assert_eq!(accepted, vec!["a_copy"]);
+        let refused: Vec<_> = p
+            .refused()
+            .map(|i| (i.path.as_str(), i.refused.clone().unwrap()))
+            .collect();
+        assert_eq!(refused[0].0, "b_copy");
+        assert!(refused[0].1.contains("pinned"));
+        assert!(refused[1].1.contains("whitelist"));
+        assert!(refused[2].1.contains("missing"));
+
+        let p = plan(
+            root,
+            &idx,
+            &focus,
+            &wl,
+            &Selection::Paths(vec!["app".into(), "nope".into()]),
+        );
+        assert!(p.items[0]
+            .refused
+            .as_ref()
+            .unwrap()
+            .contains("not a backup"));
+        assert!(p.items[1]
+            .refused
+            .as_ref()
+            .unwrap()
+            .contains("not in index"));
+    }
+
+    #[test]
+    fn plan_refuses_projects_under_a_pinned_ancestor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("clients").join("acme_old")).unwrap();
+        let idx = index(vec![entry(
+            "clients/acme_old",
+            ProjectKind::BackupDuplicate,
+        )]);
+        let only = Selection::Paths(vec!["clients/acme_old".into()]);
+        for pin in ["clients", "."] {
+            let focus = Focus {
+                pinned: vec![pin.into()],
+            };
+            let p = plan(root, &idx, &focus, &Whitelist::default(), &only);
+            let why = p.items[0].refused.clone().unwrap();
+            assert_eq!(why, format!("pinned in focus ({})", pin));
+        }
+        let p = plan(root, &idx, &Focus::default(), &Whitelist::default(), &only);
+        assert!(p.items[0].refused.is_none());
+    }
+
+    #[test]
+    fn archiving_all_keeps_newest_copy_and_projects_without_a_copy() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for p in ["app", "app_copy", "lone_backup"] {
+            fs::create_dir_all(root.join(p)).unwrap();
+        }
+        let mut app = entry("app", ProjectKind::BackupDuplicate);
+        let mut copy = entry("app_copy", ProjectKind::BackupDuplicate);
+        app.fingerprint = Some("fp".into());
+        copy.fingerprint = Some("fp".into());
+        app.latest_mtime = Some(Local.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap());
+        copy.latest_mtime = Some(Local.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        let idx = index(vec![
+            app,
+            copy,
+            entry("lone_backup", ProjectKind::BackupDuplicate),
+        ]);
+
+        let p = plan(
+            root,
+            &idx,
+            &Focus::default(),
+            &Whitelist::default(),
+            &Selection::AllDuplicates {
+                similarity_threshold: 0.85,
+            },
+        );
+        let accepted: Vec<_> = p.accepted().map(|i| i.path.as_str()).collect();
+        assert_eq!(accepted, vec!["app_copy"]);
+        assert!(p.items[0].refused.as_ref().unwrap().contains("newest copy"));
+        assert!(p.items[2]
+            .refused
+            .as_ref()
+            .unwrap()
+            .contains("no near-duplicate"));
+    }
+
+    #[test]
+    fn archive_then_restore_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let proj = root.join("site_backup");
+        fs::create_dir_all(proj.join("src")).unwrap();
+        fs::write(proj.join("README.md"), "old").unwrap();
+        fs::write(proj.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+
+        let mut idx = index(vec![entry("site_backup", ProjectKind::BackupDuplicate)]);
+        let p = plan(
+            root,
+            &idx,
+            &Focus::default(),
+            &Whitelist::default(),
+            &Selection::Paths(vec!["site_backup".into()]),
+        );
+        let manifest = execute(root, &idx, &p).unwrap();
+        assert!(!proj.exists());
+        assert!(p.dest.join("archive.tar.gz").is_file());
+        assert_eq!(manifest.projects[0].files, 2);
+        remove_from_index(&mut idx, &manifest);
+        assert!(idx.projects.is_empty());
+        assert_eq!(list_archives(root).unwrap().len(), 1);
+
+        let restored = restore(root, &manifest.id).unwrap();
+        add_to_index(&mut idx, &restored);
+        assert_eq!(fs::read_to_string(proj.join("README.md")).unwrap(), "old");
+        assert!(proj.join("src").join("main.rs").is_file());
+        assert_eq!(idx.projects.len(), 1);
+        assert!(!p.dest.exists());
+    }
+
+    #[test]
+    fn same_second_runs_get_distinct_ids() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let stamp = "2025-03-01_10-00-00";
+        assert_eq!(unused_id(dir, stamp), stamp);
+        fs::create_dir(dir.join(stamp)).unwrap();
+        assert_eq!(unused_id(dir, stamp), "2025-03-01_10-00-00-2");
+        fs::create_dir(dir.join("2025-03-01_10-00-00-2")).unwrap();
+        assert_eq!(unused_id(dir, stamp), "2025-03-01_10-00-00-3");
+    }
+
+    #[test]
+    fn verification_compares_every_file_size() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("old").join("src")).unwrap();
+        fs::write(root.join("old").join("a.txt"), "abc").unwrap();
+        fs::write(root.join("old").join("src").join("b.rs"), "fn b() {}\n").unwrap();
+        let archive = root.join("archive.tar.gz");
+        let projects = vec![ArchivedProject {
+            path: "old".into(),
+            files: 2,
+            size_bytes: 13,
+            entry: ProjectEntry::stub("old"),
+        }];
+        write_tarball(root, &archive, &projects).unwrap();
+
+        let expected = file_sizes(root, "old").unwrap();
+        assert_eq!(expected[&PathBuf::from("old/a.txt")], 3);
+        verify_tarball(&archive, &expected).unwrap();
+
+        let mut grown = expected.clone();
+        grown.insert("old/a.txt".into(), 4);
+        let err = verify_tarball(&archive, &grown).unwrap_err().to_string();
+        assert!(
+            err.contains("old/a.txt: expected 4 bytes, found 3 bytes"),
+            "{err}"
+        );
+
+        let mut missing = expected.clone();
+        missing.insert("old/c.txt".into(), 3);
+        let err = verify_tarball(&archive, &missing).unwrap_err().to_string();
+        assert!(err.contains("old/c.txt"), "{err}");
+
+        let mut fewer = expected;
+        fewer.remove(&PathBuf::from("old/a.txt"));
+        let err = verify_tarball(&archive, &fewer).unwrap_err().to_string();
+        assert!(err.contains("unexpected file old/a.txt"), "{err}");
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::focus::{self, Focus};
+use crate::index::store::{self, OrbitIndex};
+use crate::index::whitelist::{self, Whitelist};
+use crate::model::project::BuildDir;
+use crate::scan::build_dirs;
+
+/// Build directories of one stale project
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanItem {
+    pub project: String,
+    pub latest_mtime: Option<DateTime<Local>>,
+    pub dirs: Vec<BuildDir>,
+    /// Why the project is left alone, if it is
+    pub refused: Option<String>,
+}
+
+impl CleanItem {
+    pub fn bytes(&self) -> u64 {
+        self.dirs.iter().map(|d| d.size_bytes).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanPlan {
+    pub older_than_days: u64,
+    pub items: Vec<CleanItem>,
+}
+
+impl CleanPlan {
+    pub fn accepted(&self) -> impl Iterator<Item = &CleanItem> {
+        self.items.iter().filter(|i| i.refused.is_none())
+    }
+
+    pub fn refused(&self) -> impl Iterator<Item = &CleanItem> {
+        self.items.iter().filter(|i| i.refused.is_some())
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.accepted().map(|i| i.bytes()).sum()
+    }
+}
+
+/// Measure build directories of every indexed project whose sources were last
+/// touched more than `older_than_days` ago. The index only preselects projects:
+/// their sources are re-stat'ed, and projects edited since are refused.
+/// Nothing is touched on disk.
+pub fn plan(
+    root: &Path,
+    idx: &OrbitIndex,
+    focus: &Focus,
+    wl: &Whitelist,
+    older_than_days: u64,
+    now: DateTime<Local>,
+) -> Result<CleanPlan> {
+    let cutoff = now - Duration::days(older_than_days as i64);
+    let mut items = Vec::new();
+    for p in &idx.projects {
+        if p.latest_mtime.is_none_or(|m| m > cutoff) {
+            continue;
+        }
+        let full = root.join(&p.path);
+        if !full.is_dir() {
+            continue;
+        }
+        let nested: Vec<PathBuf> = idx
+            .projects
+            .iter()
+            .filter(|c| {
+                c.path != p.path && (p.path == "." || Path::new(&c.path).starts_with(&p.path))
+            })
+            .map(|c| root.join(&c.path))
+            .collect();
+        let measured = build_dirs::measure(&full, &nested)?;
+        let skip: Vec<PathBuf> = nested
+            .iter()
+            .cloned()
+            .chain(measured.iter().map(|d| full.join(&d.path)))
+            .collect();
+        let dirs: Vec<BuildDir> = measured
+            .into_iter()
+            .filter(|d| !dir_protected(&full.join(&d.path), root, wl))
+            .collect();
+        if dirs.is_empty() {
+            continue;
+        }
+        let touched = sources_touched(&full, &skip).or(p.latest_mtime);
+        let refused = refusal(root, focus, wl, &p.path).or_else(|| {
+            touched.filter(|t| *t > cutoff).map(|t| {
+                format!(
+                    "sources changed {} (since the last census)",
+                    t.format("%Y-%m-%d")
+                )
+            })
+        });
+        items.push(CleanItem {
+            refused,
+            project: p.path.clone(),
+            latest_mtime: touched,
+            dirs,
+        });
+    }
+    items.sort_by(|a, b| b.bytes().cmp(&a.bytes()).then(a.project.cmp(&b.project)));
+    Ok(CleanPlan {
+        older_than_days,
+        items,
+    })
+}
+
+/// Newest mtime of the files under `project` outside `skip` (its build
+/// directories and nested projects). Unreadable entries are ignored.
+fn sources_touched(project: &Path, skip: &[PathBuf]) -> Option<DateTime<Local>> {
+    walkdir::WalkDir::new(project)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git" && !skip.iter().any(|s| s == e.path()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .map(DateTime::<Local>::from)
+        .max()
+}
+
+fn dir_protected(dir: &Path, root: &Path, wl: &Whitelist) -> bool {
+    whitelist::is_protected(root, dir, wl) || wl.paths.iter().any(|w| root.join(w).starts_with(dir))
+}
+
+fn refusal(root: &Path, focus: &Focus, wl: &Whitelist, path: &str) -> Option<String> {
+    if let Some(p) = focus.pin_covering(path) {
+        return Some(format!("pinned in focus ({})", p));
+    }
+    if whitelist::is_protected(root, &root.join(path), wl) {
+        return Some("protected by whitelist".into());
+    }
+    None
+}
+
+/// Delete the accepted build directories; returns the bytes freed
+pub fn execute(root: &Path, plan: &CleanPlan) -> Result<u64> {
+    let mut freed = 0;
+    for item in plan.accepted() {
+        for d in &item.dirs {
+            let p = root.join(&item.project).join(&d.path);
+            let md = fs::symlink_metadata(&p)
+                .with_context(|| format!("Failed to read metadata for {}", p.display()))?;
+            if !md.is_dir() {
+                bail!("Refusing to delete {}: not a directory", p.display());
+            }
+            fs::remove_dir_all(&p).with_context(|| format!("Failed to delete {}", p.display()))?;
+            freed += d.size_bytes;
+        }
+    }
+    Ok(freed)
+}
+
+pub fn handle_clean_artifacts(
+    root_str: &str,
+    older_than: u64,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+    let idx = store::load(root)?;
+    if idx.projects.is_empty() {
+        bail!("Index is empty; run `orbit census` first");
+    }
+    let focus = focus::load_focus(root).unwrap_or_default();
+    let wl = whitelist::load_whitelist(root)?;
+    let plan = plan(root, &idx, &focus, &wl, older_than, Local::now())?;
+
+    if dry_run {
+        print_plan(&plan, true, json_output);
+        return Ok(());
+    }
+    if plan.accepted().next().is_none() {
+        print_plan(&plan, false, json_output);
+        if !json_output {
+            println!("Nothing to clean.");
+        }
+        return Ok(());
+    }
+    if !json_output {
+        print_plan(&plan, false, false);
+    }
+
+    let freed = execute(root, &plan)?;
+    // Keep `census --reclaim` measurements in the index truthful (re-read under
+    // the lock, as another process may have saved while deleting)
+    let _lock = store::lock(root)?;
+    let mut idx = store::load(root)?;
+    for item in plan.accepted() {
+        if let Some(p) = idx.projects.iter_mut().find(|p| p.path == item.project) {
+            if let Some(dirs) = &mut p.build_dirs {
+                dirs.retain(|d| !item.dirs.iter().any(|x| x.path == d.path));
+            }
+        }
+    }
+    store::save(root, &idx)?;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "cleaned",
+                "freed_bytes": freed,
+                "projects": plan.accepted().collect::<Vec<_>>(),
+                "refused": plan.refused().collect::<Vec<_>>(),
+            })
+        );
+    } else {
+        println!(
+            "Freed {} bytes from {} projects.",
+            freed,
+            plan.accepted().count()
+        );
+    }
+    Ok(())
+}
+
+fn print_plan(plan: &CleanPlan, dry_run: bool, json_output: bool) {
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "dry_run": dry_run, "plan": plan })
+        );
+        return;
+    }
+    let prefix = if dry_run {
+        "[dry-run] would delete"
+    } else {
+        "Deleting"
+    };
+    println!(
+        "{} build outputs of {} projects untouched for {}+ days ({} bytes)",
+        prefix,
+        plan.accepted().count(),
+        plan.older_than_days,
+        plan.total_bytes()
+    );
+    for i in plan.accepted() {
+        for d in &i.dirs {
+            println!(
+                "  - {}/{} ({}, {} bytes)",
+                i.project, d.path, d.kind, d.size_bytes
+            );
+        }
+    }
+    for i in plan.refused() {
+        println!(
+            "  ! {} ({} bytes): {}",
+            i.project,
+            i.bytes(),
+            i.refused.as_deref().unwrap_or("")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{execute, plan};
+    use crate::index::focus::Focus;
+    use crate::index::store::OrbitIndex;
+    use crate::index::whitelist::Whitelist;
+    use crate::model::project::ProjectEntry;
+    use chrono::{Duration, Local};
+    use std::fs;
+
+    fn entry(path: &str, age_days: i64) -> ProjectEntry {
+        ProjectEntry {
+            latest_mtime: Some(Local::now() - Duration::days(age_days)),
+            ..ProjectEntry::stub(path)
+        }
+    }
+
+    #[test]
+    fn cleans_only_stale_unprotected_projects() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for p in ["old", "fresh", "pinned", "kept", "edited"] {
+            let nm = root.join(p).join("node_modules");
+            fs::create_dir_all(&nm).unwrap();
+            fs::write(nm.join("dep.js"), "12345").unwrap();
+        }
+        let long_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(90 * 86400);
+        let write_old = |p: std::path::PathBuf, body: &str| {
+            fs::write(&p, body).unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&p)
+                .unwrap()
+                .set_modified(long_ago)
+                .unwrap();
+        };
+        fs::create_dir_all(root.join("kept").join("dist")).unwrap();
+        write_old(root.join("kept").join("package.json"), "{}");
+        write_old(root.join("kept").join(".gitignore"), "dist/\n");
+        fs::write(root.join("kept").join("dist").join("app.js"), "x").unwrap();
+        // Stale in the index, but a source file was edited since
+        fs::write(root.join("edited").join("main.js"), "new").unwrap();
+
+        let idx = OrbitIndex {
+            projects: vec![
+                entry("old", 90),
+                entry("fresh", 1),
+                entry("pinned", 90),
+                entry("kept", 90),
+                entry("edited", 90),
+            ],
+            ..Default::default()
+        };
+        let focus = Focus {
+            pinned: vec!["pinned".into()],
+        };
+        let wl = Whitelist {
+            version: "0.1".into(),
+            paths: vec!["kept/dist".into()],
+        };
+
+        let plan = plan(root, &idx, &focus, &wl, 30, Local::now()).unwrap();
+        let accepted: Vec<&str> = plan.accepted().map(|i| i.project.as_str()).collect();
+        assert_eq!(accepted, vec!["kept", "old"]);
+        let refused: Vec<&str> = plan.refused().map(|i| i.project.as_str()).collect();
+        assert_eq!(refused, vec!["edited", "pinned"]);
+        let edited = plan.refused().find(|i| i.project == "edited").unwrap();
+        assert!(edited
+            .refused
+            .as_deref()
+            .unwrap()
+            .starts_with("sources changed"));
+        let kept = plan.accepted().find(|i| i.project == "kept").unwrap();
+        assert_eq!(kept.dirs.len(), 1, "whitelisted dist/ is not offered");
+
+        assert_eq!(execute(root, &plan).unwrap(), 10);
+        assert!(!root.join("old").join("node_modules").exists());
+        assert!(!root.join("kept").join("node_modules").exists());
+        assert!(root.join("kept").join("dist").exists());
+        assert!(root.join("fresh").join("node_modules").exists());
+        assert!(root.join("pinned").join("node_modules").exists());
+        assert!(root.join("edited").join("node_modules").exists());
+    }
+}
@@ -1,8 +1,10 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
-use crate::index::{session, whitelist};
 use crate::index::session::OrbitSession;
+use crate::index::{session, whitelist};
+use crate::scan::census::CensusOptions;
+use crate::scan::delta::DeltaOptions;
 
 #[derive(Parser)]
 #[command(name = "orbit")]
@@ -25,12 +27,43 @@ pub struct OrbitCli {
 
 #[derive(Subcommand)]
 pub enum Commands {
-    Tui,
+    Tui {
+        /// Start in live mode: watch the root and update the index as files change
+        #[arg(long)]
+        live: bool,
+        /// Census depth used by the TUI (Enter on Home, live rescans)
+        #[arg(long, default_value = "4")]
+        depth: usize,
+    },
     Census {
         #[arg(long, default_value = "4")]
         depth: usize,
         #[arg(long)]
         since: Option<String>,
+        /// Worker threads for summarizing projects (0 = all available cores)
+        #[arg(short, long, default_value = "0")]
+        jobs: usize,
+        /// Re-summarize every project, ignoring the census cache
+        #[arg(long)]
+        full: bool,
+        /// Don't honor .gitignore, global git excludes or .orbitignore (audit mode)
+        #[arg(long)]
+        no_ignore: bool,
+        /// Count code/comment/blank lines per language (slower: reads every source file)
+        #[arg(long)]
+        loc: bool,
+        /// Measure build-output directories (target/, node_modules/, venvs) per project
+        #[arg(long)]
+        reclaim: bool,
+        /// Hash every file for near-duplicate similarity (slower: reads every file)
+        #[arg(long)]
+        similarity: bool,
+        /// Smallest project size change (MiB) listed in the delta report
+        #[arg(long, default_value = "100")]
+        delta_threshold_mb: u64,
+        /// Also write the delta report to .orbit/exports/delta.md
+        #[arg(long)]
+        delta_md: bool,
     },
     Status,
     Focus {
@@ -52,31 +85,194 @@ pub enum Commands {
     Snap {
         #[arg(short, long)]
         label: Option<String>,
+        #[command(subcommand)]
+        action: Option<SnapAction>,
     },
     Export,
+    /// Move backup/duplicate projects into a compressed archive under .orbit/archive
+    Archive {
+        /// Project paths as listed in the index
+        paths: Vec<String>,
+        /// Archive every backup_duplicate project that has a near-duplicate copy (newest copy kept)
+        #[arg(long)]
+        all: bool,
+        /// Restore a previous archive by id
+        #[arg(long, conflicts_with_all = ["paths", "all"])]
+        restore: Option<String>,
+        /// List archives
+        #[arg(long)]
+        list: bool,
+    },
+    /// Delete build outputs (target/, node_modules/, venvs) of projects untouched for N days
+    CleanArtifacts {
+        /// Only projects whose sources are older than this many days
+        #[arg(long)]
+        older_than: u64,
+    },
+    /// Revert the most recent focus, whitelist, index or archive change
+    Undo {
+        /// Revert even if the state changed since the journaled operation
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the operations journal (.orbit/journal.jsonl)
+    History {
+        /// Number of most recent entries to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+    /// List catalogued artifacts (handoffs, plans, transcripts…), newest first
+    Artifacts {
+        /// Only this category
+        #[arg(long)]
+        category: Option<String>,
+        /// Only this project (relative path)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Full-text search over artifact contents (.orbit/artifact_index.json)
+    GrepArtifacts {
+        /// Words that must all appear in the artifact
+        query: String,
+        /// Maximum number of artifacts to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        /// Rebuild the catalog and search index before searching
+        #[arg(long)]
+        reindex: bool,
+    },
+    /// Chronological artifact timeline of a project (default: every pinned project)
+    Timeline {
+        /// Project path relative to the root
+        project: Option<String>,
+        /// Write .orbit/exports/timelines/<project>/timeline.md instead of printing
+        #[arg(long)]
+        export: bool,
+    },
+    /// Size growth, activity and staleness across recorded census runs (~/.orbit/history)
+    Trends {
+        /// Window to compare, in days
+        #[arg(long, default_value = "30")]
+        days: i64,
+        /// Maximum number of size changes to list
+        #[arg(long, default_value = "10")]
+        limit: usize,
+        /// Only this project (relative path)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Watch the root and update changed projects in the index as files change
+    Watch {
+        /// Census depth used when projects appear or disappear
+        #[arg(long, default_value = "4")]
+        depth: usize,
+        /// Quiet period before a burst of changes is applied
+        #[arg(long, default_value = "500")]
+        debounce_ms: u64,
+    },
+    /// Index maintenance (schema migrations)
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
     /// Headless scan + export for CI (writes shared index/session)
     Ci {
         #[arg(long, default_value = "4")]
         depth: usize,
         #[arg(long)]
         since: Option<String>,
+        /// Worker threads for summarizing projects (0 = all available cores)
+        #[arg(short, long, default_value = "0")]
+        jobs: usize,
+        /// Re-summarize every project, ignoring the census cache
+        #[arg(long)]
+        full: bool,
+        /// Don't honor .gitignore, global git excludes or .orbitignore (audit mode)
+        #[arg(long)]
+        no_ignore: bool,
+        /// Count code/comment/blank lines per language (slower: reads every source file)
+        #[arg(long)]
+        loc: bool,
+        /// Measure build-output directories (target/, node_modules/, venvs) per project
+        #[arg(long)]
+        reclaim: bool,
+        /// Hash every file for near-duplicate similarity (slower: reads every file)
+        #[arg(long)]
+        similarity: bool,
         #[arg(long)]
         no_export: bool,
     },
 }
 
+/// `orbit snap <action>`; without an action a new snapshot is taken
+#[derive(Subcommand)]
+pub enum SnapAction {
+    /// List snapshots under .orbit/snapshots
+    List,
+    /// Show a snapshot's pins, index summary and artifacts (`latest` or an id prefix)
+    Show { id: String },
+    /// Compare two snapshots: projects added/removed/reclassified, pins, artifacts
+    Diff { a: String, b: String },
+    /// Restore focus and index from a snapshot (journaled; `orbit undo` reverts)
+    Restore { id: String },
+    /// Prune snapshots outside the retention policy and unreferenced objects
+    Gc {
+        /// Keep the newest N snapshots (default from `[snapshots]` config, else 10)
+        #[arg(long)]
+        keep_last: Option<usize>,
+        /// Keep the newest snapshot of each day for this many days (default 30)
+        #[arg(long)]
+        keep_daily: Option<u32>,
+    },
+}
+
+/// `orbit index <action>`
+#[derive(Subcommand)]
+pub enum IndexAction {
+    /// Upgrade the shared and per-root index to the current schema (a backup is kept)
+    Migrate {
+        /// Only report; exit non-zero if any index needs migration or is unreadable
+        #[arg(long)]
+        check: bool,
+    },
+}
+
 pub fn run() -> Result<()> {
     let cli = OrbitCli::parse();
-    match cli.command.unwrap_or(Commands::Tui) {
-        Commands::Tui => crate::tui::run(&cli.root, cli.dry_run),
-        Commands::Census { depth, since } => crate::scan::census::run_census(
-            &cli.root,
+    match cli.command.unwrap_or(Commands::Tui {
+        live: false,
+        depth: 4,
+    }) {
+        Commands::Tui { live, depth } => crate::tui::run(&cli.root, cli.dry_run, live, depth),
+        Commands::Census {
             depth,
-            since.as_deref(),
-            cli.json,
-            None,
-        )
-        .map(|_| ()),
+            since,
+            jobs,
+            full,
+            no_ignore,
+            loc,
+            reclaim,
+            similarity,
+            delta_threshold_mb,
+            delta_md,
+        } => {
+            let opts = CensusOptions {
+                depth,
+                since,
+                jobs,
+                full,
+                no_ignore,
+                loc,
+                reclaim,
+                similarity,
+                record: true,
+            };
+            let delta = DeltaOptions {
+                min_size_change: delta_threshold_mb * 1024 * 1024,
+                export: delta_md,
+            };
+            crate::scan::census::run_census(&cli.root, &opts, &delta, cli.json, None)
+        }
         Commands::Status => crate::index::status::print_status(&cli.root, cli.json),
         Commands::Focus { add, remove, list } => {
             crate::index::focus::handle_focus(&cli.root, add, remove, list, cli.json)
@@ -84,15 +280,123 @@ pub fn run() -> Result<()> {
         Commands::Whitelist { add, remove, list } => {
             whitelist::handle_whitelist(&cli.root, add, remove, list, cli.json)
         }
-        Commands::Snap { label } => {
-            crate::snapshot::quick::snapshot_pinned(&cli.root, label.as_deref(), cli.dry_run)
-        }
+        Commands::Snap { label, action } => match action {
+            None => {
+                crate::snapshot::quick::snapshot_pinned(&cli.root, label.as_deref(), cli.dry_run)
+            }
+            Some(SnapAction::List) => crate::snapshot::inspect::handle_list(&cli.root, cli.json),
+            Some(SnapAction::Show { id }) => {
+                crate::snapshot::inspect::handle_show(&cli.root, &id, cli.json)
+            }
+            Some(SnapAction::Diff { a, b }) => {
+                crate::snapshot::inspect::handle_diff(&cli.root, &a, &b, cli.json)
+            }
+            Some(SnapAction::Restore { id }) => {
+                crate::snapshot::inspect::handle_restore(&cli.root, &id, cli.dry_run, cli.json)
+            }
+            Some(SnapAction::Gc {
+                keep_last,
+                keep_daily,
+            }) => crate::snapshot::gc::handle_gc(
+                &cli.root,
+                keep_last,
+                keep_daily,
+                cli.dry_run,
+                cli.json,
+            ),
+        },
         Commands::Export => crate::export::all::export_all(&cli.root, cli.dry_run),
-        Commands::Ci { depth, since, no_export } => {
-            crate::scan::census::run_census(
+        Commands::Archive {
+            paths,
+            all,
+            restore,
+            list,
+        } => crate::cleanup::archive::handle_archive(
+            &cli.root,
+            paths,
+            all,
+            restore,
+            list,
+            cli.dry_run,
+            cli.json,
+        ),
+        Commands::CleanArtifacts { older_than } => {
+            crate::cleanup::build_dirs::handle_clean_artifacts(
                 &cli.root,
+                older_than,
+                cli.dry_run,
+                cli.json,
+            )
+        }
+        Commands::Undo { force } => {
+            crate::index::journal::handle_undo(&cli.root, force, cli.dry_run, cli.json)
+        }
+        Commands::History { limit } => {
+            crate::index::journal::handle_history(&cli.root, limit, cli.json)
+        }
+        Commands::Artifacts { category, project } => crate::index::catalog::handle_artifacts(
+            &cli.root,
+            category.as_deref(),
+            project.as_deref(),
+            cli.json,
+        ),
+        Commands::GrepArtifacts {
+            query,
+            limit,
+            reindex,
+        } => {
+            crate::index::search::handle_grep_artifacts(&cli.root, &query, limit, reindex, cli.json)
+        }
+        Commands::Timeline { project, export } => crate::index::timeline::handle_timeline(
+            &cli.root,
+            project.as_deref(),
+            export,
+            cli.dry_run,
+            cli.json,
+        ),
+        Commands::Trends {
+            days,
+            limit,
+            project,
+        } => crate::index::history::handle_trends(
+            &cli.root,
+            days,
+            limit,
+            project.as_deref(),
+            cli.json,
+        ),
+        Commands::Watch { depth, debounce_ms } => {
+            crate::scan::watch::handle_watch(&cli.root, depth, debounce_ms, cli.dry_run, cli.json)
+        }
+        Commands::Index {
+            action: IndexAction::Migrate { check },
+        } => crate::index::migrate::handle_migrate(&cli.root, check, cli.dry_run, cli.json),
+        Commands::Ci {
+            depth,
+            since,
+            jobs,
+            full,
+            no_ignore,
+            loc,
+            reclaim,
+            similarity,
+            no_export,
+        } => {
+            let opts = CensusOptions {
                 depth,
-                since.as_deref(),
+                since,
+                jobs,
+                full,
+                no_ignore,
+                loc,
+                reclaim,
+                similarity,
+                record: true,
+            };
+            crate::scan::census::run_census(
+                &cli.root,
+                &opts,
+                &DeltaOptions::default(),
                 cli.json,
                 None,
             )?;
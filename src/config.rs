@@ -0,0 +1,222 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scan::artifacts::{ArtifactRuleConfig, ArtifactRules};
+
+/// User configuration, layered from `~/.orbit/config.toml` then `<root>/.orbit/config.toml`.
+/// List values are additive: each layer extends the built-in defaults.
+///
+/// ```toml
+/// [scan]
+/// skip_dirs = [".terraform", ".gradle"]
+/// markers = ["go.mod", "pom.xml", "flake.nix"]
+///
+/// [similarity]
+/// threshold = 0.9   # near-duplicate cut-off (0.0–1.0); the last layer that sets it wins
+///
+/// [snapshots]
+/// keep_last = 10         # `orbit snap gc` keeps the newest N snapshots…
+/// keep_daily_days = 30   # …plus the newest snapshot of each day for M days
+///
+/// [artifacts]
+/// defaults = true   # keep the built-in handoff/plan/prompt/transcript/summary/agent rules
+///
+/// [[artifacts.rules]]   # tried before the built-in rules; the first match wins
+/// category = "adr"
+/// glob = "docs/adr/*.md"   # or `regex = "..."` or `extension = "ipynb"`
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrbitConfig {
+    pub scan: ScanConfig,
+    pub similarity: SimilarityConfig,
+    pub snapshots: SnapshotConfig,
+    pub artifacts: ArtifactConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    /// Extra directory names skipped by discovery, summarization and snapshots
+    pub skip_dirs: Vec<String>,
+    /// Extra project marker globs; bare file names match at any depth
+    pub markers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimilarityConfig {
+    /// Minimum estimated similarity for two projects to be grouped as near-duplicates
+    pub threshold: Option<f64>,
+}
+
+impl SimilarityConfig {
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+            .unwrap_or(crate::scan::similarity::DEFAULT_THRESHOLD)
+            .clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// Newest snapshots always kept by `orbit snap gc`
+    pub keep_last: Option<usize>,
+    /// Days for which the newest snapshot of each day is kept
+    pub keep_daily_days: Option<u32>,
+}
+
+impl SnapshotConfig {
+    pub fn retention(&self) -> crate::snapshot::gc::Retention {
+        let d = crate::snapshot::gc::Retention::default();
+        crate::snapshot::gc::Retention {
+            keep_last: self.keep_last.unwrap_or(d.keep_last),
+            keep_daily_days: self.keep_daily_days.unwrap_or(d.keep_daily_days),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactConfig {
+    /// Set to `false` to use only the rules below
+    pub defaults: Option<bool>,
+    pub rules: Vec<ArtifactRuleConfig>,
+}
+
+impl ArtifactConfig {
+    pub fn compile(&self) -> Result<ArtifactRules> {
+        ArtifactRules::new(&self.rules, self.defaults.unwrap_or(true))
+    }
+}
+
+impl OrbitConfig {
+    /// Fold a higher-priority layer into this one
+    fn merge(&mut self, other: OrbitConfig) {
+        extend_unique(&mut self.scan.skip_dirs, other.scan.skip_dirs);
+        extend_unique(&mut self.scan.markers, other.scan.markers);
+        if other.similarity.threshold.is_some() {
+            self.similarity.threshold = other.similarity.threshold;
+        }
+        if other.snapshots.keep_last.is_some() {
+            self.snapshots.keep_last = other.snapshots.keep_last;
+        }
+        if other.snapshots.keep_daily_days.is_some() {
+            self.snapshots.keep_daily_days = other.snapshots.keep_daily_days;
+        }
+        if other.artifacts.defaults.is_some() {
+            self.artifacts.defaults = other.artifacts.defaults;
+        }
+        // Higher-priority rules are tried first
+        let mut rules = other.artifacts.rules;
+        rules.extend(std::mem::take(&mut self.artifacts.rules));
+        self.artifacts.rules = rules;
+    }
+}
+
+fn extend_unique(dst: &mut Vec<String>, src: Vec<String>) {
+    for s in src {
+        if !dst.contains(&s) {
+            dst.push(s);
+        }
+    }
+}
+
+fn home_config_path() -> Option<PathBuf> {
+    env::var("HOME")
+        .ok()
+        .map(|h| PathBuf::from(h).join(".orbit").join("config.toml"))
+}
+
+pub fn root_config_path(root: &Path) -> PathBuf {
+    root.join(".orbit").join("config.toml")
+}
+
+pub fn load_config(root: &Path) -> Result<OrbitConfig> {
+    let layers: Vec<PathBuf> = home_config_path()
+        .into_iter()
+        .chain(std::iter::once(root_config_path(root)))
+        .collect();
+    load_layers(&layers)
+}
+
+/// Load and merge config files in priority order (later wins); missing files are skipped
+fn load_layers(paths: &[PathBuf]) -> Result<OrbitConfig> {
+    let mut cfg = OrbitConfig::default();
+    for p in paths {
+        if !p.is_file() {
+            continue;
+        }
+        let content =
+            fs::read_to_string(p).with_context(|| format!("Failed to read {}", p.display()))?;
+        let layer: OrbitConfig =
+            toml::from_str(&content).with_context(|| format!("Failed to parse {}", p.display()))?;
+        cfg.merge(layer);
+    }
+    Ok(cfg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load_layers;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn layers_extend_and_dedup() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().join("home.toml");
+        let root = tmp.path().join("root.toml");
+        fs::write(
+            &home,
+            "[scan]\nskip_dirs = [\".gradle\"]\nmarkers = [\"go.mod\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            &root,
+            "[scan]\nskip_dirs = [\".terraform\", \".gradle\"]\n[similarity]\nthreshold = 0.7\n",
+        )
+        .unwrap();
+
+        let cfg = load_layers(&[home, root, tmp.path().join("missing.toml")]).unwrap();
+        assert_eq!(cfg.scan.skip_dirs, vec![".gradle", ".terraform"]);
+        assert_eq!(cfg.scan.markers, vec!["go.mod"]);
+        assert_eq!(cfg.similarity.threshold(), 0.7);
+    }
+
+    #[test]
+    fn root_artifact_rules_take_precedence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().join("home.toml");
+        let root = tmp.path().join("root.toml");
+        fs::write(
+            &home,
+            "[[artifacts.rules]]\ncategory = \"notes\"\nglob = \"*.md\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &root,
+            "[artifacts]\ndefaults = false\n[[artifacts.rules]]\ncategory = \"adr\"\nglob = \"adr/*.md\"\n",
+        )
+        .unwrap();
+
+        let cfg = load_layers(&[home, root]).unwrap();
+        let rules = cfg.artifacts.compile().unwrap();
+        assert_eq!(rules.category(Path::new("adr/0001.md")), Some("adr"));
+        assert_eq!(rules.category(Path::new("HANDOFF.md")), Some("notes"));
+        assert_eq!(rules.category(Path::new("HANDOFF.txt")), None);
+    }
+
+    #[test]
+    fn invalid_config_is_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let p = tmp.path().join("config.toml");
+        fs::write(&p, "[scan]\nskip_dirs = \"not-a-list\"\n").unwrap();
+        let err = load_layers(&[p]).unwrap_err().to_string();
+        assert!(err.contains("Failed to parse"));
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use std::fs;
 use std::path::Path;
 
-use crate::index::store;
+use crate::index::{catalog, store, timeline};
 
 pub fn export_all(root_str: &str, dry_run: bool) -> Result<()> {
     let root = Path::new(root_str);
@@ -14,14 +14,29 @@ pub fn export_all(root_str: &str, dry_run: bool) -> Result<()> {
         println!("  - summary.md");
         println!("  - index.json");
         println!("  - index.csv");
+        println!("  - loc.csv");
+        println!("  - timelines/<project>/timeline.md (pinned projects)");
         return Ok(());
     }
 
     fs::create_dir_all(&out)
         .with_context(|| format!("Failed to create exports directory {}", out.display()))?;
 
+    let mut summary = crate::export::md::render_md(&idx)?;
+    let this_root = store::root_key(root);
+    let others: Vec<_> = store::load_shared()?
+        .summaries()
+        .into_iter()
+        .filter(|r| r.root != this_root)
+        .collect();
+    if !others.is_empty() {
+        summary.push_str("\n## Other indexed roots\n");
+        for r in others {
+            summary.push_str(&format!("- `{}`: {} projects\n", r.root, r.projects));
+        }
+    }
     let md_path = out.join("summary.md");
-    fs::write(&md_path, crate::export::md::render_md(&idx)?)
+    fs::write(&md_path, summary)
         .with_context(|| format!("Failed to write {}", md_path.display()))?;
 
     let json_path = out.join("index.json");
@@ -39,13 +54,23 @@ pub fn export_all(root_str: &str, dry_run: bool) -> Result<()> {
         "latest_mtime",
         "size_bytes",
         "artifact_count",
+        "artifacts",
         "has_git",
         "has_rust",
         "has_node",
         "has_python",
+        "ecosystems",
         "fingerprint",
+        "parent",
+        "members",
+        "git_branch",
+        "git_dirty",
+        "git_untracked",
+        "git_ahead",
+        "git_behind",
     ])?;
     for p in &idx.projects {
+        let git = p.git.as_ref();
         wtr.write_record([
             p.path.as_str(),
             format!("{:?}", p.kind).as_str(),
@@ -58,17 +83,72 @@ pub fn export_all(root_str: &str, dry_run: bool) -> Result<()> {
                 .map(|n| n.to_string())
                 .unwrap_or_default()
                 .as_str(),
-            p.artifact_count.to_string().as_str(),
+            p.artifact_count().to_string().as_str(),
+            p.artifacts
+                .iter()
+                .map(|(c, n)| format!("{c}:{n}"))
+                .collect::<Vec<_>>()
+                .join(";")
+                .as_str(),
             p.has_git.to_string().as_str(),
-            p.has_rust.to_string().as_str(),
-            p.has_node.to_string().as_str(),
-            p.has_python.to_string().as_str(),
+            p.has_ecosystem("rust").to_string().as_str(),
+            p.has_ecosystem("node").to_string().as_str(),
+            p.has_ecosystem("python").to_string().as_str(),
+            p.ecosystems.join(";").as_str(),
             p.fingerprint.clone().unwrap_or_default().as_str(),
+            p.parent.clone().unwrap_or_default().as_str(),
+            p.members.join(";").as_str(),
+            git.and_then(|g| g.branch.clone())
+                .unwrap_or_default()
+                .as_str(),
+            git.map(|g| g.dirty.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            git.map(|g| g.untracked.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            git.and_then(|g| g.ahead)
+                .map(|n| n.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            git.and_then(|g| g.behind)
+                .map(|n| n.to_string())
+                .unwrap_or_default()
+                .as_str(),
         ])?;
     }
     let csv_path = out.join("index.csv");
     fs::write(&csv_path, wtr.into_inner()?)
         .with_context(|| format!("Failed to write {}", csv_path.display()))?;
+
+    let loc_path = out.join("loc.csv");
+    fs::write(&loc_path, render_loc_csv(&idx)?)
+        .with_context(|| format!("Failed to write {}", loc_path.display()))?;
+
+    let cat = match catalog::load(root)? {
+        Some(c) => c,
+        None => catalog::refresh(root)?.0,
+    };
+    timeline::export_pinned(root, &cat)?;
     println!("Exported to {}", out.display());
     Ok(())
 }
+
+/// One row per (project, language); projects censused without `--loc` are omitted
+fn render_loc_csv(idx: &store::OrbitIndex) -> Result<Vec<u8>> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(["path", "language", "files", "code", "comment", "blank"])?;
+    for p in &idx.projects {
+        for (lang, c) in p.loc.iter().flatten() {
+            wtr.write_record([
+                p.path.as_str(),
+                lang.as_str(),
+                c.files.to_string().as_str(),
+                c.code.to_string().as_str(),
+                c.comment.to_string().as_str(),
+                c.blank.to_string().as_str(),
+            ])?;
+        }
+    }
+    Ok(wtr.into_inner()?)
+}
@@ -33,11 +33,17 @@ pub fn render_md(idx: &OrbitIndex) -> Result<String> {
     ps.sort_by_key(|p| p.latest_mtime);
     ps.reverse();
     for p in ps.iter().take(50) {
+        let eco = if p.ecosystems.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", p.ecosystems.join(", "))
+        };
         s.push_str(&format!(
-            "- {} {} — {:?}\n",
+            "- {} {} — {:?}{}\n",
             if p.pinned { "★" } else { " " },
             p.path,
-            p.kind
+            p.kind,
+            eco
         ));
     }
     Ok(s)
@@ -33,7 +33,7 @@ static FLAGS: Lazy<FeatureFlags> = Lazy::new(FeatureFlags::from_env);
 
 /// Accessor for current feature flags (cached from environment).
 pub fn flags() -> &'static FeatureFlags {
-    &*FLAGS
+    &FLAGS
 }
 
 /// For tests: produce a fresh snapshot of flags from current env (not cached).
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::index::search::{self, SearchIndex};
+use crate::index::store::{self, atomic_write, OrbitIndex};
+use crate::index::timeline::{self, DateSource};
+use crate::scan::rules::{self, ScanRules};
+use crate::scan::similarity::rel_key;
+
+/// Artifacts larger than this are catalogued but not read for titles or search
+const MAX_READ_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Every artifact file in the indexed projects, stored at `<root>/.orbit/artifacts.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArtifactCatalog {
+    pub generated_at: Option<DateTime<Local>>,
+    pub artifacts: Vec<ArtifactRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArtifactRecord {
+    pub project: String,
+    /// Path relative to `project`, `/`-separated
+    pub path: String,
+    pub category: String,
+    /// First Markdown heading, if any
+    pub title: Option<String>,
+    pub size: u64,
+    pub mtime: Option<DateTime<Local>>,
+    /// Date parsed from the file name or a heading, for timelines
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    #[serde(default)]
+    pub date_source: Option<DateSource>,
+}
+
+impl ArtifactRecord {
+    /// Workspace-relative path
+    pub fn key(&self) -> String {
+        match self.project.as_str() {
+            "" | "." => self.path.clone(),
+            p => format!("{}/{}", p, self.path),
+        }
+    }
+
+    /// Title, falling back to the file name
+    pub fn label(&self) -> &str {
+        self.title
+            .as_deref()
+            .unwrap_or_else(|| self.path.rsplit('/').next().unwrap_or(&self.path))
+    }
+}
+
+pub fn catalog_path(root: &Path) -> PathBuf {
+    root.join(".orbit").join("artifacts.json")
+}
+
+/// First ATX heading (`# Title`), trimmed of its markers
+pub fn first_heading(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .find(|l| l.starts_with('#'))
+        .map(|l| {
+            l.trim_matches('#')
+                .trim()
+                .chars()
+                .take(120)
+                .collect::<String>()
+        })
+        .filter(|t| !t.is_empty())
+}
+
+/// Walk every indexed project that has artifacts and catalog them, feeding
+/// their contents into a fresh search index
+pub fn build(
+    root: &Path,
+    idx: &OrbitIndex,
+    rules: &ScanRules,
+) -> Result<(ArtifactCatalog, SearchIndex)> {
+    let mut cat = ArtifactCatalog {
+        generated_at: Some(Local::now()),
+        artifacts: vec![],
+    };
+    let mut search = SearchIndex::new();
+    for p in idx.projects.iter().filter(|p| p.artifact_count() > 0) {
+        let project_root = root.join(&p.path);
+        if !project_root.is_dir() {
+            continue;
+        }
+        // Nested projects are catalogued on their own, as in census
+        let children: Vec<PathBuf> = idx
+            .projects
+            .iter()
+            .filter(|c| c.parent.as_deref() == Some(p.path.as_str()))
+            .map(|c| root.join(&c.path))
+            .collect();
+        for item in rules.walker(&project_root, &children).build() {
+            let Some(entry) = rules::walk_entry(item, &project_root)? else {
+                continue;
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let f = entry.path();
+            let rel = rel_key(&project_root, f);
+            let Some(category) = rules.artifact_category(Path::new(&rel)) else {
+                continue;
+            };
+            let md = entry
+                .metadata()
+                .with_context(|| format!("Failed to read metadata for {}", f.display()))?;
+            let mut rec = ArtifactRecord {
+                project: p.path.clone(),
+                path: rel,
+                category: category.to_string(),
+                title: None,
+                size: md.len(),
+                mtime: md.modified().ok().map(DateTime::<Local>::from),
+                date: None,
+                date_source: None,
+            };
+            if md.len() <= MAX_READ_BYTES {
+                let bytes =
+                    fs::read(f).with_context(|| format!("Failed to read {}", f.display()))?;
+                let text = String::from_utf8_lossy(&bytes);
+                rec.title = first_heading(&text);
+                let name = rec.path.rsplit('/').next().unwrap_or(&rec.path);
+                if let Some((d, src)) = timeline::artifact_date(name, &text) {
+                    rec.date = Some(d);
+                    rec.date_source = Some(src);
+                }
+                search.add(&rec.key(), &text);
+            }
+            cat.artifacts.push(rec);
+        }
+    }
+    Ok((cat, search))
+}
+
+pub fn load(root: &Path) -> Result<Option<ArtifactCatalog>> {
+    let p = catalog_path(root);
+    if !p.is_file() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse {}", p.display()))
+}
+
+/// Persist the catalog and its search index side by side
+pub fn save(root: &Path, cat: &ArtifactCatalog, search_idx: &SearchIndex) -> Result<()> {
+    let dir = root.join(".orbit");
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let content =
+        serde_json::to_string_pretty(cat).context("Failed to serialize artifact catalog")?;
+    atomic_write(&catalog_path(root), &content)?;
+    search::save(root, search_idx)
+}
+
+/// Rebuild from the current index and config (used when no census has written one yet)
+pub fn refresh(root: &Path) -> Result<(ArtifactCatalog, SearchIndex)> {
+    let idx = store::load(root)?;
+    let rules = ScanRules::from_config(&config::load_config(root)?)?;
+    let (cat, search_idx) = build(root, &idx, &rules)?;
+    save(root, &cat, &search_idx)?;
+    Ok((cat, search_idx))
+}
+
+pub fn handle_artifacts(
+    root_str: &str,
+    category: Option<&str>,
+    project: Option<&str>,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+    let cat = match load(root)? {
+        Some(c) => c,
+        None => refresh(root)?.0,
+    };
+    let mut list: Vec<&ArtifactRecord> = cat
+        .artifacts
+        .iter()
+        .filter(|a| category.is_none_or(|c| a.category == c))
+        .filter(|a| project.is_none_or(|p| a.project == p))
+        .collect();
+    list.sort_by(|a, b| b.mtime.cmp(&a.mtime).then(a.key().cmp(&b.key())));
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "generated_at": cat.generated_at,
+                "total": list.len(),
+                "artifacts": list,
+            })
+        );
+        return Ok(());
+    }
+    if list.is_empty() {
+        println!("No artifacts catalogued; run `orbit census` first.");
+        return Ok(());
+    }
+    for a in &list {
+        println!(
+            "{}  {:<10} {:>8}  {}  {}",
+            a.mtime
+                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "N/A".into()),
+            a.category,
+            a.size,
+            a.key(),
+            a.title.as_deref().unwrap_or("")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build, first_heading};
+    use crate::index::store::OrbitIndex;
+    use crate::model::project::ProjectEntry;
+    use crate::scan::rules::ScanRules;
+    use std::fs;
+
+    fn entry(path: &str, parent: Option<&str>) -> ProjectEntry {
+        ProjectEntry {
+            artifacts: [("handoff".to_string(), 1)].into(),
+            parent: parent.map(String::from),
+            ..ProjectEntry::stub(path)
+        }
+    }
+
+    #[test]
+    fn headings_become_titles() {
+        assert_eq!(
+            first_heading("intro\n## Handoff: auth rewrite ##\n# Later"),
+            Some("Handoff: auth rewrite".into())
+        );
+        assert_eq!(first_heading("no headings here"), None);
+        assert_eq!(first_heading("#\ntext"), None);
+    }
+
+    #[test]
+    fn catalog_covers_each_project_once_and_feeds_search() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("app/svc")).unwrap();
+        fs::write(root.join("app/HANDOFF.md"), "# Auth handoff\nrotate tokens").unwrap();
+        fs::write(root.join("app/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("app/svc/plan.md"), "migrate tokens").unwrap();
+        let idx = OrbitIndex {
+            projects: vec![entry("app", None), entry("app/svc", Some("app"))],
+            ..Default::default()
+        };
+
+        let (cat, search) = build(root, &idx, &ScanRules::default()).unwrap();
+        let keys: Vec<String> = cat.artifacts.iter().map(|a| a.key()).collect();
+        assert_eq!(keys, vec!["app/HANDOFF.md", "app/svc/plan.md"]);
+        assert_eq!(cat.artifacts[0].title.as_deref(), Some("Auth handoff"));
+        assert_eq!(cat.artifacts[1].category, "plan");
+        assert_eq!(cat.artifacts[1].label(), "plan.md");
+        assert_eq!(search.query("tokens").len(), 2);
+        assert_eq!(search.query("rotate")[0].key, "app/HANDOFF.md");
+    }
+}
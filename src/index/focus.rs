@@ -4,6 +4,8 @@ use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::index::journal::{self, Target};
+use crate::index::lock::{self, FileLock};
 use crate::index::store::atomic_write;
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -11,6 +13,16 @@ pub struct Focus {
     pub pinned: Vec<String>,
 }
 
+impl Focus {
+    /// The pin that covers `path`: the path itself, one of its ancestors, or `.`
+    pub fn pin_covering(&self, path: &str) -> Option<&str> {
+        self.pinned
+            .iter()
+            .map(|p| p.as_str())
+            .find(|p| *p == path || *p == "." || Path::new(path).starts_with(p))
+    }
+}
+
 fn home_focus_path() -> Option<PathBuf> {
     env::var("HOME").ok().map(|h| PathBuf::from(h).join(".orbit").join("focus.json"))
 }
@@ -36,8 +48,14 @@ pub fn load_focus(root: &Path) -> Result<Focus> {
     Ok(Focus::default())
 }
 
+/// Lock focus for a read-modify-write spanning [`load_focus`] and [`save_focus`]
+pub fn lock(root: &Path) -> Result<FileLock> {
+    lock::acquire(&home_focus_path().unwrap_or_else(|| root.join(".orbit").join("focus.json")))
+}
+
 pub fn save_focus(root: &Path, f: &Focus) -> Result<()> {
     let content = serde_json::to_string_pretty(f).context("Failed to serialize focus")?;
+    let _lock = lock(root)?;
 
     // Primary: home-level shared file
     if let Some(home_path) = home_focus_path() {
@@ -65,6 +83,7 @@ pub fn handle_focus(
     json_output: bool,
 ) -> Result<()> {
     let root = Path::new(root_str);
+    let _lock = lock(root)?;
     let mut f = load_focus(root)?;
     if list {
         if json_output {
@@ -82,11 +101,13 @@ pub fn handle_focus(
         if !full_path.exists() {
             anyhow::bail!("Path does not exist: {}", full_path.display());
         }
+        let before = f.clone();
         if !f.pinned.iter().any(|x| x == &a) {
             f.pinned.push(a);
             f.pinned.sort();
         }
         save_focus(root, &f)?;
+        record_change(root, "focus add", &before, &f)?;
         if json_output {
             println!(
                 "{}",
@@ -97,8 +118,10 @@ pub fn handle_focus(
         }
     }
     if let Some(r) = remove {
+        let before = f.clone();
         f.pinned.retain(|x| x != &r);
         save_focus(root, &f)?;
+        record_change(root, "focus remove", &before, &f)?;
         if json_output {
             println!(
                 "{}",
@@ -110,3 +133,16 @@ pub fn handle_focus(
     }
     Ok(())
 }
+
+/// Journal a focus change so `orbit undo` can revert it
+pub fn record_change(root: &Path, op: &str, before: &Focus, after: &Focus) -> Result<()> {
+    journal::record(
+        root,
+        op,
+        Target::Focus,
+        journal::list_summary(&before.pinned, &after.pinned),
+        before,
+        after,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,471 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::index::lock;
+use crate::index::store::{root_key, OrbitIndex};
+use crate::model::project::{size_label, ProjectKind};
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One project as seen by one census
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub path: String,
+    pub kind: ProjectKind,
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
+    #[serde(default)]
+    pub latest_mtime: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub artifacts: u32,
+}
+
+/// One census run: a line of `~/.orbit/history/<YYYY-MM>.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRun {
+    pub at: DateTime<Local>,
+    /// Canonical root the census ran on
+    pub root: String,
+    pub projects: Vec<HistoryRecord>,
+}
+
+impl HistoryRun {
+    pub fn from_index(root: &Path, idx: &OrbitIndex, at: DateTime<Local>) -> Self {
+        Self {
+            at,
+            root: root_key(root),
+            projects: idx
+                .projects
+                .iter()
+                .map(|p| HistoryRecord {
+                    path: p.path.clone(),
+                    kind: p.kind.clone(),
+                    size_bytes: p.size_bytes,
+                    latest_mtime: p.latest_mtime,
+                    artifacts: p.artifact_count(),
+                })
+                .collect(),
+        }
+    }
+
+    fn get(&self, path: &str) -> Option<&HistoryRecord> {
+        self.projects.iter().find(|p| p.path == path)
+    }
+}
+
+/// `~/.orbit/history/`, or `<root>/.orbit/history/` without a home directory
+pub fn history_dir(root: &Path) -> PathBuf {
+    env::var("HOME")
+        .map(|h| PathBuf::from(h).join(".orbit"))
+        .unwrap_or_else(|_| root.join(".orbit"))
+        .join("history")
+}
+
+/// Record the freshly saved index as a new run
+pub fn append(root: &Path, idx: &OrbitIndex) -> Result<()> {
+    append_to(
+        &history_dir(root),
+        &HistoryRun::from_index(root, idx, Local::now()),
+    )
+}
+
+fn append_to(dir: &Path, run: &HistoryRun) -> Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let p = dir.join(format!("{}.jsonl", run.at.format("%Y-%m")));
+    let mut line = serde_json::to_string(run).context("Failed to serialize history run")?;
+    line.push('\n');
+    // Censuses of other roots append to the same monthly file
+    let _lock = lock::acquire(&p)?;
+    let mut f = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(&p)
+        .with_context(|| format!("Failed to open {}", p.display()))?;
+    // Start on a fresh line after a tail cut short by a crash
+    let mut last = [0u8; 1];
+    let len = f
+        .metadata()
+        .with_context(|| format!("Failed to read metadata for {}", p.display()))?
+        .len();
+    if len > 0
+        && f.seek(SeekFrom::Start(len - 1))
+            .and_then(|_| f.read_exact(&mut last))
+            .is_ok()
+        && last[0] != b'\n'
+    {
+        line.insert(0, '\n');
+    }
+    f.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to append to {}", p.display()))
+}
+
+/// Runs for `root` since `since`, oldest first, and a description of every
+/// line that couldn't be parsed (skipped)
+pub fn load(root: &Path, since: Option<DateTime<Local>>) -> Result<(Vec<HistoryRun>, Vec<String>)> {
+    load_from(&history_dir(root), &root_key(root), since)
+}
+
+fn load_from(
+    dir: &Path,
+    root: &str,
+    since: Option<DateTime<Local>>,
+) -> Result<(Vec<HistoryRun>, Vec<String>)> {
+    if !dir.is_dir() {
+        return Ok((vec![], vec![]));
+    }
+    let first_month = since.map(|s| s.format("%Y-%m").to_string());
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == "jsonl"))
+        .filter(|p| {
+            let month = p.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            first_month.as_deref().is_none_or(|m| month >= m)
+        })
+        .collect();
+    files.sort();
+
+    let mut runs = Vec::new();
+    let mut skipped = Vec::new();
+    for p in files {
+        let content =
+            fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+        for (n, l) in content
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+        {
+            let run: HistoryRun = match serde_json::from_str(l) {
+                Ok(run) => run,
+                Err(e) => {
+                    skipped.push(format!("{} line {}: {}", p.display(), n + 1, e));
+                    continue;
+                }
+            };
+            if run.root == root && since.is_none_or(|s| run.at >= s) {
+                runs.push(run);
+            }
+        }
+    }
+    runs.sort_by_key(|r| r.at);
+    Ok((runs, skipped))
+}
+
+/// How one project evolved over a series of runs
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectTrend {
+    pub path: String,
+    /// Size at each run, oldest first (0 where the project was absent)
+    pub sizes: Vec<u64>,
+    /// Per run: whether the project's newest file changed since the previous run
+    pub activity: Vec<bool>,
+    pub first_size: u64,
+    pub last_size: u64,
+    pub size_delta: i64,
+    pub last_activity: Option<DateTime<Local>>,
+    pub kind: ProjectKind,
+    /// Absent from the first run
+    pub new: bool,
+    /// Absent from the latest run
+    pub gone: bool,
+    /// Classified active when first seen, no longer active now
+    pub went_stale: bool,
+}
+
+pub fn project_trend(runs: &[HistoryRun], path: &str) -> Option<ProjectTrend> {
+    let seen: Vec<Option<&HistoryRecord>> = runs.iter().map(|r| r.get(path)).collect();
+    let first = seen.iter().flatten().next()?;
+    let last = seen.iter().flatten().last()?;
+    let size = |r: &HistoryRecord| r.size_bytes.unwrap_or(0);
+    let activity = seen
+        .iter()
+        .enumerate()
+        .map(|(i, cur)| {
+            i > 0 && cur.is_some_and(|c| c.latest_mtime > seen[i - 1].and_then(|p| p.latest_mtime))
+        })
+        .collect();
+    let is_active = |r: &HistoryRecord| matches!(r.kind, ProjectKind::ActiveStandalone);
+    let gone = seen.last().is_some_and(|r| r.is_none());
+    Some(ProjectTrend {
+        path: path.to_string(),
+        sizes: seen.iter().map(|r| r.map(size).unwrap_or(0)).collect(),
+        activity,
+        first_size: size(first),
+        last_size: size(last),
+        size_delta: size(last) as i64 - size(first) as i64,
+        last_activity: last.latest_mtime,
+        kind: last.kind.clone(),
+        new: runs.len() > 1 && seen[0].is_none(),
+        gone,
+        went_stale: !gone && is_active(first) && !is_active(last),
+    })
+}
+
+/// Trends of every project seen in `runs`, largest size change first
+pub fn trends(runs: &[HistoryRun]) -> Vec<ProjectTrend> {
+    let paths: BTreeSet<&str> = runs
+        .iter()
+        .flat_map(|r| r.projects.iter().map(|p| p.path.as_str()))
+        .collect();
+    let mut out: Vec<ProjectTrend> = paths
+        .into_iter()
+        .filter_map(|p| project_trend(runs, p))
+        .collect();
+    out.sort_by(|a, b| {
+        b.size_delta
+            .unsigned_abs()
+            .cmp(&a.size_delta.unsigned_abs())
+            .then(a.path.cmp(&b.path))
+    });
+    out
+}
+
+/// `▁▂▅█`, scaled between the smallest and largest value
+pub fn sparkline(values: &[u64]) -> String {
+    let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let span = (max - min).max(1) as f64;
+    values
+        .iter()
+        .map(|v| BARS[(((v - min) as f64 / span) * (BARS.len() - 1) as f64).round() as usize])
+        .collect()
+}
+
+/// `·█··█`: one mark per run, full where the project changed
+pub fn activity_line(activity: &[bool]) -> String {
+    activity
+        .iter()
+        .map(|a| if *a { '█' } else { '·' })
+        .collect()
+}
+
+pub fn signed_size(delta: i64) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, size_label(delta.unsigned_abs()))
+}
+
+pub fn handle_trends(
+    root_str: &str,
+    days: i64,
+    limit: usize,
+    project: Option<&str>,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+    let since = Local::now() - Duration::days(days);
+    let (runs, skipped) = load(root, Some(since))?;
+    let mut all = trends(&runs);
+    if let Some(p) = project {
+        let p = p.trim_end_matches('/');
+        all.retain(|t| t.path == p);
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "root": root_key(root),
+                "days": days,
+                "runs": runs.iter().map(|r| r.at).collect::<Vec<_>>(),
+                "trends": all,
+                "skipped_lines": skipped,
+            })
+        );
+        return Ok(());
+    }
+    for s in &skipped {
+        println!("Skipped unreadable history line: {}", s);
+    }
+    if runs.len() < 2 {
+        println!(
+            "{} census run(s) recorded in the last {} days; trends need at least two (run `orbit census` again later).",
+            runs.len(),
+            days
+        );
+        return Ok(());
+    }
+    println!(
+        "Trends over {} days: {} census runs since {}",
+        days,
+        runs.len(),
+        runs[0].at.format("%Y-%m-%d %H:%M")
+    );
+
+    let changed: Vec<&ProjectTrend> = all
+        .iter()
+        .filter(|t| t.size_delta != 0 && !t.gone)
+        .take(limit)
+        .collect();
+    if !changed.is_empty() {
+        println!("\nSize changes:");
+        for t in changed {
+            println!(
+                "  {:>11}  {:<32} {}  ({} → {})",
+                signed_size(t.size_delta),
+                t.path,
+                sparkline(&t.sizes),
+                size_label(t.first_size),
+                size_label(t.last_size)
+            );
+        }
+    }
+    let list = |title: &str, ts: Vec<&ProjectTrend>| {
+        if ts.is_empty() {
+            return;
+        }
+        println!("\n{}:", title);
+        for t in ts {
+            println!(
+                "  {:<32} last change {}  {}",
+                t.path,
+                t.last_activity
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .unwrap_or_else(|| "N/A".into()),
+                activity_line(&t.activity)
+            );
+        }
+    };
+    list("Went stale", all.iter().filter(|t| t.went_stale).collect());
+    list("New", all.iter().filter(|t| t.new && !t.gone).collect());
+    list("Gone", all.iter().filter(|t| t.gone).collect());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{activity_line, append_to, load_from, project_trend, sparkline, trends};
+    use super::{HistoryRecord, HistoryRun};
+    use crate::model::project::ProjectKind;
+    use chrono::{DateTime, Local, TimeZone};
+
+    fn day(d: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 3, d, 12, 0, 0).unwrap()
+    }
+
+    fn rec(path: &str, kind: ProjectKind, size: u64, mtime_day: u32) -> HistoryRecord {
+        HistoryRecord {
+            path: path.into(),
+            kind,
+            size_bytes: Some(size),
+            latest_mtime: Some(day(mtime_day)),
+            artifacts: 0,
+        }
+    }
+
+    fn run(d: u32, projects: Vec<HistoryRecord>) -> HistoryRun {
+        HistoryRun {
+            at: day(d),
+            root: "/ws".into(),
+            projects,
+        }
+    }
+
+    #[test]
+    fn runs_round_trip_per_root_and_window() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("history");
+        append_to(&dir, &run(1, vec![])).unwrap();
+        append_to(&dir, &run(5, vec![])).unwrap();
+        append_to(
+            &dir,
+            &HistoryRun {
+                root: "/other".into(),
+                ..run(6, vec![])
+            },
+        )
+        .unwrap();
+        let (all, skipped) = load_from(&dir, "/ws", None).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(
+            all.iter().map(|r| r.at).collect::<Vec<_>>(),
+            vec![day(1), day(5)]
+        );
+        assert_eq!(load_from(&dir, "/ws", Some(day(3))).unwrap().0.len(), 1);
+        assert!(load_from(&tmp.path().join("none"), "/ws", None)
+            .unwrap()
+            .0
+            .is_empty());
+    }
+
+    #[test]
+    fn a_torn_line_is_skipped_and_later_runs_still_load() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("history");
+        append_to(&dir, &run(1, vec![])).unwrap();
+        let file = dir.join("2025-03.jsonl");
+        let mut f = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .unwrap();
+        std::io::Write::write_all(&mut f, b"{\"at\":\"2025-03-02T1").unwrap();
+        append_to(&dir, &run(3, vec![])).unwrap();
+
+        let (runs, skipped) = load_from(&dir, "/ws", None).unwrap();
+        assert_eq!(
+            runs.iter().map(|r| r.at).collect::<Vec<_>>(),
+            vec![day(1), day(3)]
+        );
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].contains("line 2"), "{}", skipped[0]);
+    }
+
+    #[test]
+    fn trends_track_growth_activity_and_staleness() {
+        use ProjectKind::{ActiveStandalone, Standalone};
+        let runs = vec![
+            run(
+                1,
+                vec![
+                    rec("app", ActiveStandalone, 100, 1),
+                    rec("api", ActiveStandalone, 50, 1),
+                    rec("old", Standalone, 10, 1),
+                ],
+            ),
+            run(
+                2,
+                vec![
+                    rec("app", ActiveStandalone, 400, 2),
+                    rec("api", ActiveStandalone, 50, 1),
+                    rec("old", Standalone, 10, 1),
+                ],
+            ),
+            run(
+                3,
+                vec![
+                    rec("app", ActiveStandalone, 900, 3),
+                    rec("api", Standalone, 50, 1),
+                    rec("lib", ActiveStandalone, 5, 3),
+                ],
+            ),
+        ];
+        let app = project_trend(&runs, "app").unwrap();
+        assert_eq!(app.sizes, vec![100, 400, 900]);
+        assert_eq!(app.size_delta, 800);
+        assert_eq!(activity_line(&app.activity), "·██");
+        assert!(!app.went_stale && !app.new && !app.gone);
+
+        let all = trends(&runs);
+        assert_eq!(all[0].path, "app");
+        let get = |p: &str| all.iter().find(|t| t.path == p).unwrap();
+        assert!(get("api").went_stale);
+        assert!(get("old").gone && !get("old").went_stale);
+        assert!(get("lib").new);
+        assert!(project_trend(&runs, "missing").is_none());
+    }
+
+    #[test]
+    fn sparklines_scale_to_range() {
+        assert_eq!(sparkline(&[0, 50, 100]), "▁▅█");
+        assert_eq!(sparkline(&[7, 7]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
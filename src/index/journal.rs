@@ -0,0 +1,668 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cleanup::archive;
+use crate::index::focus::{self, Focus};
+use crate::index::lock::{self, FileLock};
+use crate::index::store::{self, atomic_write, OrbitIndex};
+use crate::index::whitelist::{self, Whitelist};
+use crate::model::project::sync_pinned_flags;
+
+/// State a journal entry describes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    Focus,
+    Whitelist,
+    Index,
+    Archive,
+}
+
+/// Index states of this many recent entries are kept for `undo`; older index
+/// entries stay in the journal but can no longer be reverted
+pub const INDEX_SNAPSHOTS_KEPT: usize = 20;
+
+/// One line of `.orbit/journal.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub at: DateTime<Local>,
+    /// e.g. `focus add`, `census`, `archive`, `undo`
+    pub op: String,
+    pub target: Target,
+    /// Human-readable change, e.g. `+proj1` or `12 → 14 projects`
+    pub summary: String,
+    /// Full state, or for the index `{"snapshot": "<file>"}` naming a file
+    /// under `.orbit/journal/` (see [`resolve`])
+    pub before: Value,
+    pub after: Value,
+    /// For `undo` entries: the id of the entry that was reverted
+    #[serde(default)]
+    pub undoes: Option<u64>,
+}
+
+/// Archive operations store this instead of the full tree
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ArchiveState {
+    /// Archive id holding the projects, when they are archived
+    pub archive_id: Option<String>,
+    pub paths: Vec<String>,
+}
+
+pub fn journal_path(root: &Path) -> PathBuf {
+    root.join(".orbit").join("journal.jsonl")
+}
+
+/// Index states referenced by journal entries
+pub fn snapshot_dir(root: &Path) -> PathBuf {
+    root.join(".orbit").join("journal")
+}
+
+/// Lock the journal while allocating an id and appending. Taken last: callers
+/// already hold the lock of the state they journal.
+pub fn lock(root: &Path) -> Result<FileLock> {
+    lock::acquire(&journal_path(root))
+}
+
+pub fn load_journal(root: &Path) -> Result<Vec<JournalEntry>> {
+    let p = journal_path(root);
+    if !p.exists() {
+        return Ok(vec![]);
+    }
+    let content =
+        fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(n, l)| {
+            serde_json::from_str(l)
+                .with_context(|| format!("Failed to parse {} line {}", p.display(), n + 1))
+        })
+        .collect()
+}
+
+/// Id of the last entry, reading only the end of the journal
+fn last_id(p: &Path) -> Result<Option<u64>> {
+    #[derive(Deserialize)]
+    struct Id {
+        id: u64,
+    }
+    let mut f = match File::open(p) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {}", p.display())),
+    };
+    let len = f
+        .metadata()
+        .with_context(|| format!("Failed to read metadata for {}", p.display()))?
+        .len();
+    // Read backwards until the tail holds a whole last line
+    let mut tail: Vec<u8> = Vec::new();
+    let mut pos = len;
+    loop {
+        let trimmed = tail.trim_ascii_end();
+        if let Some(nl) = trimmed.iter().rposition(|&b| b == b'\n') {
+            tail = trimmed[nl + 1..].to_vec();
+            break;
+        }
+        if pos == 0 {
+            tail = trimmed.to_vec();
+            break;
+        }
+        let n = pos.min(8192);
+        pos -= n;
+        let mut chunk = vec![0; n as usize];
+        f.seek(SeekFrom::Start(pos))
+            .and_then(|_| f.read_exact(&mut chunk))
+            .with_context(|| format!("Failed to read {}", p.display()))?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+    }
+    if tail.is_empty() {
+        return Ok(None);
+    }
+    let last: Id = serde_json::from_slice(&tail)
+        .with_context(|| format!("Failed to parse the last line of {}", p.display()))?;
+    Ok(Some(last.id))
+}
+
+/// Append an entry; returns its id. Nothing is journaled when `before` and
+/// `after` are equal, so `orbit undo` never spends itself on a no-op.
+pub fn record(
+    root: &Path,
+    op: &str,
+    target: Target,
+    summary: String,
+    before: &impl Serialize,
+    after: &impl Serialize,
+) -> Result<Option<u64>> {
+    let before = serde_json::to_value(before).context("Failed to serialize journal state")?;
+    let after = serde_json::to_value(after).context("Failed to serialize journal state")?;
+    if before == after {
+        return Ok(None);
+    }
+    append(root, op, target, summary, before, after, None).map(|e| Some(e.id))
+}
+
+fn append(
+    root: &Path,
+    op: &str,
+    target: Target,
+    summary: String,
+    before: Value,
+    after: Value,
+    undoes: Option<u64>,
+) -> Result<JournalEntry> {
+    let _lock = lock(root)?;
+    let p = journal_path(root);
+    let id = last_id(&p)?.map(|id| id + 1).unwrap_or(1);
+    // Index states are large; keep them out of line and only for recent entries
+    let (before, after) = if target == Target::Index {
+        let refs = (
+            store_snapshot(root, id, "before", &before)?,
+            store_snapshot(root, id, "after", &after)?,
+        );
+        prune_snapshots(root)?;
+        refs
+    } else {
+        (before, after)
+    };
+    let entry = JournalEntry {
+        id,
+        at: Local::now(),
+        op: op.to_string(),
+        target,
+        summary,
+        before,
+        after,
+        undoes,
+    };
+    if let Some(parent) = p.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    let mut line = serde_json::to_string(&entry).context("Failed to serialize journal entry")?;
+    line.push('\n');
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&p)
+        .with_context(|| format!("Failed to open {}", p.display()))?;
+    f.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to append to {}", p.display()))?;
+    Ok(entry)
+}
+
+/// Write `<id>.<side>.json` under [`snapshot_dir`]; returns the reference to it
+fn store_snapshot(root: &Path, id: u64, side: &str, state: &Value) -> Result<Value> {
+    let dir = snapshot_dir(root);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let name = format!("{}.{}.json", id, side);
+    let content = serde_json::to_string(state).context("Failed to serialize journal state")?;
+    atomic_write(&dir.join(&name), &content)?;
+    Ok(serde_json::json!({ "snapshot": name }))
+}
+
+/// Drop the snapshots of all but the newest [`INDEX_SNAPSHOTS_KEPT`] entries
+fn prune_snapshots(root: &Path) -> Result<()> {
+    let dir = snapshot_dir(root);
+    let snapshot_id = |p: &Path| -> Option<u64> {
+        let name = p.file_name()?.to_str()?.strip_suffix(".json")?;
+        name.split_once('.')?.0.parse().ok()
+    };
+    let files: Vec<(u64, PathBuf)> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((snapshot_id(&e.path())?, e.path())))
+        .collect();
+    let mut ids: Vec<u64> = files.iter().map(|(id, _)| *id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let Some(cut) = ids.len().checked_sub(INDEX_SNAPSHOTS_KEPT) else {
+        return Ok(());
+    };
+    let oldest_kept = ids[cut];
+    for (id, p) in files {
+        if id < oldest_kept {
+            fs::remove_file(&p).with_context(|| format!("Failed to delete {}", p.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// The state a journal value describes, reading out-of-line snapshots
+pub fn resolve(root: &Path, v: &Value) -> Result<Value> {
+    let Some(name) = v.get("snapshot").and_then(|n| n.as_str()) else {
+        return Ok(v.clone());
+    };
+    let p = snapshot_dir(root).join(name);
+    let content = fs::read_to_string(&p).with_context(|| {
+        format!(
+            "Failed to read {} (only the last {} index changes can be undone)",
+            p.display(),
+            INDEX_SNAPSHOTS_KEPT
+        )
+    })?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", p.display()))
+}
+
+/// `+a +b -c` for list-valued states (focus pins, whitelist paths)
+pub fn list_summary(before: &[String], after: &[String]) -> String {
+    let added = after
+        .iter()
+        .filter(|p| !before.contains(p))
+        .map(|p| format!("+{p}"));
+    let removed = before
+        .iter()
+        .filter(|p| !after.contains(p))
+        .map(|p| format!("-{p}"));
+    let parts: Vec<String> = added.chain(removed).collect();
+    if parts.is_empty() {
+        "no change".into()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Ids of entries already reverted by a later `undo`
+fn undone_ids(entries: &[JournalEntry]) -> Vec<u64> {
+    entries.iter().filter_map(|e| e.undoes).collect()
+}
+
+/// Most recent entry that can still be undone
+pub fn next_undo(entries: &[JournalEntry]) -> Option<&JournalEntry> {
+    let undone = undone_ids(entries);
+    entries
+        .iter()
+        .rev()
+        .find(|e| e.undoes.is_none() && !undone.contains(&e.id))
+}
+
+/// Current state of `target` in the same shape the journal records
+fn current_state(root: &Path, target: Target) -> Result<Value> {
+    Ok(match target {
+        Target::Focus => serde_json::to_value(focus::load_focus(root)?)?,
+        Target::Whitelist => serde_json::to_value(whitelist::load_whitelist(root)?)?,
+        Target::Index => serde_json::to_value(store::load(root)?)?,
+        Target::Archive => Value::Null,
+    })
+}
+
+/// Pinned flags are derived from focus, so they don't count as index drift
+fn comparable(target: Target, v: &Value) -> Value {
+    let mut v = v.clone();
+    if target == Target::Index {
+        if let Some(ps) = v.get_mut("projects").and_then(|p| p.as_array_mut()) {
+            for p in ps {
+                if let Some(o) = p.as_object_mut() {
+                    o.remove("pinned");
+                }
+            }
+        }
+    }
+    v
+}
+
+/// Locks guarding `target`, in the global order (focus before index)
+fn lock_target(root: &Path, target: Target) -> Result<Vec<FileLock>> {
+    Ok(match target {
+        // Reverting focus re-syncs the index's pinned flags
+        Target::Focus => vec![focus::lock(root)?, store::lock(root)?],
+        Target::Whitelist => vec![whitelist::lock(root)?],
+        Target::Index | Target::Archive => vec![store::lock(root)?],
+    })
+}
+
+/// Revert the most recent journaled change and record the revert.
+/// Refuses when the state has changed since (unless `force`).
+/// The target stays locked from the drift check to the journal entry, and the
+/// journal from picking the entry to recording its revert.
+pub fn undo(root: &Path, force: bool) -> Result<JournalEntry> {
+    let (e, _target_lock, _journal_lock) = loop {
+        let Some(target) = next_undo(&load_journal(root)?).map(|e| e.target) else {
+            bail!("Nothing to undo");
+        };
+        let target_lock = lock_target(root, target)?;
+        let journal_lock = lock(root)?;
+        let entries = load_journal(root)?;
+        match next_undo(&entries) {
+            None => bail!("Nothing to undo"),
+            Some(e) if e.target == target => break (e.clone(), target_lock, journal_lock),
+            // Another process journaled a change to a different state meanwhile
+            Some(_) => continue,
+        }
+    };
+
+    let before = resolve(root, &e.before)?;
+    let current = current_state(root, e.target)?;
+    if e.target != Target::Archive
+        && !force
+        && comparable(e.target, &current) != comparable(e.target, &resolve(root, &e.after)?)
+    {
+        bail!(
+            "{:?} changed since #{} ({}); use --force to revert anyway",
+            e.target,
+            e.id,
+            e.op
+        );
+    }
+
+    let after = match e.target {
+        Target::Focus => {
+            let f: Focus =
+                serde_json::from_value(before.clone()).context("Invalid focus state in journal")?;
+            focus::save_focus(root, &f)?;
+            sync_index_pins(root, &f)?;
+            before
+        }
+        Target::Whitelist => {
+            let wl: Whitelist = serde_json::from_value(before.clone())
+                .context("Invalid whitelist state in journal")?;
+            whitelist::save_whitelist(root, &wl)?;
+            before
+        }
+        Target::Index => {
+            let mut idx: OrbitIndex =
+                serde_json::from_value(before.clone()).context("Invalid index state in journal")?;
+            let f = focus::load_focus(root).unwrap_or_default();
+            sync_pinned_flags(&mut idx.projects, &f.pinned);
+            store::save(root, &idx)?;
+            before
+        }
+        Target::Archive => undo_archive(root, &e)?,
+    };
+
+    let summary = format!("revert #{} {} ({})", e.id, e.op, e.summary);
+    append(root, "undo", e.target, summary, current, after, Some(e.id))
+}
+
+fn sync_index_pins(root: &Path, f: &Focus) -> Result<()> {
+    let _lock = store::lock(root)?;
+    let mut idx = store::load(root)?;
+    sync_pinned_flags(&mut idx.projects, &f.pinned);
+    store::save(root, &idx)
+}
+
+/// Archiving is undone by restoring; restoring is undone by archiving the same paths again
+fn undo_archive(root: &Path, e: &JournalEntry) -> Result<Value> {
+    let after: ArchiveState =
+        serde_json::from_value(e.after.clone()).context("Invalid archive state in journal")?;
+    let idx = store::load(root)?;
+    let (state, m) = if let Some(id) = &after.archive_id {
+        let m = archive::restore(root, id)?;
+        let state = ArchiveState {
+            archive_id: None,
+            paths: after.paths.clone(),
+        };
+        (state, m)
+    } else {
+        let f = focus::load_focus(root).unwrap_or_default();
+        let wl = whitelist::load_whitelist(root)?;
+        let plan = archive::plan(
+            root,
+            &idx,
+            &f,
+            &wl,
+            &archive::Selection::Paths(after.paths.clone()),
+        );
+        if let Some(i) = plan.refused().next() {
+            bail!(
+                "Cannot re-archive {}: {}",
+                i.path,
+                i.refused.as_deref().unwrap_or("")
+            );
+        }
+        let m = archive::execute(root, &idx, &plan)?;
+        let state = ArchiveState {
+            archive_id: Some(m.id.clone()),
+            paths: after.paths.clone(),
+        };
+        (state, m)
+    };
+    // Re-read under the lock: moving trees can take a while
+    let _lock = store::lock(root)?;
+    let mut idx = store::load(root)?;
+    if state.archive_id.is_some() {
+        archive::remove_from_index(&mut idx, &m);
+    } else {
+        archive::add_to_index(&mut idx, &m);
+    }
+    store::save(root, &idx)?;
+    Ok(serde_json::to_value(state)?)
+}
+
+pub fn handle_undo(root_str: &str, force: bool, dry_run: bool, json_output: bool) -> Result<()> {
+    let root = Path::new(root_str);
+    if dry_run {
+        let entries = load_journal(root)?;
+        match next_undo(&entries) {
+            Some(e) if json_output => {
+                println!(
+                    "{}",
+                    serde_json::json!({ "dry_run": true, "would_undo": e })
+                )
+            }
+            Some(e) => println!(
+                "[dry-run] would undo #{} {} ({}) from {}",
+                e.id,
+                e.op,
+                e.summary,
+                e.at.format("%Y-%m-%d %H:%M")
+            ),
+            None => println!("Nothing to undo."),
+        }
+        return Ok(());
+    }
+    let e = undo(root, force)?;
+    if json_output {
+        println!("{}", serde_json::json!({ "status": "undone", "entry": e }));
+    } else {
+        println!("Undone: {}", e.summary);
+    }
+    Ok(())
+}
+
+pub fn handle_history(root_str: &str, limit: usize, json_output: bool) -> Result<()> {
+    let root = Path::new(root_str);
+    let entries = load_journal(root)?;
+    let undone = undone_ids(&entries);
+    let start = entries.len().saturating_sub(limit);
+    let shown = &entries[start..];
+    if json_output {
+        println!("{}", serde_json::json!({ "entries": shown }));
+        return Ok(());
+    }
+    if shown.is_empty() {
+        println!("Journal is empty.");
+    }
+    for e in shown {
+        println!(
+            "#{:<4} {}  {:<16} {:<9} {}{}",
+            e.id,
+            e.at.format("%Y-%m-%d %H:%M"),
+            e.op,
+            format!("{:?}", e.target).to_lowercase(),
+            e.summary,
+            if undone.contains(&e.id) {
+                "  (undone)"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        journal_path, list_summary, load_journal, next_undo, record, resolve, snapshot_dir, undo,
+        Target, INDEX_SNAPSHOTS_KEPT,
+    };
+    use crate::index::whitelist::{load_whitelist, save_whitelist, Whitelist};
+
+    fn wl(paths: &[&str]) -> Whitelist {
+        Whitelist {
+            version: "0.1".into(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn undo_walks_back_and_skips_undone_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let (w0, w1, w2) = (wl(&[]), wl(&["a"]), wl(&["a", "b"]));
+        save_whitelist(root, &w2).unwrap();
+        record(
+            root,
+            "whitelist add",
+            Target::Whitelist,
+            "+a".into(),
+            &w0,
+            &w1,
+        )
+        .unwrap();
+        record(
+            root,
+            "whitelist add",
+            Target::Whitelist,
+            "+b".into(),
+            &w1,
+            &w2,
+        )
+        .unwrap();
+        // Re-adding an existing entry changes nothing and isn't journaled
+        let noop = record(
+            root,
+            "whitelist add",
+            Target::Whitelist,
+            "no change".into(),
+            &w2,
+            &w2,
+        );
+        assert_eq!(noop.unwrap(), None);
+
+        let u = undo(root, false).unwrap();
+        assert_eq!(u.undoes, Some(2));
+        assert_eq!(load_whitelist(root).unwrap().paths, vec!["a"]);
+
+        let u = undo(root, false).unwrap();
+        assert_eq!(u.undoes, Some(1));
+        assert!(load_whitelist(root).unwrap().paths.is_empty());
+
+        let entries = load_journal(root).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert!(next_undo(&entries).is_none());
+        assert!(undo(root, false).is_err());
+    }
+
+    #[test]
+    fn undo_refuses_when_state_drifted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let (w0, w1) = (wl(&[]), wl(&["a"]));
+        record(
+            root,
+            "whitelist add",
+            Target::Whitelist,
+            "+a".into(),
+            &w0,
+            &w1,
+        )
+        .unwrap();
+        save_whitelist(root, &wl(&["a", "manual"])).unwrap();
+
+        let err = undo(root, false).unwrap_err().to_string();
+        assert!(err.contains("--force"), "{err}");
+        undo(root, true).unwrap();
+        assert!(load_whitelist(root).unwrap().paths.is_empty());
+    }
+
+    #[test]
+    fn concurrent_appends_get_distinct_ids() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_path_buf();
+        let workers: Vec<_> = (0..4)
+            .map(|t| {
+                let root = root.clone();
+                std::thread::spawn(move || {
+                    for i in 0..10 {
+                        let after = wl(&[&format!("{t}-{i}")]);
+                        record(
+                            &root,
+                            "whitelist add",
+                            Target::Whitelist,
+                            "+x".into(),
+                            &wl(&[]),
+                            &after,
+                        )
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for w in workers {
+            w.join().unwrap();
+        }
+        let mut ids: Vec<u64> = load_journal(&root).unwrap().iter().map(|e| e.id).collect();
+        ids.sort();
+        assert_eq!(ids, (1..=40).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn index_states_are_kept_out_of_line_for_recent_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        record(
+            root,
+            "whitelist add",
+            Target::Whitelist,
+            "+a".into(),
+            &wl(&[]),
+            &wl(&["a"]),
+        )
+        .unwrap();
+        let total = INDEX_SNAPSHOTS_KEPT as u64 + 2;
+        for n in 0..total {
+            let state = |n: u64| serde_json::json!({ "projects": [], "n": n });
+            let id = record(
+                root,
+                "census",
+                Target::Index,
+                "0 → 0 projects".into(),
+                &state(n),
+                &state(n + 1),
+            )
+            .unwrap();
+            assert_eq!(id, Some(n + 2));
+        }
+
+        let entries = load_journal(root).unwrap();
+        let last = entries.last().unwrap();
+        assert!(last.after.get("snapshot").is_some());
+        assert_eq!(resolve(root, &last.after).unwrap()["n"], total);
+        let err = resolve(root, &entries[1].before).unwrap_err().to_string();
+        assert!(err.contains("can be undone"), "{err}");
+        let kept = std::fs::read_dir(snapshot_dir(root)).unwrap().count();
+        assert_eq!(kept, 2 * INDEX_SNAPSHOTS_KEPT);
+        // Lines stay small; the journal no longer carries whole indexes
+        let size = std::fs::metadata(journal_path(root)).unwrap().len();
+        assert!(size < 400 * entries.len() as u64, "{size}");
+    }
+
+    #[test]
+    fn list_summary_shows_added_and_removed() {
+        let before = vec!["a".to_string(), "b".to_string()];
+        let after = vec!["b".to_string(), "c".to_string()];
+        assert_eq!(list_summary(&before, &after), "+c -a");
+        assert_eq!(list_summary(&after, &after), "no change");
+    }
+}
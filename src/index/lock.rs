@@ -0,0 +1,167 @@
+use anyhow::{bail, Context, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for another process before giving up
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+thread_local! {
+    /// Locks this thread already holds, with their nesting depth
+    static HELD: RefCell<HashMap<PathBuf, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Advisory lock on a shared state file (`index.json`, `focus.json`, …), held
+/// on a `<file>.lock` sidecar so it survives the rename in `atomic_write`.
+/// Re-entrant within a thread, so a read-modify-write can hold the lock while
+/// the `save_*` it calls takes it again. Released on drop.
+pub struct FileLock {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+/// `ORBIT_LOCK_TIMEOUT_SECS`, else [`DEFAULT_LOCK_TIMEOUT`]
+pub fn timeout() -> Duration {
+    env::var("ORBIT_LOCK_TIMEOUT_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs_f64)
+        .unwrap_or(DEFAULT_LOCK_TIMEOUT)
+}
+
+/// `index.json` → `index.json.lock`
+pub fn lock_path(data: &Path) -> PathBuf {
+    let mut name = data.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    data.with_file_name(name)
+}
+
+/// Lock `data` (the file being protected), waiting up to [`timeout`]
+pub fn acquire(data: &Path) -> Result<FileLock> {
+    acquire_within(data, timeout())
+}
+
+pub fn acquire_within(data: &Path, wait: Duration) -> Result<FileLock> {
+    let path = lock_path(data);
+    let nested = HELD.with(|h| match h.borrow_mut().get_mut(&path) {
+        Some(depth) => {
+            *depth += 1;
+            true
+        }
+        None => false,
+    });
+    if nested {
+        return Ok(FileLock { path, file: None });
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+
+    let start = Instant::now();
+    let mut backoff = Duration::from_millis(5);
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) if start.elapsed() < wait => {
+                thread::sleep(backoff.min(wait.saturating_sub(start.elapsed())));
+                backoff = (backoff * 2).min(Duration::from_millis(200));
+            }
+            Err(TryLockError::WouldBlock) => {
+                let mut holder = String::new();
+                let _ = file.read_to_string(&mut holder);
+                let holder = match holder.trim() {
+                    "" => String::new(),
+                    pid => format!(" (held by pid {})", pid),
+                };
+                bail!(
+                    "Timed out after {:.1}s waiting for {}{}; another Orbit or Mole process is using it. \
+                     Retry, or raise ORBIT_LOCK_TIMEOUT_SECS",
+                    wait.as_secs_f64(),
+                    path.display(),
+                    holder
+                );
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", path.display()))
+            }
+        }
+    }
+
+    // Record the holder for the timeout message of whoever waits next
+    let _ = file.set_len(0);
+    let _ = file.seek(SeekFrom::Start(0));
+    let _ = write!(file, "{}", std::process::id());
+    HELD.with(|h| h.borrow_mut().insert(path.clone(), 1));
+    Ok(FileLock {
+        path,
+        file: Some(file),
+    })
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        HELD.with(|h| {
+            let mut held = h.borrow_mut();
+            if let Some(depth) = held.get_mut(&self.path) {
+                *depth -= 1;
+                if *depth == 0 {
+                    held.remove(&self.path);
+                }
+            }
+        });
+        if let Some(file) = self.file.take() {
+            let _ = file.set_len(0);
+            let _ = file.unlock();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{acquire_within, lock_path};
+    use std::time::Duration;
+
+    #[test]
+    fn lock_is_reentrant_in_a_thread_and_exclusive_across_threads() {
+        let tmp = tempfile::tempdir().unwrap();
+        let data = tmp.path().join("focus.json");
+        assert_eq!(lock_path(&data), tmp.path().join("focus.json.lock"));
+        let wait = Duration::from_millis(50);
+
+        let outer = acquire_within(&data, wait).unwrap();
+        let inner = acquire_within(&data, wait).unwrap();
+        drop(inner);
+
+        let d = data.clone();
+        let err = std::thread::spawn(move || acquire_within(&d, wait).err())
+            .join()
+            .unwrap()
+            .expect("lock is still held by the outer guard");
+        let msg = format!("{:#}", err);
+        assert!(msg.contains("Timed out"), "{msg}");
+        assert!(
+            msg.contains(&format!("pid {}", std::process::id())),
+            "{msg}"
+        );
+
+        drop(outer);
+        let d = data.clone();
+        assert!(std::thread::spawn(move || acquire_within(&d, wait).is_ok())
+            .join()
+            .unwrap());
+    }
+}
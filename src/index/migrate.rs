@@ -0,0 +1,430 @@
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::store::{self, OrbitIndex, SharedIndex};
+
+/// Schema of index files written by this build (`"0.6"` on disk)
+pub const INDEX_SCHEMA: u32 = 6;
+
+/// Oldest schema we can read; earlier drafts (`0.1`–`0.4`, also written by Mole)
+/// share its flat shape
+const OLDEST_SCHEMA: u32 = 4;
+
+/// `"0.6"`
+pub fn schema_label(n: u32) -> String {
+    format!("0.{}", n)
+}
+
+/// Schema number of a document: `"0.5"` → 5; a bare number is accepted too
+pub fn schema_of(doc: &Value) -> Result<u32> {
+    match doc.get("version") {
+        None | Some(Value::Null) => Ok(OLDEST_SCHEMA),
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .map(|n| n as u32)
+            .context("Invalid index version number"),
+        Some(Value::String(s)) => {
+            let minor = s.strip_prefix("0.").unwrap_or(s);
+            minor
+                .parse()
+                .with_context(|| format!("Unrecognized index version '{}'", s))
+        }
+        Some(other) => bail!("Unrecognized index version {}", other),
+    }
+}
+
+/// 0.1–0.4 → 0.5: fill in fields early drafts could omit. Per-project legacy
+/// fields (`has_rust`, `artifact_count`, …) are read by the serde shims on `ProjectEntry`.
+fn v4_to_v5(doc: &mut Value) -> Result<()> {
+    let obj = doc.as_object_mut().context("Index is not a JSON object")?;
+    obj.entry("root").or_insert_with(|| ".".into());
+    obj.entry("generated_at").or_insert(Value::Null);
+    obj.entry("projects")
+        .or_insert_with(|| Value::Array(vec![]));
+    Ok(())
+}
+
+/// 0.5 → 0.6: single-root indexes keep their shape; 0.6 is the schema that added
+/// the multi-root shared index, whose entries are single-root indexes
+fn v5_to_v6(_doc: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+type Step = fn(&mut Value) -> Result<()>;
+
+/// Upgrade steps, each taking a single-root index from the listed schema to the next
+const STEPS: &[(u32, Step)] = &[(4, v4_to_v5), (5, v5_to_v6)];
+
+/// A document read at schema `from` and upgraded to the current one
+#[derive(Debug)]
+pub struct Migrated<T> {
+    pub doc: T,
+    pub from: u32,
+}
+
+impl<T> Migrated<T> {
+    pub fn changed(&self) -> bool {
+        self.from < INDEX_SCHEMA
+    }
+}
+
+fn check_supported(from: u32) -> Result<()> {
+    if from > INDEX_SCHEMA {
+        bail!(
+            "Index schema {} was written by a newer Orbit (this build reads up to {})",
+            schema_label(from),
+            schema_label(INDEX_SCHEMA)
+        );
+    }
+    Ok(())
+}
+
+/// Upgrade a single-root index document to the current schema
+pub fn migrate_index(mut doc: Value) -> Result<Migrated<OrbitIndex>> {
+    let from = schema_of(&doc)?;
+    check_supported(from)?;
+    for (at, step) in STEPS {
+        if from.max(OLDEST_SCHEMA) <= *at {
+            step(&mut doc).with_context(|| {
+                format!("Failed to migrate index from schema {}", schema_label(*at))
+            })?;
+            doc["version"] = schema_label(at + 1).into();
+        }
+    }
+    let idx = serde_json::from_value(doc).with_context(|| {
+        format!(
+            "Index does not match schema {} after migration",
+            schema_label(INDEX_SCHEMA)
+        )
+    })?;
+    Ok(Migrated { doc: idx, from })
+}
+
+/// Upgrade the shared index. A single-root index (the shared format before 0.6)
+/// becomes the only entry, keyed by its root.
+pub fn migrate_shared(doc: Value) -> Result<Migrated<SharedIndex>> {
+    migrate_shared_for(doc, None)
+}
+
+/// [`migrate_shared`], resolving a relative root of a single-root index
+/// against `base` (the `--root` of `orbit index migrate`). Without `base` such
+/// an index is refused: whichever command happened to run next would claim it.
+pub fn migrate_shared_for(doc: Value, base: Option<&Path>) -> Result<Migrated<SharedIndex>> {
+    let Some(roots) = doc.get("roots") else {
+        let mut m = migrate_index(doc)?;
+        let raw = PathBuf::from(&m.doc.root);
+        let key = match base {
+            _ if raw.is_absolute() => store::root_key(&raw),
+            Some(base) => store::root_key(&base.join(&raw)),
+            None => bail!(
+                "The shared index records its root as {:?}, a relative path; run \
+                 `orbit --root <dir> index migrate` with the directory it was written for",
+                m.doc.root
+            ),
+        };
+        m.doc.root = key.clone();
+        let mut shared = SharedIndex::default();
+        shared.roots.insert(key, m.doc);
+        return Ok(Migrated {
+            doc: shared,
+            from: m.from,
+        });
+    };
+    let container = schema_of(&doc)?;
+    check_supported(container)?;
+    let roots = roots
+        .as_object()
+        .context("Shared index `roots` is not an object")?;
+    let mut shared = SharedIndex::default();
+    let mut from = container;
+    for (key, entry) in roots {
+        let m = migrate_index(entry.clone())
+            .with_context(|| format!("Failed to migrate the index of root {}", key))?;
+        from = from.min(m.from);
+        shared.roots.insert(key.clone(), m.doc);
+    }
+    Ok(Migrated { doc: shared, from })
+}
+
+pub fn read_json(p: &Path) -> Result<Value> {
+    let content =
+        fs::read_to_string(p).with_context(|| format!("Failed to read {}", p.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", p.display()))
+}
+
+/// Copy `p` to `<name>.bak-<timestamp>` next to it, unless an identical
+/// backup is already there (a broken index fails every load until fixed)
+pub fn backup(p: &Path) -> Result<PathBuf> {
+    let name = p
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("index.json");
+    let content = fs::read(p).with_context(|| format!("Failed to read {}", p.display()))?;
+    let prefix = format!("{}.bak-", name);
+    let existing = p
+        .parent()
+        .and_then(|d| fs::read_dir(d).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+        .find(|e| fs::read(e.path()).is_ok_and(|c| c == content));
+    if let Some(e) = existing {
+        return Ok(e.path());
+    }
+    let stamp = Local::now().format("%Y%m%dT%H%M%S%.3f").to_string();
+    let mut dest = p.with_file_name(format!("{}{}", prefix, stamp));
+    let mut n = 1;
+    while dest.exists() {
+        n += 1;
+        dest = p.with_file_name(format!("{}{}-{}", prefix, stamp, n));
+    }
+    fs::copy(p, &dest)
+        .with_context(|| format!("Failed to back up {} to {}", p.display(), dest.display()))?;
+    Ok(dest)
+}
+
+/// Read and migrate `p`; when that fails the file is backed up and the error
+/// names the copy, so a broken or future index is never silently replaced
+pub fn load_or_backup<T>(p: &Path, migrate: fn(Value) -> Result<Migrated<T>>) -> Result<T> {
+    match read_json(p).and_then(migrate) {
+        Ok(m) => Ok(m.doc),
+        Err(e) => {
+            let copy = backup(p)?;
+            Err(e.context(format!(
+                "Cannot load {} (a copy was saved to {}); fix it or run `orbit index migrate --check` for details",
+                p.display(),
+                copy.display()
+            )))
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    pub from: Option<String>,
+    /// `current`, `needs_migration`, `migrated` or `failed`
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+}
+
+/// Migrate (or with `check`, only inspect) the shared index and this root's index
+pub fn handle_migrate(root_str: &str, check: bool, dry_run: bool, json_output: bool) -> Result<()> {
+    let root = Path::new(root_str);
+    let write = !check && !dry_run;
+    let mut reports = Vec::new();
+    // Another process may save the index between reading and rewriting it
+    let _lock = if write {
+        Some(store::lock(root)?)
+    } else {
+        None
+    };
+
+    let mut files: Vec<(PathBuf, bool)> = vec![];
+    if let Some(p) = store::home_index_path() {
+        files.push((p, true));
+    }
+    let legacy = store::index_path(root);
+    if !files.iter().any(|(p, _)| *p == legacy) {
+        files.push((legacy, false));
+    }
+
+    for (p, shared) in files.into_iter().filter(|(p, _)| p.is_file()) {
+        let doc = read_json(&p);
+        let result: Result<(u32, String)> = doc.and_then(|d| {
+            if shared {
+                let m = migrate_shared_for(d, Some(root))?;
+                let out = serde_json::to_string_pretty(&m.doc)?;
+                Ok((m.from, out))
+            } else {
+                let m = migrate_index(d)?;
+                let out = serde_json::to_string_pretty(&m.doc)?;
+                Ok((m.from, out))
+            }
+        });
+        let mut r = FileReport {
+            path: p.display().to_string(),
+            from: None,
+            status: "current",
+            error: None,
+            backup: None,
+        };
+        match result {
+            Err(e) => {
+                r.status = "failed";
+                r.error = Some(format!("{:#}", e));
+                if write {
+                    r.backup = Some(backup(&p)?.display().to_string());
+                }
+            }
+            Ok((from, content)) => {
+                r.from = Some(schema_label(from));
+                if from < INDEX_SCHEMA {
+                    r.status = "needs_migration";
+                    if write {
+                        r.backup = Some(backup(&p)?.display().to_string());
+                        store::atomic_write(&p, &content)?;
+                        r.status = "migrated";
+                    }
+                }
+            }
+        }
+        reports.push(r);
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "schema": schema_label(INDEX_SCHEMA),
+                "check": check,
+                "dry_run": dry_run,
+                "files": reports,
+            })
+        );
+    } else {
+        if reports.is_empty() {
+            println!("No index files found.");
+        }
+        for r in &reports {
+            let prefix = if dry_run && r.status == "needs_migration" {
+                "[dry-run] would migrate"
+            } else {
+                r.status
+            };
+            println!(
+                "{}: {} (schema {})",
+                prefix,
+                r.path,
+                r.from.as_deref().unwrap_or("?")
+            );
+            if let Some(e) = &r.error {
+                println!("  error: {}", e);
+            }
+            if let Some(b) = &r.backup {
+                println!("  backup: {}", b);
+            }
+        }
+    }
+
+    let failed = reports.iter().filter(|r| r.status == "failed").count();
+    let pending = reports
+        .iter()
+        .filter(|r| r.status == "needs_migration")
+        .count();
+    if failed > 0 {
+        bail!("{} index file(s) cannot be migrated", failed);
+    }
+    if check && pending > 0 {
+        bail!(
+            "{} index file(s) need migration to schema {}; run `orbit index migrate`",
+            pending,
+            schema_label(INDEX_SCHEMA)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        backup, load_or_backup, migrate_index, migrate_shared, migrate_shared_for, schema_of,
+        INDEX_SCHEMA,
+    };
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn versions_parse_from_strings_and_numbers() {
+        assert_eq!(schema_of(&json!({ "version": "0.5" })).unwrap(), 5);
+        assert_eq!(schema_of(&json!({ "version": 6 })).unwrap(), 6);
+        assert_eq!(schema_of(&json!({})).unwrap(), 4);
+        assert!(schema_of(&json!({ "version": "beta" })).is_err());
+    }
+
+    #[test]
+    fn early_drafts_are_upgraded() {
+        let doc = json!({
+            "version": "0.1",
+            "projects": [{ "path": "api", "kind": "standalone", "pinned": false,
+                           "has_git": true, "has_rust": true, "artifact_count": 2 }]
+        });
+        let m = migrate_index(doc).unwrap();
+        assert!(m.changed());
+        assert_eq!(m.from, 1);
+        assert_eq!(m.doc.version, "0.6");
+        assert_eq!(m.doc.root, ".");
+        assert_eq!(m.doc.projects[0].ecosystems, vec!["rust"]);
+        assert_eq!(m.doc.projects[0].artifact_count(), 2);
+
+        let current = migrate_index(json!({ "version": "0.6", "root": "/w", "projects": [] }));
+        assert!(!current.unwrap().changed());
+    }
+
+    #[test]
+    fn single_root_shared_index_becomes_a_root_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().display().to_string();
+        let flat = json!({ "version": "0.5", "root": root, "generated_at": null, "projects": [] });
+        let m = migrate_shared(flat).unwrap();
+        assert_eq!(m.from, 5);
+        let key = tmp.path().canonicalize().unwrap().display().to_string();
+        assert!(m.doc.roots.contains_key(&key));
+
+        let shared = json!({ "version": "0.6", "roots": { "/w": { "version": "0.5", "root": "/w", "projects": [] } } });
+        let m = migrate_shared(shared).unwrap();
+        assert_eq!(m.from, 5, "oldest entry decides");
+        assert_eq!(m.doc.roots["/w"].version, "0.6");
+    }
+
+    #[test]
+    fn relative_single_root_is_resolved_only_against_an_explicit_root() {
+        let flat = json!({ "version": "0.5", "root": ".", "projects": [] });
+        let err = migrate_shared(flat.clone()).unwrap_err();
+        assert!(format!("{:#}", err).contains("orbit --root <dir> index migrate"));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let m = migrate_shared_for(flat, Some(tmp.path())).unwrap();
+        let key = tmp.path().canonicalize().unwrap().display().to_string();
+        assert_eq!(m.doc.roots.keys().collect::<Vec<_>>(), vec![&key]);
+        assert_eq!(m.doc.roots[&key].root, key);
+    }
+
+    #[test]
+    fn newer_or_broken_indexes_fail_loudly_with_a_backup() {
+        let future = json!({ "version": format!("0.{}", INDEX_SCHEMA + 1), "projects": [] });
+        let err = migrate_index(future).unwrap_err();
+        assert!(format!("{:#}", err).contains("newer Orbit"));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let p = tmp.path().join("index.json");
+        fs::write(&p, "{ not json").unwrap();
+        let err = load_or_backup(&p, migrate_index).unwrap_err();
+        assert!(format!("{:#}", err).contains("a copy was saved"));
+        // Failing again doesn't pile up identical copies
+        assert!(load_or_backup(&p, migrate_index).is_err());
+        let backups: Vec<_> = fs::read_dir(tmp.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .starts_with("index.json.bak-")
+            })
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0].path()).unwrap(), "{ not json");
+        fs::write(&p, "{ still not json").unwrap();
+        let changed = backup(&p).unwrap();
+        assert_ne!(changed, backups[0].path());
+        assert!(backup(&tmp.path().join("missing.json")).is_err());
+    }
+}
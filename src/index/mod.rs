@@ -1,5 +1,12 @@
+pub mod catalog;
 pub mod focus;
+pub mod history;
+pub mod journal;
+pub mod lock;
+pub mod migrate;
+pub mod search;
 pub mod session;
 pub mod status;
 pub mod store;
+pub mod timeline;
 pub mod whitelist;
@@ -0,0 +1,241 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::catalog::{self, ArtifactRecord};
+use crate::index::store::atomic_write;
+
+/// Bumped when tokenization changes and stored indexes must be rebuilt
+pub const SEARCH_INDEX_VERSION: u32 = 1;
+
+const MIN_TOKEN: usize = 2;
+const MAX_TOKEN: usize = 40;
+
+/// Inverted index over artifact contents, stored at `<root>/.orbit/artifact_index.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub version: u32,
+    /// Catalog keys (`project/path`), addressed by position in `terms`
+    pub docs: Vec<String>,
+    /// Token → `(doc, occurrences)` pairs, ordered by doc
+    pub terms: BTreeMap<String, Vec<(u32, u32)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hit {
+    pub key: String,
+    /// Total occurrences of the query terms
+    pub score: u32,
+}
+
+/// Lowercased alphanumeric runs; very short and very long runs are dropped
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| (MIN_TOKEN..=MAX_TOKEN).contains(&t.chars().count()))
+        .map(|t| t.to_lowercase())
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self {
+            version: SEARCH_INDEX_VERSION,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, key: &str, text: &str) {
+        let doc = self.docs.len() as u32;
+        self.docs.push(key.to_string());
+        let mut counts: BTreeMap<String, u32> = BTreeMap::new();
+        for t in tokenize(text) {
+            *counts.entry(t).or_default() += 1;
+        }
+        for (t, n) in counts {
+            self.terms.entry(t).or_default().push((doc, n));
+        }
+    }
+
+    /// Documents containing every query term, best first
+    pub fn query(&self, q: &str) -> Vec<Hit> {
+        let mut terms: Vec<String> = tokenize(q).collect();
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() {
+            return vec![];
+        }
+        let mut scores: Option<BTreeMap<u32, u32>> = None;
+        for t in &terms {
+            let postings: BTreeMap<u32, u32> = self
+                .terms
+                .get(t)
+                .map(|p| p.iter().copied().collect())
+                .unwrap_or_default();
+            scores = Some(match scores {
+                None => postings,
+                Some(prev) => prev
+                    .into_iter()
+                    .filter_map(|(d, s)| postings.get(&d).map(|n| (d, s + n)))
+                    .collect(),
+            });
+        }
+        let mut hits: Vec<Hit> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(d, score)| {
+                self.docs.get(d as usize).map(|k| Hit {
+                    key: k.clone(),
+                    score,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.key.cmp(&b.key)));
+        hits
+    }
+}
+
+pub fn index_path(root: &Path) -> PathBuf {
+    root.join(".orbit").join("artifact_index.json")
+}
+
+/// `None` when missing or written by an older tokenizer
+pub fn load(root: &Path) -> Result<Option<SearchIndex>> {
+    let p = index_path(root);
+    if !p.is_file() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+    let idx: SearchIndex = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", p.display()))?;
+    Ok((idx.version == SEARCH_INDEX_VERSION).then_some(idx))
+}
+
+pub fn save(root: &Path, idx: &SearchIndex) -> Result<()> {
+    let content = serde_json::to_string(idx).context("Failed to serialize artifact index")?;
+    atomic_write(&index_path(root), &content)
+}
+
+/// Lines of `text` containing any query term, as `(line number, line)`
+pub fn matching_lines(text: &str, q: &str, limit: usize) -> Vec<(usize, String)> {
+    let terms: Vec<String> = tokenize(q).collect();
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| tokenize(l).any(|t| terms.contains(&t)))
+        .take(limit)
+        .map(|(i, l)| (i + 1, l.trim().to_string()))
+        .collect()
+}
+
+pub fn handle_grep_artifacts(
+    root_str: &str,
+    query: &str,
+    limit: usize,
+    reindex: bool,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+    let (cat, idx) = match (reindex, catalog::load(root)?, load(root)?) {
+        (false, Some(c), Some(i)) => (c, i),
+        _ => catalog::refresh(root)?,
+    };
+    let by_key: BTreeMap<String, &ArtifactRecord> =
+        cat.artifacts.iter().map(|a| (a.key(), a)).collect();
+
+    let hits = idx.query(query);
+    let mut out = Vec::new();
+    for h in hits.iter().take(limit) {
+        let Some(a) = by_key.get(&h.key) else {
+            continue;
+        };
+        // The file may have changed since the last census; show what is there now
+        let lines = fs::read(root.join(&h.key))
+            .map(|b| matching_lines(&String::from_utf8_lossy(&b), query, 3))
+            .unwrap_or_default();
+        out.push((h, *a, lines));
+    }
+
+    if json_output {
+        let shown: Vec<_> = out
+            .iter()
+            .map(|(h, a, lines)| {
+                serde_json::json!({
+                    "key": h.key,
+                    "project": a.project,
+                    "path": a.path,
+                    "category": a.category,
+                    "title": a.title,
+                    "score": h.score,
+                    "lines": lines
+                        .iter()
+                        .map(|(n, l)| serde_json::json!({ "line": n, "text": l }))
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({ "query": query, "total": hits.len(), "hits": shown })
+        );
+        return Ok(());
+    }
+
+    if out.is_empty() {
+        println!("No artifacts match '{}'.", query);
+        return Ok(());
+    }
+    for (h, a, lines) in &out {
+        println!(
+            "{} [{}] {}",
+            h.key,
+            a.category,
+            a.title.as_deref().unwrap_or("")
+        );
+        for (n, l) in lines {
+            println!("  {:>5}: {}", n, l);
+        }
+    }
+    if hits.len() > out.len() {
+        println!("… {} more (use --limit)", hits.len() - out.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matching_lines, tokenize, SearchIndex};
+
+    #[test]
+    fn tokenizer_lowercases_and_drops_noise() {
+        let t: Vec<String> = tokenize("Fix the OAuth-flow in v2 (see a/b)").collect();
+        assert_eq!(t, vec!["fix", "the", "oauth", "flow", "in", "v2", "see"]);
+    }
+
+    #[test]
+    fn query_requires_every_term_and_ranks_by_occurrences() {
+        let mut idx = SearchIndex::new();
+        idx.add("app/HANDOFF.md", "Next: migrate auth. Auth tokens expire.");
+        idx.add("api/plan.md", "Plan: auth rewrite, then migrate billing");
+        idx.add("web/session.md", "Nothing relevant");
+
+        let keys = |q: &str| -> Vec<String> { idx.query(q).into_iter().map(|h| h.key).collect() };
+        assert_eq!(keys("auth"), vec!["app/HANDOFF.md", "api/plan.md"]);
+        assert_eq!(keys("MIGRATE billing"), vec!["api/plan.md"]);
+        assert!(keys("kubernetes").is_empty());
+        assert!(keys("!").is_empty());
+
+        let round: SearchIndex =
+            serde_json::from_str(&serde_json::to_string(&idx).unwrap()).unwrap();
+        assert_eq!(round.query("auth"), idx.query("auth"));
+    }
+
+    #[test]
+    fn matching_lines_are_numbered() {
+        let text = "# Handoff\n\nAuth is half done\nbilling untouched\n";
+        assert_eq!(
+            matching_lines(text, "auth", 3),
+            vec![(3, "Auth is half done".to_string())]
+        );
+    }
+}
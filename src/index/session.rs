@@ -4,6 +4,7 @@ use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::index::lock;
 use crate::index::store::atomic_write;
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -49,5 +50,6 @@ pub fn save_session(session: &OrbitSession) -> Result<()> {
             .with_context(|| format!("Failed to create directory {}", parent.display()))?;
     }
     let content = serde_json::to_string_pretty(session).context("Failed to serialize session")?;
+    let _lock = lock::acquire(&path)?;
     atomic_write(&path, &content)
 }
@@ -21,6 +21,13 @@ pub fn print_status(root_str: &str, json_output: bool) -> Result<()> {
         .iter()
         .filter(|p| matches!(p.kind, ProjectKind::BackupDuplicate))
         .count();
+    let uncommitted = idx
+        .projects
+        .iter()
+        .filter(|p| p.has_uncommitted_work())
+        .count();
+    let this_root = store::root_key(root);
+    let roots = store::load_shared()?.summaries();
 
     if json_output {
         println!(
@@ -30,8 +37,11 @@ pub fn print_status(root_str: &str, json_output: bool) -> Result<()> {
                 "indexed_projects": total,
                 "active": active,
                 "backup_duplicate": backups,
+                "uncommitted": uncommitted,
                 "pinned": pinned,
-                "index_path": store::index_path(root).display().to_string()
+                "index_path": store::index_path(root).display().to_string(),
+                "roots": roots,
+                "current_root": this_root
             })
         );
     } else {
@@ -40,8 +50,20 @@ pub fn print_status(root_str: &str, json_output: bool) -> Result<()> {
         println!("  Indexed projects: {}", total);
         println!("  Active: {}", active);
         println!("  Backup/Duplicate: {}", backups);
+        println!("  Uncommitted work: {}", uncommitted);
         println!("  Pinned: {}", pinned);
         println!("  Index: {}", store::index_path(root).display());
+        if roots.len() > 1 {
+            println!("  Indexed roots:");
+            for r in &roots {
+                println!(
+                    "    {} {} ({} projects)",
+                    if r.root == this_root { "*" } else { " " },
+                    r.root,
+                    r.projects
+                );
+            }
+        }
     }
     Ok(())
 }
@@ -1,13 +1,21 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
-use std::fs;
+use std::fs::{self, File};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
+use crate::index::lock::{self, FileLock};
+use crate::index::migrate::{self, schema_label, INDEX_SCHEMA};
 use crate::model::project::ProjectEntry;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Projects of one root. This is the flat schema of `<root>/.orbit/index.json`
+/// and of each entry in the shared index. Older schemas are upgraded on load
+/// by [`migrate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrbitIndex {
     pub version: String,
     pub root: String,
@@ -18,7 +26,7 @@ pub struct OrbitIndex {
 impl Default for OrbitIndex {
     fn default() -> Self {
         Self {
-            version: "0.5".into(),
+            version: schema_label(INDEX_SCHEMA),
             root: ".".into(),
             generated_at: None,
             projects: vec![],
@@ -26,7 +34,53 @@ impl Default for OrbitIndex {
     }
 }
 
-fn home_index_path() -> Option<PathBuf> {
+/// The shared `~/.orbit/index.json`: one [`OrbitIndex`] per scanned root, keyed by
+/// canonical path, so scanning one root never clobbers another
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedIndex {
+    pub version: String,
+    pub roots: BTreeMap<String, OrbitIndex>,
+}
+
+impl Default for SharedIndex {
+    fn default() -> Self {
+        Self {
+            version: schema_label(INDEX_SCHEMA),
+            roots: BTreeMap::new(),
+        }
+    }
+}
+
+/// A root in the shared index, as listed by `orbit status` and the TUI
+#[derive(Debug, Clone, Serialize)]
+pub struct RootSummary {
+    pub root: String,
+    pub projects: usize,
+    pub generated_at: Option<DateTime<Local>>,
+}
+
+impl SharedIndex {
+    pub fn summaries(&self) -> Vec<RootSummary> {
+        self.roots
+            .iter()
+            .map(|(r, idx)| RootSummary {
+                root: r.clone(),
+                projects: idx.projects.len(),
+                generated_at: idx.generated_at,
+            })
+            .collect()
+    }
+}
+
+/// Canonical form of a root, used as its key in the shared index and history
+pub fn root_key(root: &Path) -> String {
+    root.canonicalize()
+        .unwrap_or_else(|_| root.to_path_buf())
+        .display()
+        .to_string()
+}
+
+pub fn home_index_path() -> Option<PathBuf> {
     env::var("HOME").ok().map(|h| PathBuf::from(h).join(".orbit").join("index.json"))
 }
 
@@ -35,38 +89,50 @@ pub fn index_path(root: &Path) -> std::path::PathBuf {
     root.join(".orbit").join("index.json")
 }
 
+/// Every root in the shared home-level index. A single-root index written by
+/// older versions is read as one entry keyed by its `root`. An index that cannot
+/// be read or migrated is an error (after backing it up), never an empty index.
+pub fn load_shared() -> Result<SharedIndex> {
+    match home_index_path().filter(|p| p.exists()) {
+        Some(p) => migrate::load_or_backup(&p, migrate::migrate_shared),
+        None => Ok(SharedIndex::default()),
+    }
+}
+
 pub fn load(root: &Path) -> Result<OrbitIndex> {
-    // Prefer shared home-level index, fallback to legacy root/.orbit/index.json
-    let candidates: Vec<PathBuf> = home_index_path()
-        .into_iter()
-        .chain(std::iter::once(index_path(root)))
-        .collect();
-
-    for p in candidates {
-        if !p.exists() {
-            continue;
-        }
-        let content =
-            fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
-        if let Ok(parsed) =
-            serde_json::from_str::<OrbitIndex>(&content).with_context(|| format!("Failed to parse {}", p.display()))
-        {
-            return Ok(parsed);
-        }
+    // Prefer this root's entry in the shared home-level index, fallback to
+    // legacy root/.orbit/index.json
+    if let Some(idx) = load_shared()?.roots.remove(&root_key(root)) {
+        return Ok(idx);
+    }
+    let p = index_path(root);
+    if p.exists() {
+        return migrate::load_or_backup(&p, migrate::migrate_index);
     }
     Ok(OrbitIndex::default())
 }
 
+/// Lock the index for a read-modify-write spanning [`load`] and [`save`]; the
+/// shared home index when there is one, else this root's file
+pub fn lock(root: &Path) -> Result<FileLock> {
+    lock::acquire(&home_index_path().unwrap_or_else(|| index_path(root)))
+}
+
 pub fn save(root: &Path, idx: &OrbitIndex) -> Result<()> {
     let content = serde_json::to_string_pretty(idx).context("Failed to serialize index")?;
+    let _lock = lock(root)?;
 
-    // Primary: home-level shared index
+    // Primary: home-level shared index, replacing only this root's entry
     if let Some(home_path) = home_index_path() {
         if let Some(parent) = home_path.parent() {
             fs::create_dir_all(parent)
                 .with_context(|| format!("Failed to create directory {}", parent.display()))?;
         }
-        atomic_write(&home_path, &content)?;
+        let mut shared = load_shared()?;
+        shared.roots.insert(root_key(root), idx.clone());
+        let shared_content =
+            serde_json::to_string_pretty(&shared).context("Failed to serialize shared index")?;
+        atomic_write(&home_path, &shared_content)?;
     }
 
     // Legacy: root/.orbit/index.json (kept for compatibility/tests)
@@ -78,11 +144,49 @@ pub fn save(root: &Path, idx: &OrbitIndex) -> Result<()> {
     atomic_write(&legacy, &content)
 }
 
-/// Write-then-rename for atomic file updates
+/// Write-then-rename for atomic file updates. The temp file is unique per
+/// process and call, and both it and the directory are fsynced, so concurrent
+/// writers never share a temp file and a crash leaves the old or new content.
 pub fn atomic_write(path: &Path, content: &str) -> Result<()> {
-    let tmp = path.with_extension("tmp");
-    fs::write(&tmp, content).with_context(|| format!("Failed to write {}", tmp.display()))?;
-    fs::rename(&tmp, path)
-        .with_context(|| format!("Failed to rename {} to {}", tmp.display(), path.display()))?;
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("orbit");
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = File::create(&tmp)
+        .and_then(|mut f| {
+            f.write_all(content.as_bytes())?;
+            f.sync_all()
+        })
+        .with_context(|| format!("Failed to write {}", tmp.display()))
+        .and_then(|_| {
+            fs::rename(&tmp, path).with_context(|| {
+                format!("Failed to rename {} to {}", tmp.display(), path.display())
+            })
+        });
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written?;
+    sync_dir(path)
+}
+
+/// Make a rename in `path`'s directory durable
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+    File::open(dir)
+        .and_then(|d| d.sync_all())
+        .with_context(|| format!("Failed to sync directory {}", dir.display()))
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> Result<()> {
     Ok(())
 }
@@ -0,0 +1,326 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDate};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::catalog::{self, ArtifactCatalog, ArtifactRecord};
+use crate::index::focus;
+use crate::index::store::{self, OrbitIndex};
+
+/// `2025-01-03`, `2025_01_03`, `2025.01.03` or `20250103`, not inside a longer number
+static DATE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:^|[^0-9])((?:19|20)\d{2})([-_.]?)(\d{2})([-_.]?)(\d{2})(?:[^0-9]|$)")
+        .expect("valid date regex")
+});
+
+/// Where a timeline date came from, in order of preference
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DateSource {
+    FileName,
+    Heading,
+    Modified,
+}
+
+/// First valid calendar date in `s`
+pub fn parse_date(s: &str) -> Option<NaiveDate> {
+    DATE_RE.captures_iter(s).find_map(|c| {
+        // Separators must agree: `2025-0103` is not a date
+        if c[2] != c[4] {
+            return None;
+        }
+        NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[3].parse().ok()?, c[5].parse().ok()?)
+    })
+}
+
+/// Date of an artifact from its file name, else from its first dated heading
+pub fn artifact_date(file_name: &str, text: &str) -> Option<(NaiveDate, DateSource)> {
+    if let Some(d) = parse_date(file_name) {
+        return Some((d, DateSource::FileName));
+    }
+    text.lines()
+        .map(str::trim)
+        .filter(|l| l.starts_with('#'))
+        .find_map(parse_date)
+        .map(|d| (d, DateSource::Heading))
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TimelineEntry {
+    pub date: NaiveDate,
+    pub source: DateSource,
+    pub artifact: ArtifactRecord,
+}
+
+/// Artifacts of `project` in chronological order; undated ones fall back to their mtime
+pub fn for_project(cat: &ArtifactCatalog, project: &str) -> Vec<TimelineEntry> {
+    let mut out: Vec<TimelineEntry> = cat
+        .artifacts
+        .iter()
+        .filter(|a| a.project == project)
+        .filter_map(|a| {
+            let (date, source) = match (a.date, a.date_source) {
+                (Some(d), Some(s)) => (d, s),
+                _ => (a.mtime?.date_naive(), DateSource::Modified),
+            };
+            Some(TimelineEntry {
+                date,
+                source,
+                artifact: a.clone(),
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then(a.artifact.mtime.cmp(&b.artifact.mtime))
+            .then(a.artifact.path.cmp(&b.artifact.path))
+    });
+    out
+}
+
+pub fn render_md(project: &str, entries: &[TimelineEntry]) -> String {
+    let mut s = format!("# Timeline — {}\n\n", project);
+    s.push_str(&format!(
+        "Generated {} from {} artifacts, oldest first. Dates come from file names, then headings, then modification times (marked *mtime*).\n",
+        Local::now().format("%Y-%m-%d %H:%M"),
+        entries.len()
+    ));
+    let mut day = None;
+    for e in entries {
+        if day != Some(e.date) {
+            s.push_str(&format!("\n## {}\n\n", e.date));
+            day = Some(e.date);
+        }
+        s.push_str(&format!(
+            "- **{}** {} — `{}`{}\n",
+            e.artifact.category,
+            e.artifact.label(),
+            e.artifact.path,
+            if e.source == DateSource::Modified {
+                " *mtime*"
+            } else {
+                ""
+            }
+        ));
+    }
+    if entries.is_empty() {
+        s.push_str("\nNo artifacts catalogued for this project.\n");
+    }
+    s
+}
+
+/// `.orbit/exports/timelines/<project>/timeline.md`
+pub fn export_path(root: &Path, project: &str) -> PathBuf {
+    let dir = match project {
+        "" | "." => "_root",
+        p => p,
+    };
+    root.join(".orbit")
+        .join("exports")
+        .join("timelines")
+        .join(dir)
+        .join("timeline.md")
+}
+
+fn write_timeline(root: &Path, cat: &ArtifactCatalog, project: &str) -> Result<PathBuf> {
+    let path = export_path(root, project);
+    let parent = path.parent().expect("timeline path has a parent");
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    fs::write(&path, render_md(project, &for_project(cat, project)))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Write a timeline for every pinned project; returns the files written
+pub fn export_pinned(root: &Path, cat: &ArtifactCatalog) -> Result<Vec<PathBuf>> {
+    let focus = focus::load_focus(root).unwrap_or_default();
+    focus
+        .pinned
+        .iter()
+        .map(|p| write_timeline(root, cat, p))
+        .collect()
+}
+
+/// `project` as listed in the index; anything else (absolute, `..`, unknown)
+/// would write outside `.orbit/exports` or print an empty timeline
+fn indexed_project(idx: &OrbitIndex, project: &str) -> Result<String> {
+    let p = project.trim_end_matches('/');
+    if !idx.projects.iter().any(|e| e.path == p) {
+        bail!(
+            "'{}' is not a project in the index (use a path as listed by `orbit status`)",
+            project
+        );
+    }
+    Ok(p.to_string())
+}
+
+pub fn handle_timeline(
+    root_str: &str,
+    project: Option<&str>,
+    export: bool,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+    let cat = match catalog::load(root)? {
+        Some(c) => c,
+        None => catalog::refresh(root)?.0,
+    };
+    let projects: Vec<String> = match project {
+        Some(p) => vec![indexed_project(&store::load(root)?, p)?],
+        None => focus::load_focus(root).unwrap_or_default().pinned,
+    };
+    if projects.is_empty() {
+        bail!("No pinned projects; pass a project path or pin one with `orbit focus --add`");
+    }
+
+    if export {
+        if dry_run {
+            for p in &projects {
+                println!("[dry-run] would write {}", export_path(root, p).display());
+            }
+            return Ok(());
+        }
+        let mut written = Vec::new();
+        for p in &projects {
+            written.push(write_timeline(root, &cat, p)?.display().to_string());
+        }
+        if json_output {
+            println!("{}", serde_json::json!({ "written": written }));
+        } else {
+            for w in &written {
+                println!("Wrote {}", w);
+            }
+        }
+        return Ok(());
+    }
+
+    if json_output {
+        let timelines: Vec<_> = projects
+            .iter()
+            .map(|p| serde_json::json!({ "project": p, "entries": for_project(&cat, p) }))
+            .collect();
+        println!("{}", serde_json::json!({ "timelines": timelines }));
+        return Ok(());
+    }
+    for p in &projects {
+        let entries = for_project(&cat, p);
+        println!("{} ({} artifacts)", p, entries.len());
+        for e in &entries {
+            println!(
+                "  {}  {:<10} {}  ({}{})",
+                e.date,
+                e.artifact.category,
+                e.artifact.label(),
+                e.artifact.path,
+                if e.source == DateSource::Modified {
+                    ", mtime"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{artifact_date, for_project, indexed_project, parse_date, render_md, DateSource};
+    use crate::index::catalog::{ArtifactCatalog, ArtifactRecord};
+    use crate::index::store::OrbitIndex;
+    use crate::model::project::ProjectEntry;
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn only_indexed_projects_get_a_timeline() {
+        let idx = OrbitIndex {
+            projects: vec![ProjectEntry::stub("."), ProjectEntry::stub("apps/web")],
+            ..Default::default()
+        };
+        assert_eq!(indexed_project(&idx, "apps/web/").unwrap(), "apps/web");
+        assert_eq!(indexed_project(&idx, ".").unwrap(), ".");
+        for bad in ["/tmp/x", "../..", "apps/../apps/web", "apps", ""] {
+            assert!(indexed_project(&idx, bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn dates_are_parsed_from_common_spellings() {
+        assert_eq!(parse_date("HANDOFF_2025-01-03.md"), Some(ymd(2025, 1, 3)));
+        assert_eq!(parse_date("session-20250214-2.md"), Some(ymd(2025, 2, 14)));
+        assert_eq!(parse_date("plan.2024.12.31.txt"), Some(ymd(2024, 12, 31)));
+        assert_eq!(parse_date("notes-2025-13-01.md"), None, "month 13");
+        assert_eq!(
+            parse_date("build-120250101.md"),
+            None,
+            "inside a longer number"
+        );
+        assert_eq!(parse_date("v2025-0103.md"), None, "mixed separators");
+    }
+
+    #[test]
+    fn file_name_wins_over_heading() {
+        let text = "intro 2020-01-01\n# Handoff — 2025-03-09\n## 2025-03-10\n";
+        assert_eq!(
+            artifact_date("HANDOFF.md", text),
+            Some((ymd(2025, 3, 9), DateSource::Heading))
+        );
+        assert_eq!(
+            artifact_date("HANDOFF_2025-03-01.md", text),
+            Some((ymd(2025, 3, 1), DateSource::FileName))
+        );
+        assert_eq!(artifact_date("HANDOFF.md", "no dates 2025-01-01"), None);
+    }
+
+    #[test]
+    fn timeline_is_chronological_with_mtime_fallback() {
+        let rec = |path: &str, date: Option<NaiveDate>, mtime_day: u32| ArtifactRecord {
+            project: "app".into(),
+            path: path.into(),
+            category: "handoff".into(),
+            title: None,
+            size: 1,
+            mtime: Some(Local.with_ymd_and_hms(2025, 2, mtime_day, 9, 0, 0).unwrap()),
+            date,
+            date_source: date.map(|_| DateSource::FileName),
+        };
+        let cat = ArtifactCatalog {
+            generated_at: None,
+            artifacts: vec![
+                rec("HANDOFF_2025-02-10.md", Some(ymd(2025, 2, 10)), 20),
+                rec("session.md", None, 5),
+                rec("HANDOFF_2025-01-02.md", Some(ymd(2025, 1, 2)), 20),
+                ArtifactRecord {
+                    project: "other".into(),
+                    ..rec("plan.md", None, 1)
+                },
+            ],
+        };
+        let t = for_project(&cat, "app");
+        let paths: Vec<&str> = t.iter().map(|e| e.artifact.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "HANDOFF_2025-01-02.md",
+                "session.md",
+                "HANDOFF_2025-02-10.md"
+            ]
+        );
+        assert_eq!(t[1].source, DateSource::Modified);
+
+        let md = render_md("app", &t);
+        assert!(md.starts_with("# Timeline — app"));
+        assert!(md.find("## 2025-01-02").unwrap() < md.find("## 2025-02-05").unwrap());
+        assert!(md.contains("`session.md` *mtime*"));
+    }
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::index::journal::{self, Target};
+use crate::index::lock::{self, FileLock};
 use crate::index::store::atomic_write;
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -28,8 +30,14 @@ pub fn load_whitelist(root: &Path) -> Result<Whitelist> {
     serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", p.display()))
 }
 
+/// Lock the whitelist for a read-modify-write spanning load and save
+pub fn lock(root: &Path) -> Result<FileLock> {
+    lock::acquire(&whitelist_path(root))
+}
+
 pub fn save_whitelist(root: &Path, wl: &Whitelist) -> Result<()> {
     let p = whitelist_path(root);
+    let _lock = lock(root)?;
     if let Some(parent) = p.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create directory {}", parent.display()))?;
@@ -38,11 +46,12 @@ pub fn save_whitelist(root: &Path, wl: &Whitelist) -> Result<()> {
     atomic_write(&p, &content)
 }
 
-/// Check if a path should be protected (whitelist)
-pub fn is_protected(path: &Path, wl: &Whitelist) -> bool {
+/// Check if a path should be protected (whitelist).
+/// Relative entries are relative to the workspace `root`.
+pub fn is_protected(root: &Path, path: &Path, wl: &Whitelist) -> bool {
     wl.paths.iter().any(|p| {
         let wp = PathBuf::from(p);
-        path.starts_with(&wp)
+        path.starts_with(&wp) || (wp.is_relative() && path.starts_with(root.join(&wp)))
     })
 }
 
@@ -54,6 +63,7 @@ pub fn handle_whitelist(
     json_output: bool,
 ) -> Result<()> {
     let root = Path::new(root_str);
+    let _lock = lock(root)?;
     let mut wl = load_whitelist(root)?;
     if list {
         if json_output {
@@ -70,11 +80,13 @@ pub fn handle_whitelist(
         if !full.exists() {
             anyhow::bail!("Path does not exist: {}", full.display());
         }
+        let before = wl.clone();
         if !wl.paths.iter().any(|p| p == &a) {
             wl.paths.push(a);
             wl.paths.sort();
         }
         save_whitelist(root, &wl)?;
+        record_change(root, "whitelist add", &before, &wl)?;
         if json_output {
             println!("{}", serde_json::json!({ "status": "updated", "paths": wl.paths }));
         } else {
@@ -82,8 +94,10 @@ pub fn handle_whitelist(
         }
     }
     if let Some(r) = remove {
+        let before = wl.clone();
         wl.paths.retain(|p| p != &r);
         save_whitelist(root, &wl)?;
+        record_change(root, "whitelist remove", &before, &wl)?;
         if json_output {
             println!("{}", serde_json::json!({ "status": "updated", "paths": wl.paths }));
         } else {
@@ -92,3 +106,15 @@ pub fn handle_whitelist(
     }
     Ok(())
 }
+
+fn record_change(root: &Path, op: &str, before: &Whitelist, after: &Whitelist) -> Result<()> {
+    journal::record(
+        root,
+        op,
+        Target::Whitelist,
+        journal::list_summary(&before.paths, &after.paths),
+        before,
+        after,
+    )?;
+    Ok(())
+}
@@ -1,4 +1,6 @@
+pub mod cleanup;
 pub mod cli;
+pub mod config;
 pub mod export;
 pub mod feature;
 pub mod index;
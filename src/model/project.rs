@@ -1,5 +1,6 @@
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectEntry {
@@ -11,12 +12,255 @@ pub struct ProjectEntry {
     pub pinned: bool,
     pub latest_mtime: Option<DateTime<Local>>,
     pub size_bytes: Option<u64>,
-    pub artifact_count: u32,
+    /// Artifact files per category (`handoff`, `plan`, …). Also written as the
+    /// legacy `artifact_count` total.
+    #[serde(flatten, with = "artifacts_compat")]
+    pub artifacts: BTreeMap<String, u32>,
     pub has_git: bool,
-    pub has_rust: bool,
-    pub has_node: bool,
-    pub has_python: bool,
+    /// Detected ecosystems (`rust`, `go`, `terraform`, …), sorted.
+    /// Also written as the legacy `has_rust`/`has_node`/`has_python` booleans.
+    #[serde(flatten, with = "ecosystems_compat")]
+    pub ecosystems: Vec<String>,
     pub fingerprint: Option<String>,
+    /// MinHash signature over (relative path, content hash) of every file,
+    /// used for near-duplicate scoring; `None` for empty trees and without
+    /// `census --similarity`
+    #[serde(default)]
+    pub minhash: Option<Vec<u32>>,
+    /// Relative path of the nearest enclosing project, if nested
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Workspace/submodule members folded into this project (relative paths)
+    #[serde(default)]
+    pub members: Vec<String>,
+    /// Repository state read from `.git` (present when `has_git`)
+    #[serde(default)]
+    pub git: Option<GitInfo>,
+    /// Line counts per language, keyed by language name (present after `census --loc`)
+    #[serde(default)]
+    pub loc: Option<BTreeMap<String, LocCounts>>,
+    /// Build-output directories (target/, node_modules/, venvs), present after `census --reclaim`
+    #[serde(default)]
+    pub build_dirs: Option<Vec<BuildDir>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GitInfo {
+    /// Current branch; `None` when HEAD is detached
+    pub branch: Option<String>,
+    /// HEAD commit id
+    pub head: Option<String>,
+    /// Committer date of the HEAD commit
+    pub head_date: Option<DateTime<Local>>,
+    /// Tracked changes in the worktree or index
+    pub dirty: bool,
+    pub untracked: u32,
+    pub stashes: u32,
+    /// Upstream tracking ref, e.g. `origin/main`
+    pub upstream: Option<String>,
+    pub ahead: Option<u32>,
+    pub behind: Option<u32>,
+}
+
+impl GitInfo {
+    /// Work that only exists locally: uncommitted, untracked, stashed or unpushed
+    pub fn has_uncommitted_work(&self) -> bool {
+        self.dirty || self.untracked > 0 || self.stashes > 0 || self.ahead.unwrap_or(0) > 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LocCounts {
+    pub files: u32,
+    pub code: u64,
+    pub comment: u64,
+    pub blank: u64,
+}
+
+impl LocCounts {
+    pub fn add(&mut self, other: &LocCounts) {
+        self.files += other.files;
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+/// A build-output directory inside a project and what deleting it would free
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BuildDir {
+    /// Path relative to the project root
+    pub path: String,
+    /// Which toolchain produced it (`cargo`, `node`, `python`, …)
+    pub kind: String,
+    pub size_bytes: u64,
+    pub files: u64,
+    /// Newest mtime of anything inside
+    pub last_touched: Option<DateTime<Local>>,
+}
+
+impl ProjectEntry {
+    /// Bare standalone entry for tests; set other fields with `..ProjectEntry::stub(path)`
+    #[cfg(test)]
+    pub fn stub(path: &str) -> Self {
+        Self {
+            path: path.into(),
+            kind: ProjectKind::Standalone,
+            pinned: false,
+            latest_mtime: None,
+            size_bytes: Some(0),
+            artifacts: Default::default(),
+            has_git: false,
+            ecosystems: vec![],
+            fingerprint: None,
+            minhash: None,
+            parent: None,
+            members: vec![],
+            git: None,
+            loc: None,
+            build_dirs: None,
+        }
+    }
+
+    pub fn has_ecosystem(&self, name: &str) -> bool {
+        self.ecosystems.iter().any(|e| e == name)
+    }
+
+    pub fn has_uncommitted_work(&self) -> bool {
+        self.git
+            .as_ref()
+            .map(|g| g.has_uncommitted_work())
+            .unwrap_or(false)
+    }
+
+    /// Artifact files across all categories
+    pub fn artifact_count(&self) -> u32 {
+        self.artifacts.values().sum()
+    }
+
+    /// Bytes held by build-output directories (0 when not measured)
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.build_dirs.iter().flatten().map(|d| d.size_bytes).sum()
+    }
+
+    /// Line counts summed across all languages
+    pub fn loc_total(&self) -> Option<LocCounts> {
+        self.loc.as_ref().map(|m| {
+            let mut t = LocCounts::default();
+            for c in m.values() {
+                t.add(c);
+            }
+            t
+        })
+    }
+}
+
+/// Serde shim keeping indexes readable by (and from) pre-`ecosystems` consumers
+mod ecosystems_compat {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Out<'a> {
+        ecosystems: &'a [String],
+        has_rust: bool,
+        has_node: bool,
+        has_python: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct In {
+        ecosystems: Option<Vec<String>>,
+        #[serde(default)]
+        has_rust: bool,
+        #[serde(default)]
+        has_node: bool,
+        #[serde(default)]
+        has_python: bool,
+    }
+
+    pub fn serialize<S: Serializer>(v: &[String], s: S) -> Result<S::Ok, S::Error> {
+        let has = |n: &str| v.iter().any(|e| e == n);
+        Out {
+            ecosystems: v,
+            has_rust: has("rust"),
+            has_node: has("node"),
+            has_python: has("python"),
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+        let i = In::deserialize(d)?;
+        if let Some(list) = i.ecosystems {
+            return Ok(list);
+        }
+        let legacy = [
+            (i.has_node, "node"),
+            (i.has_python, "python"),
+            (i.has_rust, "rust"),
+        ];
+        Ok(legacy
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, n)| n.to_string())
+            .collect())
+    }
+}
+
+/// Serde shim for indexes written before per-category artifact counts; their
+/// `artifact_count` total is read back under the `other` category
+mod artifacts_compat {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Out<'a> {
+        artifacts: &'a BTreeMap<String, u32>,
+        artifact_count: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct In {
+        artifacts: Option<BTreeMap<String, u32>>,
+        #[serde(default)]
+        artifact_count: u32,
+    }
+
+    pub fn serialize<S: Serializer>(v: &BTreeMap<String, u32>, s: S) -> Result<S::Ok, S::Error> {
+        Out {
+            artifacts: v,
+            artifact_count: v.values().sum(),
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<String, u32>, D::Error> {
+        let i = In::deserialize(d)?;
+        if let Some(map) = i.artifacts {
+            return Ok(map);
+        }
+        let mut map = BTreeMap::new();
+        if i.artifact_count > 0 {
+            map.insert("other".to_string(), i.artifact_count);
+        }
+        Ok(map)
+    }
+}
+
+/// `512 B`, `1.5 KiB`, `3.2 GiB`
+pub fn size_label(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut v = bytes as f64;
+    let mut u = 0;
+    while v >= 1024.0 && u + 1 < UNITS.len() {
+        v /= 1024.0;
+        u += 1;
+    }
+    if u == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", v, UNITS[u])
+    }
 }
 
 /// Sync pinned flags from focus list to project entries.
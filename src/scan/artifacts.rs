@@ -1,43 +1,228 @@
+use anyhow::{bail, Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::sync::Arc;
 
-pub const ARTIFACT_KEYWORDS: &[&str] = &[
-    "handoff",
-    "agent",
-    "claude",
-    "export",
-    "plan",
-    "roadmap",
-    "decision",
-    "prompt",
-    "session",
-    "conversation",
-    "summary",
+/// Built-in categories and the file-name keywords that select them.
+/// Only text documents (`.md`, `.markdown`, `.txt`) qualify.
+pub const DEFAULT_CATEGORIES: &[(&str, &[&str])] = &[
+    ("handoff", &["handoff"]),
+    ("plan", &["plan", "roadmap", "decision"]),
+    ("prompt", &["prompt"]),
+    ("transcript", &["session", "conversation", "export"]),
+    ("summary", &["summary"]),
+    ("agent", &["agent", "claude"]),
 ];
 
-pub fn is_artifact_name(path: &Path) -> bool {
-    if let Some(n) = path.file_name().and_then(|s| s.to_str()) {
-        let ln = n.to_lowercase();
-        return ARTIFACT_KEYWORDS.iter().any(|k| ln.contains(k));
+const DEFAULT_EXTENSIONS: &str = "md|markdown|txt";
+
+/// One `[[artifacts.rules]]` entry; exactly one of `glob`, `regex` or `extension` is set
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ArtifactRuleConfig {
+    pub category: String,
+    /// Glob over the project-relative path; bare names match at any depth (case-insensitive)
+    pub glob: Option<String>,
+    /// Regex over the `/`-separated project-relative path
+    pub regex: Option<String>,
+    /// File extension without the dot (case-insensitive)
+    pub extension: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Glob(GlobMatcher),
+    Regex(Regex),
+    Extension(String),
+}
+
+impl Matcher {
+    fn is_match(&self, rel: &str) -> bool {
+        match self {
+            Matcher::Glob(g) => g.is_match(rel),
+            Matcher::Regex(r) => r.is_match(rel),
+            Matcher::Extension(e) => Path::new(rel)
+                .extension()
+                .and_then(|s| s.to_str())
+                .map(|x| x.eq_ignore_ascii_case(e))
+                .unwrap_or(false),
+        }
     }
-    false
+}
+
+#[derive(Debug, Clone)]
+struct ArtifactRule {
+    category: String,
+    matcher: Matcher,
+}
+
+/// Ordered artifact rules shared by census counting and snapshots.
+/// User rules are tried before the built-in ones; the first match wins.
+#[derive(Debug, Clone)]
+pub struct ArtifactRules {
+    rules: Arc<Vec<ArtifactRule>>,
+}
+
+impl Default for ArtifactRules {
+    fn default() -> Self {
+        Self::new(&[], true).expect("default artifact rules are valid")
+    }
+}
+
+impl ArtifactRules {
+    pub fn new(user: &[ArtifactRuleConfig], with_defaults: bool) -> Result<Self> {
+        let mut rules = Vec::new();
+        for r in user {
+            rules.push(compile(r)?);
+        }
+        if with_defaults {
+            for (category, keywords) in DEFAULT_CATEGORIES {
+                let pat = format!(
+                    r"(?i)(^|/)[^/]*({})[^/]*\.({})$",
+                    keywords.join("|"),
+                    DEFAULT_EXTENSIONS
+                );
+                rules.push(ArtifactRule {
+                    category: category.to_string(),
+                    matcher: Matcher::Regex(Regex::new(&pat).expect("valid default regex")),
+                });
+            }
+        }
+        Ok(Self {
+            rules: Arc::new(rules),
+        })
+    }
+
+    /// Category of the file at `rel` (relative to its project), if it is an artifact
+    pub fn category(&self, rel: &Path) -> Option<&str> {
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .find(|r| r.matcher.is_match(&rel))
+            .map(|r| r.category.as_str())
+    }
+}
+
+fn compile(r: &ArtifactRuleConfig) -> Result<ArtifactRule> {
+    let category = r.category.trim();
+    if category.is_empty() {
+        bail!("Artifact rule is missing a category");
+    }
+    let matcher = match (&r.glob, &r.regex, &r.extension) {
+        (Some(g), None, None) => {
+            let pat = if g.contains('/') {
+                g.clone()
+            } else {
+                format!("**/{g}")
+            };
+            let glob = GlobBuilder::new(&pat)
+                .case_insensitive(true)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid artifact glob '{}'", g))?;
+            Matcher::Glob(glob.compile_matcher())
+        }
+        (None, Some(re), None) => Matcher::Regex(
+            RegexBuilder::new(re)
+                .build()
+                .with_context(|| format!("Invalid artifact regex '{}'", re))?,
+        ),
+        (None, None, Some(ext)) => Matcher::Extension(ext.trim_start_matches('.').to_string()),
+        _ => bail!(
+            "Artifact rule '{}' needs exactly one of glob, regex or extension",
+            category
+        ),
+    };
+    Ok(ArtifactRule {
+        category: category.to_string(),
+        matcher,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::is_artifact_name;
+    use super::{ArtifactRuleConfig, ArtifactRules};
     use std::path::Path;
 
+    fn cat<'a>(rules: &'a ArtifactRules, p: &str) -> Option<&'a str> {
+        rules.category(Path::new(p))
+    }
+
     #[test]
-    fn detects_known_artifacts() {
-        assert!(is_artifact_name(Path::new("HANDOFF_notes.md")));
-        assert!(is_artifact_name(Path::new("session_2025-01-01.txt")));
-        assert!(is_artifact_name(Path::new("roadmap.md")));
+    fn defaults_categorize_known_artifacts() {
+        let r = ArtifactRules::default();
+        assert_eq!(cat(&r, "HANDOFF_notes.md"), Some("handoff"));
+        assert_eq!(cat(&r, "session_2025-01-01.txt"), Some("transcript"));
+        assert_eq!(cat(&r, "docs/roadmap.md"), Some("plan"));
+        assert_eq!(cat(&r, "decisions/DECISION-004.markdown"), Some("plan"));
+        assert_eq!(cat(&r, "CLAUDE.md"), Some("agent"));
+    }
+
+    #[test]
+    fn defaults_ignore_non_artifacts() {
+        let r = ArtifactRules::default();
+        assert_eq!(cat(&r, "Cargo.toml"), None);
+        assert_eq!(cat(&r, "src/main.rs"), None);
+        assert_eq!(cat(&r, "notes.txt"), None);
+        assert_eq!(
+            cat(&r, "src/agent.rs"),
+            None,
+            "source files are not artifacts"
+        );
+        assert_eq!(cat(&r, "plans/notes.md"), None, "only the file name counts");
+    }
+
+    #[test]
+    fn user_rules_come_first_and_support_each_matcher() {
+        let rule = |category: &str| ArtifactRuleConfig {
+            category: category.into(),
+            ..Default::default()
+        };
+        let user = vec![
+            ArtifactRuleConfig {
+                glob: Some("docs/adr/*.md".into()),
+                ..rule("adr")
+            },
+            ArtifactRuleConfig {
+                regex: Some(r"(^|/)chat-\d+\.jsonl$".into()),
+                ..rule("transcript")
+            },
+            ArtifactRuleConfig {
+                extension: Some(".ipynb".into()),
+                ..rule("notebook")
+            },
+        ];
+        let r = ArtifactRules::new(&user, true).unwrap();
+        assert_eq!(cat(&r, "docs/adr/0001-plan-storage.md"), Some("adr"));
+        assert_eq!(cat(&r, "logs/chat-42.jsonl"), Some("transcript"));
+        assert_eq!(cat(&r, "Analysis.IPYNB"), Some("notebook"));
+        assert_eq!(cat(&r, "HANDOFF.md"), Some("handoff"));
+
+        let only_user = ArtifactRules::new(&user, false).unwrap();
+        assert_eq!(cat(&only_user, "HANDOFF.md"), None);
     }
 
     #[test]
-    fn ignores_non_artifacts() {
-        assert!(!is_artifact_name(Path::new("Cargo.toml")));
-        assert!(!is_artifact_name(Path::new("src/main.rs")));
-        assert!(!is_artifact_name(Path::new("notes.txt")));
+    fn invalid_rules_are_rejected() {
+        let both = ArtifactRuleConfig {
+            category: "x".into(),
+            glob: Some("*.md".into()),
+            extension: Some("md".into()),
+            ..Default::default()
+        };
+        assert!(ArtifactRules::new(&[both], true).is_err());
+        let bad_regex = ArtifactRuleConfig {
+            category: "x".into(),
+            regex: Some("(".into()),
+            ..Default::default()
+        };
+        assert!(ArtifactRules::new(&[bad_regex], true).is_err());
+        let no_category = ArtifactRuleConfig {
+            extension: Some("md".into()),
+            ..Default::default()
+        };
+        assert!(ArtifactRules::new(&[no_category], true).is_err());
     }
 }
@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+use crate::model::project::BuildDir;
+use crate::scan::similarity::rel_key;
+
+/// When a directory with a build-output name really is one
+enum Requires {
+    /// Always (the name alone is unambiguous)
+    Any,
+    /// One of these files sits next to it
+    Sibling(&'static [&'static str]),
+    /// This file is inside it
+    Inside(&'static str),
+    /// One of these files sits next to it and the directory is git-ignored
+    /// (plenty of repos keep checked-in sources in `dist/` or `build/`)
+    IgnoredSibling(&'static [&'static str]),
+}
+
+/// Directory name, toolchain label, and how to confirm it. First match wins.
+const RULES: &[(&str, &str, Requires)] = &[
+    ("node_modules", "node", Requires::Any),
+    ("target", "cargo", Requires::Sibling(&["Cargo.toml"])),
+    ("target", "maven", Requires::Sibling(&["pom.xml"])),
+    (".venv", "python", Requires::Inside("pyvenv.cfg")),
+    ("venv", "python", Requires::Inside("pyvenv.cfg")),
+    ("__pycache__", "python", Requires::Any),
+    (".pytest_cache", "python", Requires::Any),
+    (".mypy_cache", "python", Requires::Any),
+    (".tox", "python", Requires::Any),
+    (".next", "node", Requires::Sibling(&["package.json"])),
+    ("dist", "node", Requires::IgnoredSibling(&["package.json"])),
+    ("build", "node", Requires::IgnoredSibling(&["package.json"])),
+    (
+        "dist",
+        "python",
+        Requires::IgnoredSibling(&["pyproject.toml", "setup.py"]),
+    ),
+    (
+        "build",
+        "python",
+        Requires::IgnoredSibling(&["pyproject.toml", "setup.py"]),
+    ),
+    (
+        "build",
+        "gradle",
+        Requires::Sibling(&["build.gradle", "build.gradle.kts"]),
+    ),
+];
+
+/// Toolchain label if `dir` is a build-output directory
+pub fn classify(dir: &Path) -> Option<&'static str> {
+    let name = dir.file_name()?.to_str()?;
+    let parent = dir.parent()?;
+    RULES
+        .iter()
+        .filter(|(n, _, _)| *n == name)
+        .find(|(_, _, req)| match req {
+            Requires::Any => true,
+            Requires::Sibling(files) => files.iter().any(|f| parent.join(f).is_file()),
+            Requires::Inside(file) => dir.join(file).is_file(),
+            Requires::IgnoredSibling(files) => {
+                files.iter().any(|f| parent.join(f).is_file()) && is_git_ignored(dir)
+            }
+        })
+        .map(|(_, kind, _)| *kind)
+}
+
+/// Whether `.gitignore` files (of `dir`'s parents) or the repository's excludes
+/// ignore `dir`
+fn is_git_ignored(dir: &Path) -> bool {
+    let Some(parent) = dir.parent() else {
+        return false;
+    };
+    let listed = WalkBuilder::new(parent)
+        .standard_filters(false)
+        .hidden(false)
+        .git_ignore(true)
+        .git_exclude(true)
+        .parents(true)
+        .require_git(false)
+        .max_depth(Some(1))
+        .build()
+        .filter_map(|e| e.ok())
+        .any(|e| e.path() == dir);
+    !listed
+}
+
+/// Find and measure build-output directories under `project_root`, skipping
+/// nested projects in `exclude` (they are measured on their own).
+/// Build directories are not descended into for further matches.
+pub fn measure(project_root: &Path, exclude: &[PathBuf]) -> Result<Vec<BuildDir>> {
+    let mut out = Vec::new();
+    let mut it = walkdir::WalkDir::new(project_root)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter();
+    while let Some(entry) = it.next() {
+        let entry =
+            entry.with_context(|| format!("Failed to read entry in {}", project_root.display()))?;
+        if !entry.file_type().is_dir() || entry.depth() == 0 {
+            continue;
+        }
+        let p = entry.path();
+        if entry.file_name() == ".git" || exclude.iter().any(|x| x == p) {
+            it.skip_current_dir();
+            continue;
+        }
+        if let Some(kind) = classify(p) {
+            out.push(dir_usage(project_root, p, kind)?);
+            it.skip_current_dir();
+        }
+    }
+    Ok(out)
+}
+
+fn dir_usage(project_root: &Path, dir: &Path, kind: &str) -> Result<BuildDir> {
+    let mut size_bytes = 0;
+    let mut files = 0;
+    let mut last_touched: Option<DateTime<Local>> = None;
+    for entry in walkdir::WalkDir::new(dir).follow_links(false) {
+        let entry = entry.with_context(|| format!("Failed to read entry in {}", dir.display()))?;
+        let md = entry
+            .metadata()
+            .with_context(|| format!("Failed to read metadata for {}", entry.path().display()))?;
+        if md.is_file() {
+            size_bytes += md.len();
+            files += 1;
+        }
+        if let Ok(m) = md.modified() {
+            let m = DateTime::<Local>::from(m);
+            if last_touched.is_none_or(|l| m > l) {
+                last_touched = Some(m);
+            }
+        }
+    }
+    Ok(BuildDir {
+        path: rel_key(project_root, dir),
+        kind: kind.to_string(),
+        size_bytes,
+        files,
+        last_touched,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, measure};
+    use std::fs;
+
+    #[test]
+    fn classify_requires_toolchain_evidence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for d in ["target", "build", "node_modules", ".venv"] {
+            fs::create_dir_all(root.join(d)).unwrap();
+        }
+        assert_eq!(classify(&root.join("node_modules")), Some("node"));
+        assert_eq!(classify(&root.join("target")), None);
+        assert_eq!(classify(&root.join("build")), None);
+        assert_eq!(classify(&root.join(".venv")), None);
+
+        fs::write(root.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(root.join(".venv").join("pyvenv.cfg"), "home = /usr").unwrap();
+        assert_eq!(classify(&root.join("target")), Some("cargo"));
+        assert_eq!(classify(&root.join(".venv")), Some("python"));
+
+        // Node `build/` counts only when it is git-ignored
+        fs::write(root.join("package.json"), "{}").unwrap();
+        assert_eq!(classify(&root.join("build")), None);
+        fs::write(root.join(".gitignore"), "/build\n").unwrap();
+        assert_eq!(classify(&root.join("build")), Some("node"));
+    }
+
+    #[test]
+    fn tracked_python_build_dir_is_not_build_output() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::write(root.join("build").join("__init__.py"), "").unwrap();
+        fs::write(root.join("pyproject.toml"), "[project]").unwrap();
+        assert_eq!(classify(&root.join("build")), None);
+        assert_eq!(classify(&root.join("dist")), None);
+        assert!(measure(root, &[]).unwrap().is_empty());
+
+        fs::write(root.join(".gitignore"), "dist/\n").unwrap();
+        assert_eq!(classify(&root.join("dist")), Some("python"));
+        assert_eq!(classify(&root.join("build")), None);
+    }
+
+    #[test]
+    fn measure_sums_build_dirs_and_skips_nested_projects() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("Cargo.toml"), "[package]").unwrap();
+        fs::create_dir_all(root.join("target").join("debug")).unwrap();
+        fs::write(
+            root.join("target").join("debug").join("app"),
+            vec![0u8; 100],
+        )
+        .unwrap();
+        fs::write(root.join("target").join("CACHEDIR.TAG"), "x").unwrap();
+        let web = root.join("web");
+        fs::create_dir_all(web.join("node_modules").join("left-pad")).unwrap();
+        fs::write(
+            web.join("node_modules").join("left-pad").join("i.js"),
+            "1234",
+        )
+        .unwrap();
+        let child = root.join("child");
+        fs::create_dir_all(child.join("node_modules")).unwrap();
+        fs::write(child.join("node_modules").join("x.js"), "x").unwrap();
+
+        let dirs = measure(root, &[child]).unwrap();
+        let got: Vec<(&str, &str, u64, u64)> = dirs
+            .iter()
+            .map(|d| (d.path.as_str(), d.kind.as_str(), d.size_bytes, d.files))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("target", "cargo", 101, 2),
+                ("web/node_modules", "node", 4, 1)
+            ]
+        );
+        assert!(dirs.iter().all(|d| d.last_touched.is_some()));
+    }
+}
@@ -1,94 +1,452 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local, NaiveDate, TimeZone};
-use std::path::Path;
-use walkdir::WalkDir;
-
-const SKIP_DIRS: &[&str] = &[
-    "node_modules",
-    "target",
-    ".git",
-    "__pycache__",
-    "venv",
-    ".venv",
-    "dist",
-    "build",
-    ".next",
-    "vendor",
-];
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
+use crate::config;
 use crate::feature;
-use crate::index::{focus::load_focus, store};
-use crate::model::project::{sync_pinned_flags, ProjectEntry, ProjectKind};
-use crate::scan::{artifacts, discover, fingerprint, progress::Progress};
+use crate::index::journal::{self, Target};
+use crate::index::{catalog, focus::load_focus, history, store};
+use crate::model::project::{sync_pinned_flags, LocCounts, ProjectEntry, ProjectKind};
+use crate::scan::delta::{self, CensusDelta, DeltaOptions};
+use crate::scan::incremental::{self, CachedProject, CensusCache};
+use crate::scan::rules::{self, ScanRules};
+use crate::scan::similarity::{self, MinHasher};
+use crate::scan::{build_dirs, discover, ecosystem, fingerprint, git, loc, progress::Progress};
 
-type ProjectSummary = (Option<DateTime<Local>>, u64, u32, (bool, bool, bool, bool));
+/// Everything gathered by a single walk of a project tree
+struct ProjectSummary {
+    latest: Option<DateTime<Local>>,
+    size_bytes: u64,
+    /// Artifact files per category
+    artifacts: BTreeMap<String, u32>,
+    has_git: bool,
+    /// Sorted ecosystem names
+    ecosystems: Vec<String>,
+    /// Per-language line counts, only when the LOC pass is enabled
+    loc: Option<BTreeMap<String, LocCounts>>,
+    minhash: Option<Vec<u32>>,
+}
+
+/// Tunables for a census run (shared by `orbit census`, `orbit ci` and the TUI)
+#[derive(Debug, Clone)]
+pub struct CensusOptions {
+    pub depth: usize,
+    pub since: Option<String>,
+    /// Worker threads for the summarize/fingerprint stage (0 = available parallelism)
+    pub jobs: usize,
+    /// Ignore the census cache and re-summarize every project
+    pub full: bool,
+    /// Count files matched by .gitignore/.orbitignore (audit mode)
+    pub no_ignore: bool,
+    /// Count code/comment/blank lines per language (reads every source file)
+    pub loc: bool,
+    /// Measure build-output directories (target/, node_modules/, venvs)
+    pub reclaim: bool,
+    /// Hash every file for MinHash near-duplicate signatures; without them,
+    /// duplicates are only found by identical fingerprints
+    pub similarity: bool,
+    /// Journal the index change and append a history run (off for watch rescans,
+    /// which would otherwise flood both)
+    pub record: bool,
+}
+
+impl Default for CensusOptions {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            since: None,
+            jobs: 0,
+            full: false,
+            no_ignore: false,
+            loc: false,
+            reclaim: false,
+            similarity: false,
+            record: true,
+        }
+    }
+}
 
 /// Main census entry point - orchestrates the pipeline stages
 pub fn run_census(
     root_str: &str,
-    depth: usize,
-    since: Option<&str>,
+    opts: &CensusOptions,
+    delta_opts: &DeltaOptions,
     json_output: bool,
     progress: Option<Progress>,
 ) -> Result<()> {
+    let root = Path::new(root_str);
+    let before = store::load(root)?;
+    let before_cat = catalog::load(root).ok().flatten();
+    let idx = update_index(root_str, opts, progress)?;
+    let after_cat = catalog::load(root)?.unwrap_or_default();
+    let delta = CensusDelta::between(
+        &before,
+        &idx,
+        before_cat.as_ref(),
+        &after_cat,
+        delta_opts.min_size_change,
+    );
+    let delta_md = if delta_opts.export {
+        Some(delta::write_md(root, &delta)?)
+    } else {
+        None
+    };
+    output_result(root, &idx, &delta, delta_md.as_deref(), json_output);
+    Ok(())
+}
+
+/// Run the census pipeline and save the index, artifact catalog and cache
+/// without printing a result (the TUI and watch mode use this directly)
+pub fn update_index(
+    root_str: &str,
+    opts: &CensusOptions,
+    progress: Option<Progress>,
+) -> Result<store::OrbitIndex> {
     let flags = feature::flags();
     let progress = progress.unwrap_or_else(|| Progress::new(flags.progress));
 
     let root = Path::new(root_str);
     let focus = load_focus(root).unwrap_or_default();
-    let cutoff = parse_cutoff(since)?;
+    let cutoff = parse_cutoff(opts.since.as_deref())?;
+    let cfg = config::load_config(root)?;
+    let rules = ScanRules::from_config(&cfg)?.with_ignore_files(!opts.no_ignore);
 
     // Pipeline stages
     progress.note("discovering projects");
-    let discovered = discover::discover_projects(root, depth)?;
+    let discovered = discover::discover_projects(root, opts.depth, &rules)?;
     progress.note(&format!("discovered {} projects", discovered.len()));
 
-    progress.note("summarizing projects");
-    let mut projects = build_project_entries(root, &discovered, cutoff)?;
+    // Incremental mode: reuse summaries computed with the same --since value
+    // and artifact rules
+    let artifact_rules = serde_json::to_string(&cfg.artifacts).unwrap_or_default();
+    let cache = if opts.full {
+        None
+    } else {
+        incremental::load_cache(root).ok().filter(|c| {
+            c.version == incremental::CACHE_VERSION
+                && c.since == opts.since
+                && c.artifact_rules == artifact_rules
+        })
+    };
+
+    progress.note(&format!(
+        "summarizing projects ({} workers)",
+        worker_count(opts.jobs, discovered.len())
+    ));
+    let built = build_project_entries(
+        root,
+        &discovered,
+        &rules,
+        cutoff,
+        opts,
+        cache.as_ref(),
+        &progress,
+    )?;
+    let reused = built.iter().filter(|b| b.reused).count();
+    progress.note(&format!(
+        "reused {} unchanged, re-summarized {}",
+        reused,
+        built.len() - reused
+    ));
+
+    let new_cache = CensusCache {
+        version: incremental::CACHE_VERSION,
+        since: opts.since.clone(),
+        artifact_rules,
+        projects: built
+            .iter()
+            .map(|b| {
+                (
+                    b.entry.path.clone(),
+                    CachedProject {
+                        stamp: b.stamp.clone(),
+                        entry: b.entry.clone(),
+                    },
+                )
+            })
+            .collect(),
+    };
+    let mut projects: Vec<ProjectEntry> = built.into_iter().map(|b| b.entry).collect();
 
     // Post-processing
     progress.note("synchronizing pins and detecting duplicates");
     sync_pinned_flags(&mut projects, &focus.pinned);
-    mark_duplicates_by_fingerprint(&mut projects);
+    mark_duplicates(&mut projects, cfg.similarity.threshold());
 
     // Persist and output
     progress.note("saving index");
-    let idx = save_index(root, root_str, projects)?;
-    output_result(root, &idx, json_output);
+    let idx = save_index(root, projects, opts.record)?;
+    if opts.record {
+        history::append(root, &idx)?;
+    }
+    incremental::save_cache(root, &new_cache)?;
+    progress.note("cataloging artifacts");
+    let (cat, search_idx) = catalog::build(root, &idx, &rules)?;
+    catalog::save(root, &cat, &search_idx)?;
+    Ok(idx)
+}
 
-    Ok(())
+/// Indexed projects re-summarized or dropped by [`refresh_projects`]
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct RefreshReport {
+    pub updated: Vec<String>,
+    /// Projects whose directory no longer exists
+    pub removed: Vec<String>,
+}
+
+/// Re-summarize only the indexed projects at `paths` (relative to `root`),
+/// keeping every other entry as is. Used by watch mode after file changes;
+/// `--loc`, `--reclaim` and `--similarity` data is kept up to date where the
+/// entry had it.
+pub fn refresh_projects(root: &Path, paths: &BTreeSet<String>) -> Result<RefreshReport> {
+    let cfg = config::load_config(root)?;
+    let rules = ScanRules::from_config(&cfg)?;
+    let focus = load_focus(root).unwrap_or_default();
+    let idx = store::load(root)?;
+    let mut report = RefreshReport::default();
+    // Classify with the window of the census that built the index
+    let since = incremental::load_cache(root).ok().and_then(|c| c.since);
+    let cutoff = parse_cutoff(since.as_deref())?;
+
+    // Summarize without holding the lock; walking a tree can take a while
+    let mut fresh: Vec<ProjectEntry> = Vec::new();
+    for path in paths {
+        let Some(old) = idx.projects.iter().find(|p| &p.path == path) else {
+            continue;
+        };
+        let dir = root.join(path);
+        if !dir.is_dir() {
+            report.removed.push(path.clone());
+            continue;
+        }
+        let dp = discover::DiscoveredProject {
+            root: dir,
+            markers: vec![],
+            parent: old.parent.as_ref().map(|p| root.join(p)),
+            members: old.members.iter().map(|m| root.join(m)).collect(),
+        };
+        let children: Vec<PathBuf> = idx
+            .projects
+            .iter()
+            .filter(|c| c.path != *path && (path == "." || Path::new(&c.path).starts_with(path)))
+            .map(|c| root.join(&c.path))
+            .collect();
+        let opts = CensusOptions {
+            loc: old.loc.is_some(),
+            reclaim: old.build_dirs.is_some(),
+            similarity: old.minhash.is_some(),
+            ..Default::default()
+        };
+        let built = build_project_entry(root, &dp, &children, &rules, cutoff, &opts, None)?;
+        fresh.push(built.entry);
+        report.updated.push(path.clone());
+    }
+
+    if report.updated.is_empty() && report.removed.is_empty() {
+        return Ok(report);
+    }
+    // Merge into the index as it is now: another process may have saved meanwhile
+    let _lock = store::lock(root)?;
+    let mut idx = store::load(root)?;
+    idx.projects.retain(|p| !report.removed.contains(&p.path));
+    for entry in fresh {
+        if let Some(p) = idx.projects.iter_mut().find(|p| p.path == entry.path) {
+            *p = entry;
+        }
+    }
+    sync_pinned_flags(&mut idx.projects, &focus.pinned);
+    mark_duplicates(&mut idx.projects, cfg.similarity.threshold());
+    idx.generated_at = Some(Local::now());
+    store::save(root, &idx)?;
+    Ok(report)
+}
+
+/// Resolve the requested job count to an actual number of worker threads
+fn worker_count(jobs: usize, total: usize) -> usize {
+    let requested = if jobs == 0 {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    } else {
+        jobs
+    };
+    requested.min(total).max(1)
 }
 
-/// Build ProjectEntry list from discovered projects
+/// A project entry plus the tree stamp it was computed for
+struct BuiltEntry {
+    entry: ProjectEntry,
+    stamp: String,
+    /// True when the entry came from the census cache
+    reused: bool,
+}
+
+/// Build ProjectEntry list from discovered projects.
+///
+/// Projects are summarized and fingerprinted across a bounded pool of
+/// `opts.jobs` threads; the result keeps the order of `discovered`.
+/// Projects whose tree stamp matches `cache` are reused instead of re-walked.
 fn build_project_entries(
     root: &Path,
     discovered: &[discover::DiscoveredProject],
+    rules: &ScanRules,
+    cutoff: Option<DateTime<Local>>,
+    opts: &CensusOptions,
+    cache: Option<&CensusCache>,
+    progress: &Progress,
+) -> Result<Vec<BuiltEntry>> {
+    let total = discovered.len();
+    let workers = worker_count(opts.jobs, total);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    // Report roughly every 5% so large workspaces don't flood the log
+    let step = (total / 20).max(1);
+
+    let mut results: Vec<(usize, Result<BuiltEntry>)> = thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut out = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= total {
+                            break;
+                        }
+                        let children = child_roots(discovered, i);
+                        out.push((
+                            i,
+                            build_project_entry(
+                                root,
+                                &discovered[i],
+                                &children,
+                                rules,
+                                cutoff,
+                                opts,
+                                cache,
+                            ),
+                        ));
+                        let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        if n.is_multiple_of(step) || n == total {
+                            progress.note(&format!("summarized {}/{} projects", n, total));
+                        }
+                    }
+                    out
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("census worker panicked"))
+            .collect()
+    });
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Roots of the projects nested directly or indirectly inside `discovered[i]`.
+/// Discovery output is sorted, so descendants immediately follow their parent.
+fn child_roots(discovered: &[discover::DiscoveredProject], i: usize) -> Vec<PathBuf> {
+    let root = &discovered[i].root;
+    discovered[i + 1..]
+        .iter()
+        .take_while(|d| d.root.starts_with(root))
+        .map(|d| d.root.clone())
+        .collect()
+}
+
+/// Summarize and fingerprint a single discovered project.
+/// Nested child projects (`children`) are excluded so their bytes aren't counted twice.
+fn build_project_entry(
+    root: &Path,
+    dp: &discover::DiscoveredProject,
+    children: &[PathBuf],
+    rules: &ScanRules,
     cutoff: Option<DateTime<Local>>,
-) -> Result<Vec<ProjectEntry>> {
-    let mut projects = Vec::with_capacity(discovered.len());
-
-    for dp in discovered {
-        let rel = relpath(root, &dp.root);
-        let (latest, size_bytes, artifact_count, hints) = summarize_project(&dp.root, cutoff)?;
-        let fp = fingerprint::fingerprint_project(&dp.root)?;
-        let kind = classify_project(&rel, latest, cutoff);
-
-        projects.push(ProjectEntry {
-            path: rel,
-            kind,
-            pinned: false, // Will be set by sync_pinned_flags
-            latest_mtime: latest,
-            size_bytes: Some(size_bytes),
-            artifact_count,
-            has_git: hints.0,
-            has_rust: hints.1,
-            has_node: hints.2,
-            has_python: hints.3,
-            fingerprint: fp,
+    opts: &CensusOptions,
+    cache: Option<&CensusCache>,
+) -> Result<BuiltEntry> {
+    let rel = relpath(root, &dp.root);
+    let mut stamp = incremental::tree_stamp(&dp.root, rules, children)?;
+    // Staging changes the untracked count without touching the tree
+    if let Some(index) = git::index_stamp(&dp.root) {
+        stamp = format!("{}+git:{}", stamp, index);
+    }
+
+    // A cached entry without line counts or a signature can't satisfy a
+    // `--loc` or `--similarity` run; a signature it has is still valid
+    let cached = cache
+        .and_then(|c| c.lookup(&rel, &stamp))
+        .filter(|e| !opts.loc || e.loc.is_some())
+        .filter(|e| !opts.similarity || e.minhash.is_some());
+    if let Some(cached) = cached {
+        let mut entry = cached.clone();
+        if !opts.loc {
+            entry.loc = None;
+        }
+        // Classification is cheap and may depend on the current time window
+        entry.kind = classify_project(&rel, entry.latest_mtime, cutoff);
+        entry.pinned = false;
+        entry.parent = dp.parent.as_ref().map(|p| relpath(root, p));
+        entry.members = dp.members.iter().map(|m| relpath(root, m)).collect();
+        // HEAD, stashes and upstream change without touching the tree stamp;
+        // untracked files don't, so their count is reused
+        entry.git = if entry.has_git {
+            let untracked = entry.git.as_ref().map(|g| g.untracked);
+            git::read_git_info_with_untracked(&dp.root, untracked)
+        } else {
+            None
+        };
+        // Build directories are outside the tree stamp, so always re-measure
+        entry.build_dirs = if opts.reclaim {
+            Some(build_dirs::measure(&dp.root, children)?)
+        } else {
+            None
+        };
+        return Ok(BuiltEntry {
+            entry,
+            stamp,
+            reused: true,
         });
     }
 
-    Ok(projects)
+    let summary = summarize_project(&dp.root, children, rules, cutoff, opts)?;
+    let fp = fingerprint::fingerprint_project(&dp.root)?;
+    let kind = classify_project(&rel, summary.latest, cutoff);
+
+    let entry = ProjectEntry {
+        path: rel,
+        kind,
+        pinned: false, // Will be set by sync_pinned_flags
+        latest_mtime: summary.latest,
+        size_bytes: Some(summary.size_bytes),
+        artifacts: summary.artifacts,
+        has_git: summary.has_git,
+        ecosystems: summary.ecosystems,
+        fingerprint: fp,
+        minhash: summary.minhash,
+        parent: dp.parent.as_ref().map(|p| relpath(root, p)),
+        members: dp.members.iter().map(|m| relpath(root, m)).collect(),
+        git: if summary.has_git {
+            git::read_git_info(&dp.root)
+        } else {
+            None
+        },
+        loc: summary.loc,
+        build_dirs: if opts.reclaim {
+            Some(build_dirs::measure(&dp.root, children)?)
+        } else {
+            None
+        },
+    };
+    Ok(BuiltEntry {
+        entry,
+        stamp,
+        reused: false,
+    })
 }
 
 /// Classify project kind based on path and activity
@@ -117,36 +475,70 @@ fn classify_project(
     ProjectKind::Standalone
 }
 
-/// Save projects to index file
-fn save_index(
-    root: &Path,
-    root_str: &str,
-    projects: Vec<ProjectEntry>,
-) -> Result<store::OrbitIndex> {
-    let mut idx = store::load(root).unwrap_or_default();
-    idx.root = root_str.to_string();
+/// Replace this root's projects in the index (other roots are kept), journaling
+/// the change when `record` is set
+fn save_index(root: &Path, projects: Vec<ProjectEntry>, record: bool) -> Result<store::OrbitIndex> {
+    let _lock = store::lock(root)?;
+    let mut idx = store::load(root)?;
+    let before = serde_json::to_value(&idx).context("Failed to serialize index")?;
+    let before_count = idx.projects.len();
+    idx.root = store::root_key(root);
     idx.generated_at = Some(Local::now());
     idx.projects = projects;
     store::save(root, &idx)?;
+    if !record {
+        return Ok(idx);
+    }
+    journal::record(
+        root,
+        "census",
+        Target::Index,
+        format!("{} → {} projects", before_count, idx.projects.len()),
+        &before,
+        &idx,
+    )?;
     Ok(idx)
 }
 
 /// Output census result
-fn output_result(root: &Path, idx: &store::OrbitIndex, json_output: bool) {
+fn output_result(
+    root: &Path,
+    idx: &store::OrbitIndex,
+    delta: &CensusDelta,
+    delta_md: Option<&Path>,
+    json_output: bool,
+) {
     if json_output {
         println!(
             "{}",
             serde_json::json!({
                 "status": "complete",
                 "index_path": store::index_path(root).display().to_string(),
-                "project_count": idx.projects.len()
+                "project_count": idx.projects.len(),
+                "delta": delta,
+                "delta_path": delta_md.map(|p| p.display().to_string()),
             })
         );
-    } else {
-        println!(
-            "Census complete. Updated {}",
-            store::index_path(root).display()
-        );
+        return;
+    }
+    println!(
+        "Census complete. Updated {}",
+        store::index_path(root).display()
+    );
+    match delta.previous {
+        None => println!("First census: {} projects.", idx.projects.len()),
+        Some(prev) if delta.is_empty() => {
+            println!("No changes since {}.", prev.format("%Y-%m-%d %H:%M"))
+        }
+        Some(prev) => {
+            println!("Changes since {}:", prev.format("%Y-%m-%d %H:%M"));
+            for l in delta.lines() {
+                println!("  {}", l);
+            }
+        }
+    }
+    if let Some(p) = delta_md {
+        println!("Wrote {}", p.display());
     }
 }
 
@@ -168,15 +560,6 @@ fn parse_cutoff(since: Option<&str>) -> Result<Option<DateTime<Local>>> {
     }
 }
 
-fn should_skip(entry: &walkdir::DirEntry) -> bool {
-    entry.file_type().is_dir()
-        && entry
-            .file_name()
-            .to_str()
-            .map(|s| SKIP_DIRS.contains(&s))
-            .unwrap_or(false)
-}
-
 fn relpath(root: &Path, p: &Path) -> String {
     let rel = p.strip_prefix(root).unwrap_or(p);
     let s = rel.to_string_lossy().replace("\\", "/");
@@ -189,48 +572,52 @@ fn relpath(root: &Path, p: &Path) -> String {
 
 fn summarize_project(
     project_root: &Path,
+    children: &[PathBuf],
+    rules: &ScanRules,
     cutoff: Option<DateTime<Local>>,
+    opts: &CensusOptions,
 ) -> Result<ProjectSummary> {
     let mut latest: Option<DateTime<Local>> = None;
     let mut size_bytes: u64 = 0;
-    let mut artifact_count: u32 = 0;
-    let mut has_git = false;
-    let mut has_rust = false;
-    let mut has_node = false;
-    let mut has_python = false;
-
-    for entry in WalkDir::new(project_root)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| !should_skip(e))
-    {
-        let entry =
-            entry.with_context(|| format!("Failed to read entry in {}", project_root.display()))?;
-        let p = entry.path();
+    let mut artifacts: BTreeMap<String, u32> = BTreeMap::new();
+    // `.git` itself is in the skip set, so check for it directly
+    let has_git = project_root.join(".git").exists();
+    let mut ecosystems: BTreeSet<&'static str> = BTreeSet::new();
+    let mut loc_counts: BTreeMap<String, LocCounts> = BTreeMap::new();
+    let mut minhash = MinHasher::default();
 
-        if entry.file_type().is_dir() && entry.file_name() == ".git" {
-            has_git = true;
-        }
+    for item in rules.walker(project_root, children).build() {
+        let Some(entry) = rules::walk_entry(item, project_root)? else {
+            continue;
+        };
+        let p = entry.path();
+        let Some(ft) = entry.file_type() else {
+            continue;
+        };
 
-        if entry.file_type().is_file() {
-            if artifacts::is_artifact_name(p) {
-                artifact_count += 1;
+        if ft.is_file() {
+            let key = similarity::rel_key(project_root, p);
+            if let Some(cat) = rules.artifact_category(Path::new(&key)) {
+                *artifacts.entry(cat.to_string()).or_default() += 1;
             }
-            if let Some(name) = p.file_name().and_then(|s| s.to_str()) {
-                if name == "Cargo.toml" {
-                    has_rust = true;
-                }
-                if name == "package.json" {
-                    has_node = true;
+            if let Some(eco) = ecosystem::detect(p) {
+                ecosystems.insert(eco);
+            }
+            // Files that vanish or can't be read mid-walk are left out
+            let Ok(md) = entry.metadata() else {
+                continue;
+            };
+            size_bytes += md.len();
+            if opts.similarity {
+                if let Ok(digest) = similarity::file_digest(p, md.len()) {
+                    minhash.add_file(&key, &digest);
                 }
-                if name == "pyproject.toml" || name == "pytest.ini" {
-                    has_python = true;
+            }
+            if opts.loc {
+                if let Some((lang, c)) = loc::count_file(p, md.len()) {
+                    loc_counts.entry(lang.to_string()).or_default().add(&c);
                 }
             }
-            let md = entry
-                .metadata()
-                .with_context(|| format!("Failed to read metadata for {}", p.display()))?;
-            size_bytes += md.len();
             if let Ok(m) = md.modified() {
                 let dt: DateTime<Local> = m.into();
                 if cutoff.map(|c| dt >= c).unwrap_or(true) {
@@ -248,28 +635,23 @@ fn summarize_project(
             }
         }
     }
-    Ok((
+    Ok(ProjectSummary {
         latest,
         size_bytes,
-        artifact_count,
-        (has_git, has_rust, has_node, has_python),
-    ))
+        artifacts,
+        has_git,
+        ecosystems: ecosystems.into_iter().map(String::from).collect(),
+        loc: opts.loc.then_some(loc_counts),
+        minhash: minhash.finish(),
+    })
 }
 
-fn mark_duplicates_by_fingerprint(projects: &mut [ProjectEntry]) {
-    use std::collections::HashMap;
-    let mut map: HashMap<String, Vec<usize>> = HashMap::new();
-    for (i, p) in projects.iter().enumerate() {
-        if let Some(fp) = &p.fingerprint {
-            map.entry(fp.clone()).or_default().push(i);
-        }
-    }
-    for (_fp, idxs) in map {
-        if idxs.len() >= 2 {
-            for &i in &idxs {
-                if !projects[i].pinned && !matches!(projects[i].kind, ProjectKind::Experimental) {
-                    projects[i].kind = ProjectKind::BackupDuplicate;
-                }
+/// Demote members of near-duplicate groups (similarity >= `threshold`)
+fn mark_duplicates(projects: &mut [ProjectEntry], threshold: f64) {
+    for idxs in similarity::group_similar(projects, threshold) {
+        for i in idxs {
+            if !projects[i].pinned && !matches!(projects[i].kind, ProjectKind::Experimental) {
+                projects[i].kind = ProjectKind::BackupDuplicate;
             }
         }
     }
@@ -277,11 +659,39 @@ fn mark_duplicates_by_fingerprint(projects: &mut [ProjectEntry]) {
 
 #[cfg(test)]
 mod tests {
-    use super::{classify_project, mark_duplicates_by_fingerprint, parse_cutoff, relpath};
+    use super::{
+        build_project_entries, classify_project, mark_duplicates, parse_cutoff, relpath,
+        worker_count, BuiltEntry, CensusOptions,
+    };
     use crate::model::project::{ProjectEntry, ProjectKind};
+    use crate::scan::incremental::{CachedProject, CensusCache};
+    use crate::scan::rules::ScanRules;
+    use crate::scan::{discover, progress::Progress};
     use chrono::{Local, TimeZone};
+    use std::collections::BTreeMap;
     use std::path::Path;
 
+    /// A census cache holding `built` as the previous run produced it
+    fn cache_of(built: &[BuiltEntry]) -> CensusCache {
+        CensusCache {
+            version: 1,
+            since: None,
+            artifact_rules: String::new(),
+            projects: built
+                .iter()
+                .map(|b| {
+                    (
+                        b.entry.path.clone(),
+                        CachedProject {
+                            stamp: b.stamp.clone(),
+                            entry: b.entry.clone(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
     #[test]
     fn relpath_handles_root_and_child() {
         let root = Path::new("/tmp/orbit_root");
@@ -292,6 +702,262 @@ mod tests {
         );
     }
 
+    #[test]
+    fn worker_count_is_bounded_by_projects() {
+        assert_eq!(worker_count(8, 3), 3);
+        assert_eq!(worker_count(2, 100), 2);
+        assert_eq!(worker_count(4, 0), 1);
+        assert!(worker_count(0, 100) >= 1);
+    }
+
+    #[test]
+    fn parallel_entries_keep_discovery_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for i in 0..12 {
+            let p = root.join(format!("proj{:02}", i));
+            std::fs::create_dir_all(&p).unwrap();
+            std::fs::write(p.join("README.md"), format!("project {i}\n")).unwrap();
+        }
+        let discovered = discover::discover_projects(root, 4, &ScanRules::default()).unwrap();
+        let rules = ScanRules::default();
+        let progress = Progress::new(false);
+
+        let serial = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions {
+                jobs: 1,
+                ..Default::default()
+            },
+            None,
+            &progress,
+        )
+        .unwrap();
+        let parallel = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions {
+                jobs: 4,
+                ..Default::default()
+            },
+            None,
+            &progress,
+        )
+        .unwrap();
+
+        let serial_paths: Vec<_> = serial.iter().map(|b| b.entry.path.clone()).collect();
+        let parallel_paths: Vec<_> = parallel.iter().map(|b| b.entry.path.clone()).collect();
+        assert_eq!(serial_paths.len(), 12);
+        assert_eq!(serial_paths, parallel_paths);
+        for (a, b) in serial.iter().zip(parallel.iter()) {
+            assert_eq!(a.entry.fingerprint, b.entry.fingerprint);
+            assert_eq!(a.entry.size_bytes, b.entry.size_bytes);
+        }
+    }
+
+    #[test]
+    fn parent_size_excludes_nested_projects() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let outer = root.join("outer");
+        let inner = outer.join("inner");
+        std::fs::create_dir_all(&inner).unwrap();
+        std::fs::write(outer.join("README.md"), "12345").unwrap();
+        std::fs::write(inner.join("README.md"), "1234567890").unwrap();
+
+        let discovered = discover::discover_projects(root, 4, &ScanRules::default()).unwrap();
+        let rules = ScanRules::default();
+        let progress = Progress::new(false);
+        let built = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions {
+                jobs: 2,
+                ..Default::default()
+            },
+            None,
+            &progress,
+        )
+        .unwrap();
+        let outer_e = &built
+            .iter()
+            .find(|b| b.entry.path == "outer")
+            .unwrap()
+            .entry;
+        let inner_e = &built
+            .iter()
+            .find(|b| b.entry.path == "outer/inner")
+            .unwrap()
+            .entry;
+        assert_eq!(outer_e.size_bytes, Some(5));
+        assert_eq!(inner_e.size_bytes, Some(10));
+        assert_eq!(inner_e.parent.as_deref(), Some("outer"));
+    }
+
+    #[test]
+    fn cached_entries_are_reused_until_tree_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for name in ["alpha", "beta"] {
+            let p = root.join(name);
+            std::fs::create_dir_all(&p).unwrap();
+            std::fs::write(p.join("README.md"), name).unwrap();
+        }
+        let discovered = discover::discover_projects(root, 4, &ScanRules::default()).unwrap();
+        let rules = ScanRules::default();
+        let progress = Progress::new(false);
+        let first = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions {
+                jobs: 2,
+                ..Default::default()
+            },
+            None,
+            &progress,
+        )
+        .unwrap();
+        assert!(first.iter().all(|b| !b.reused));
+
+        let cache = cache_of(&first);
+
+        std::fs::write(root.join("beta").join("NOTES.md"), "new file").unwrap();
+        let second = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions {
+                jobs: 2,
+                ..Default::default()
+            },
+            Some(&cache),
+            &progress,
+        )
+        .unwrap();
+        let reused: Vec<_> = second
+            .iter()
+            .map(|b| (b.entry.path.as_str(), b.reused))
+            .collect();
+        assert_eq!(reused, vec![("alpha", true), ("beta", false)]);
+    }
+
+    #[test]
+    fn loc_pass_counts_lines_and_invalidates_cache_without_counts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let p = root.join("app");
+        std::fs::create_dir_all(p.join("src")).unwrap();
+        std::fs::write(p.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        std::fs::write(p.join("src").join("main.rs"), "// entry\n\nfn main() {}\n").unwrap();
+        let discovered = discover::discover_projects(root, 4, &ScanRules::default()).unwrap();
+        let rules = ScanRules::default();
+        let progress = Progress::new(false);
+
+        let plain = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions {
+                jobs: 1,
+                ..Default::default()
+            },
+            None,
+            &progress,
+        )
+        .unwrap();
+        assert!(plain[0].entry.loc.is_none());
+
+        let cache = cache_of(&plain);
+        let counted = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions {
+                loc: true,
+                jobs: 1,
+                ..Default::default()
+            },
+            Some(&cache),
+            &progress,
+        )
+        .unwrap();
+        assert!(!counted[0].reused);
+        let loc = counted[0].entry.loc.as_ref().unwrap();
+        let rust = loc["Rust"];
+        assert_eq!(
+            (rust.files, rust.code, rust.comment, rust.blank),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(loc["TOML"].code, 2);
+    }
+
+    #[test]
+    fn similarity_pass_is_opt_in_and_invalidates_cache_without_signatures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let p = root.join("app");
+        std::fs::create_dir_all(&p).unwrap();
+        std::fs::write(p.join("README.md"), "app\n").unwrap();
+        let discovered = discover::discover_projects(root, 4, &ScanRules::default()).unwrap();
+        let rules = ScanRules::default();
+        let progress = Progress::new(false);
+
+        let plain = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions::default(),
+            None,
+            &progress,
+        )
+        .unwrap();
+        assert!(plain[0].entry.minhash.is_none());
+
+        let opts = CensusOptions {
+            similarity: true,
+            ..Default::default()
+        };
+        let hashed = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &opts,
+            Some(&cache_of(&plain)),
+            &progress,
+        )
+        .unwrap();
+        assert!(!hashed[0].reused);
+        assert!(hashed[0].entry.minhash.is_some());
+
+        // A plain run keeps the still-valid signature of an unchanged tree
+        let again = build_project_entries(
+            root,
+            &discovered,
+            &rules,
+            None,
+            &CensusOptions::default(),
+            Some(&cache_of(&hashed)),
+            &progress,
+        )
+        .unwrap();
+        assert!(again[0].reused);
+        assert_eq!(again[0].entry.minhash, hashed[0].entry.minhash);
+    }
+
     #[test]
     fn parse_cutoff_none_returns_none() {
         let result = parse_cutoff(None).unwrap();
@@ -332,12 +998,16 @@ mod tests {
                 pinned: false,
                 latest_mtime: None,
                 size_bytes: None,
-                artifact_count: 0,
+                artifacts: BTreeMap::new(),
                 has_git: false,
-                has_rust: false,
-                has_node: false,
-                has_python: false,
+                ecosystems: vec![],
                 fingerprint: Some("fp".into()),
+                minhash: None,
+                parent: None,
+                members: vec![],
+                git: None,
+                loc: None,
+                build_dirs: None,
             },
             ProjectEntry {
                 path: "b".into(),
@@ -345,12 +1015,16 @@ mod tests {
                 pinned: false,
                 latest_mtime: None,
                 size_bytes: None,
-                artifact_count: 0,
+                artifacts: BTreeMap::new(),
                 has_git: false,
-                has_rust: false,
-                has_node: false,
-                has_python: false,
+                ecosystems: vec![],
                 fingerprint: Some("fp".into()),
+                minhash: None,
+                parent: None,
+                members: vec![],
+                git: None,
+                loc: None,
+                build_dirs: None,
             },
             ProjectEntry {
                 path: "c".into(),
@@ -358,22 +1032,54 @@ mod tests {
                 pinned: true,
                 latest_mtime: None,
                 size_bytes: None,
-                artifact_count: 0,
+                artifacts: BTreeMap::new(),
                 has_git: false,
-                has_rust: false,
-                has_node: false,
-                has_python: false,
+                ecosystems: vec![],
                 fingerprint: Some("fp".into()),
+                minhash: None,
+                parent: None,
+                members: vec![],
+                git: None,
+                loc: None,
+                build_dirs: None,
             },
         ];
 
-        mark_duplicates_by_fingerprint(&mut projects);
+        mark_duplicates(&mut projects, 0.85);
 
         assert!(matches!(projects[0].kind, ProjectKind::BackupDuplicate));
         assert!(matches!(projects[1].kind, ProjectKind::BackupDuplicate));
         assert!(matches!(projects[2].kind, ProjectKind::Standalone));
     }
 
+    #[test]
+    fn near_duplicates_are_grouped_by_signature_threshold() {
+        let entry = |path: &str, sig: Vec<u32>| ProjectEntry {
+            // Different fingerprints: only the signatures can group these
+            fingerprint: Some(path.into()),
+            minhash: Some(sig),
+            ..ProjectEntry::stub(path)
+        };
+        let base: Vec<u32> = (0..64).collect();
+        let mut edited = base.clone();
+        edited[..4].copy_from_slice(&[900, 901, 902, 903]);
+        let mut projects = vec![
+            entry("app", base),
+            entry("app-edited", edited),
+            entry("other", (1000..1064).collect()),
+        ];
+
+        mark_duplicates(&mut projects, 0.9);
+        assert!(matches!(projects[0].kind, ProjectKind::BackupDuplicate));
+        assert!(matches!(projects[1].kind, ProjectKind::BackupDuplicate));
+        assert!(matches!(projects[2].kind, ProjectKind::Standalone));
+
+        projects[0].kind = ProjectKind::Standalone;
+        projects[1].kind = ProjectKind::Standalone;
+        mark_duplicates(&mut projects, 0.95);
+        assert!(matches!(projects[0].kind, ProjectKind::Standalone));
+    }
+
     #[test]
     fn classify_project_by_path_patterns() {
         // Backup patterns
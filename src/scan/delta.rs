@@ -0,0 +1,370 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::catalog::ArtifactCatalog;
+use crate::index::history::signed_size;
+use crate::index::store::OrbitIndex;
+use crate::model::project::size_label;
+use crate::snapshot::inspect::Reclassified;
+
+/// Size changes smaller than this are not reported by default
+pub const DEFAULT_MIN_SIZE_CHANGE: u64 = 100 * 1024 * 1024;
+
+/// How `orbit census` reports what changed since the previous run
+#[derive(Debug, Clone)]
+pub struct DeltaOptions {
+    /// Smallest size change (in bytes) worth reporting
+    pub min_size_change: u64,
+    /// Also write `.orbit/exports/delta.md`
+    pub export: bool,
+}
+
+impl Default for DeltaOptions {
+    fn default() -> Self {
+        Self {
+            min_size_change: DEFAULT_MIN_SIZE_CHANGE,
+            export: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SizeChange {
+    pub path: String,
+    pub before: u64,
+    pub after: u64,
+    pub delta: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct NewArtifact {
+    /// Workspace-relative path
+    pub path: String,
+    pub category: String,
+}
+
+/// What changed between the previous index and a fresh census
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct CensusDelta {
+    /// When the previous census ran (`None` on the first one)
+    pub previous: Option<DateTime<Local>>,
+    pub projects_added: Vec<String>,
+    pub projects_removed: Vec<String>,
+    pub reclassified: Vec<Reclassified>,
+    /// Largest change first
+    pub size_changes: Vec<SizeChange>,
+    pub new_artifacts: Vec<NewArtifact>,
+}
+
+impl CensusDelta {
+    /// Compare two indexes (and their artifact catalogs, when the old one exists)
+    pub fn between(
+        before: &OrbitIndex,
+        after: &OrbitIndex,
+        before_cat: Option<&ArtifactCatalog>,
+        after_cat: &ArtifactCatalog,
+        min_size_change: u64,
+    ) -> Self {
+        let old: BTreeMap<&str, _> = before
+            .projects
+            .iter()
+            .map(|p| (p.path.as_str(), p))
+            .collect();
+        let new: BTreeMap<&str, _> = after
+            .projects
+            .iter()
+            .map(|p| (p.path.as_str(), p))
+            .collect();
+
+        let mut size_changes: Vec<SizeChange> = new
+            .iter()
+            .filter_map(|(path, p)| {
+                let (b, a) = (old.get(path)?.size_bytes?, p.size_bytes?);
+                let delta = a as i64 - b as i64;
+                (delta.unsigned_abs() >= min_size_change.max(1)).then(|| SizeChange {
+                    path: path.to_string(),
+                    before: b,
+                    after: a,
+                    delta,
+                })
+            })
+            .collect();
+        size_changes.sort_by(|x, y| {
+            y.delta
+                .unsigned_abs()
+                .cmp(&x.delta.unsigned_abs())
+                .then(x.path.cmp(&y.path))
+        });
+
+        // Without an earlier catalog every artifact would look new
+        let new_artifacts = match before_cat {
+            Some(bc) => {
+                let known: BTreeSet<String> = bc.artifacts.iter().map(|a| a.key()).collect();
+                after_cat
+                    .artifacts
+                    .iter()
+                    .filter(|a| !known.contains(&a.key()))
+                    .map(|a| NewArtifact {
+                        path: a.key(),
+                        category: a.category.clone(),
+                    })
+                    .collect()
+            }
+            None => vec![],
+        };
+
+        CensusDelta {
+            previous: before.generated_at,
+            projects_added: new
+                .keys()
+                .filter(|p| !old.contains_key(*p))
+                .map(|p| p.to_string())
+                .collect(),
+            projects_removed: old
+                .keys()
+                .filter(|p| !new.contains_key(*p))
+                .map(|p| p.to_string())
+                .collect(),
+            reclassified: old
+                .iter()
+                .filter_map(|(path, p)| {
+                    let (from, to) = (
+                        format!("{:?}", p.kind),
+                        format!("{:?}", new.get(path)?.kind),
+                    );
+                    (from != to).then(|| Reclassified {
+                        path: path.to_string(),
+                        from,
+                        to,
+                    })
+                })
+                .collect(),
+            size_changes,
+            new_artifacts,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.projects_added.is_empty()
+            && self.projects_removed.is_empty()
+            && self.reclassified.is_empty()
+            && self.size_changes.is_empty()
+            && self.new_artifacts.is_empty()
+    }
+
+    /// `2 new, 1 gone, 3 reclassified`, for one-line logs
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "no changes".into();
+        }
+        let mut parts = vec![];
+        let mut count = |n: usize, what: &str| {
+            if n > 0 {
+                parts.push(format!("{} {}", n, what));
+            }
+        };
+        count(self.projects_added.len(), "new");
+        count(self.projects_removed.len(), "gone");
+        count(self.reclassified.len(), "reclassified");
+        count(self.size_changes.len(), "resized");
+        count(self.new_artifacts.len(), "new artifacts");
+        parts.join(", ")
+    }
+
+    /// One change per line, as printed after `orbit census`
+    pub fn lines(&self) -> Vec<String> {
+        let mut out = vec![];
+        for p in &self.projects_added {
+            out.push(format!("+ {:<9} {}", "project", p));
+        }
+        for p in &self.projects_removed {
+            out.push(format!("- {:<9} {}", "project", p));
+        }
+        for r in &self.reclassified {
+            out.push(format!(
+                "~ {:<9} {} ({} → {})",
+                "kind", r.path, r.from, r.to
+            ));
+        }
+        for s in &self.size_changes {
+            out.push(format!(
+                "~ {:<9} {} {} ({} → {})",
+                "size",
+                s.path,
+                signed_size(s.delta),
+                size_label(s.before),
+                size_label(s.after)
+            ));
+        }
+        for a in &self.new_artifacts {
+            out.push(format!("+ {:<9} {} ({})", "artifact", a.path, a.category));
+        }
+        out
+    }
+
+    pub fn render_md(&self) -> String {
+        let mut s = String::from("# Census delta\n\n");
+        s.push_str(&format!(
+            "Generated {}, compared with the census of {}.\n",
+            Local::now().format("%Y-%m-%d %H:%M"),
+            self.previous
+                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "— (first run)".into())
+        ));
+        let mut section = |title: &str, items: Vec<String>| {
+            if items.is_empty() {
+                return;
+            }
+            s.push_str(&format!("\n## {}\n\n", title));
+            for i in items {
+                s.push_str(&format!("- {}\n", i));
+            }
+        };
+        let code = |p: &String| format!("`{}`", p);
+        section(
+            "New projects",
+            self.projects_added.iter().map(code).collect(),
+        );
+        section(
+            "Disappeared projects",
+            self.projects_removed.iter().map(code).collect(),
+        );
+        section(
+            "Reclassified",
+            self.reclassified
+                .iter()
+                .map(|r| format!("`{}`: {} → {}", r.path, r.from, r.to))
+                .collect(),
+        );
+        section(
+            "Size changes",
+            self.size_changes
+                .iter()
+                .map(|c| {
+                    format!(
+                        "`{}`: {} ({} → {})",
+                        c.path,
+                        signed_size(c.delta),
+                        size_label(c.before),
+                        size_label(c.after)
+                    )
+                })
+                .collect(),
+        );
+        section(
+            "New artifacts",
+            self.new_artifacts
+                .iter()
+                .map(|a| format!("**{}** `{}`", a.category, a.path))
+                .collect(),
+        );
+        if self.is_empty() {
+            s.push_str("\nNo changes.\n");
+        }
+        s
+    }
+}
+
+/// `.orbit/exports/delta.md`
+pub fn export_path(root: &Path) -> PathBuf {
+    root.join(".orbit").join("exports").join("delta.md")
+}
+
+pub fn write_md(root: &Path, delta: &CensusDelta) -> Result<PathBuf> {
+    let path = export_path(root);
+    let parent = path.parent().expect("delta path has a parent");
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    fs::write(&path, delta.render_md())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CensusDelta;
+    use crate::index::catalog::{ArtifactCatalog, ArtifactRecord};
+    use crate::index::store::OrbitIndex;
+    use crate::model::project::{ProjectEntry, ProjectKind};
+
+    fn entry(path: &str, kind: ProjectKind, size: u64) -> ProjectEntry {
+        ProjectEntry {
+            kind,
+            size_bytes: Some(size),
+            ..ProjectEntry::stub(path)
+        }
+    }
+
+    fn artifact(project: &str, path: &str) -> ArtifactRecord {
+        ArtifactRecord {
+            project: project.into(),
+            path: path.into(),
+            category: "handoff".into(),
+            title: None,
+            size: 1,
+            mtime: None,
+            date: None,
+            date_source: None,
+        }
+    }
+
+    #[test]
+    fn delta_reports_projects_kinds_sizes_and_artifacts() {
+        use ProjectKind::{ActiveStandalone, Standalone};
+        let before = OrbitIndex {
+            projects: vec![
+                entry("app", ActiveStandalone, 1_000),
+                entry("api", ActiveStandalone, 5_000),
+                entry("old", Standalone, 10),
+            ],
+            ..Default::default()
+        };
+        let after = OrbitIndex {
+            projects: vec![
+                entry("app", ActiveStandalone, 9_000),
+                entry("api", Standalone, 5_050),
+                entry("lib", ActiveStandalone, 10),
+            ],
+            ..Default::default()
+        };
+        let before_cat = ArtifactCatalog {
+            generated_at: None,
+            artifacts: vec![artifact("app", "HANDOFF.md")],
+        };
+        let after_cat = ArtifactCatalog {
+            generated_at: None,
+            artifacts: vec![
+                artifact("app", "HANDOFF.md"),
+                artifact("api", "HANDOFF_2.md"),
+            ],
+        };
+
+        let d = CensusDelta::between(&before, &after, Some(&before_cat), &after_cat, 100);
+        assert_eq!(d.projects_added, vec!["lib"]);
+        assert_eq!(d.projects_removed, vec!["old"]);
+        assert_eq!(d.reclassified.len(), 1);
+        assert_eq!(d.reclassified[0].path, "api");
+        assert_eq!(d.reclassified[0].to, "Standalone");
+        assert_eq!(d.size_changes.len(), 1, "api grew below the threshold");
+        assert_eq!(d.size_changes[0].delta, 8_000);
+        assert_eq!(d.new_artifacts.len(), 1);
+        assert_eq!(d.new_artifacts[0].path, "api/HANDOFF_2.md");
+        assert_eq!(
+            d.summary(),
+            "1 new, 1 gone, 1 reclassified, 1 resized, 1 new artifacts"
+        );
+
+        let md = d.render_md();
+        assert!(md.contains("## New projects\n\n- `lib`"));
+        assert!(md.contains("`api`: ActiveStandalone → Standalone"));
+
+        let first = CensusDelta::between(&OrbitIndex::default(), &after, None, &after_cat, 100);
+        assert_eq!(first.projects_added.len(), 3);
+        assert!(first.new_artifacts.is_empty());
+        assert!(CensusDelta::between(&after, &after, Some(&after_cat), &after_cat, 100).is_empty());
+    }
+}
@@ -1,78 +1,44 @@
-use anyhow::{Context, Result};
-use globset::{Glob, GlobSet, GlobSetBuilder};
-use once_cell::sync::Lazy;
+use anyhow::Result;
+use globset::{GlobBuilder, GlobSetBuilder};
+use std::fs;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+
+use crate::scan::rules::{self, ScanRules};
 
 #[derive(Debug, Clone)]
 pub struct DiscoveredProject {
     pub root: PathBuf,
     pub markers: Vec<String>,
+    /// Nearest enclosing project, if this one is nested inside another
+    pub parent: Option<PathBuf>,
+    /// Workspace/submodule members folded into this project
+    pub members: Vec<PathBuf>,
 }
 
-/// Cached glob matcher for project marker files - built once and reused
-static MARKER_GLOBSET: Lazy<GlobSet> = Lazy::new(|| {
-    let mut b = GlobSetBuilder::new();
-    // These patterns are known valid, so expect() is safe
-    b.add(Glob::new("**/CLAUDE.md").expect("valid glob"));
-    b.add(Glob::new("**/AGENT.md").expect("valid glob"));
-    b.add(Glob::new("**/HANDOFF*.md").expect("valid glob"));
-    b.add(Glob::new("**/*handoff*.md").expect("valid glob"));
-    b.add(Glob::new("**/*export*.md").expect("valid glob"));
-    b.add(Glob::new("**/*session*.md").expect("valid glob"));
-    b.add(Glob::new("**/README.md").expect("valid glob"));
-    b.add(Glob::new("**/Cargo.toml").expect("valid glob"));
-    b.add(Glob::new("**/pyproject.toml").expect("valid glob"));
-    b.add(Glob::new("**/package.json").expect("valid glob"));
-    b.build().expect("valid globset")
-});
-
-const SKIP_DIRS: &[&str] = &[
-    "node_modules",
-    "target",
-    ".git",
-    "__pycache__",
-    "venv",
-    ".venv",
-    "dist",
-    "build",
-    ".next",
-    "vendor",
-];
-
-fn should_skip(entry: &walkdir::DirEntry) -> bool {
-    entry.file_type().is_dir()
-        && entry
-            .file_name()
-            .to_str()
-            .map(|s| SKIP_DIRS.contains(&s))
-            .unwrap_or(false)
-}
-
-pub fn discover_projects(root: &Path, depth: usize) -> Result<Vec<DiscoveredProject>> {
-    let gs = &*MARKER_GLOBSET;
+pub fn discover_projects(
+    root: &Path,
+    depth: usize,
+    rules: &ScanRules,
+) -> Result<Vec<DiscoveredProject>> {
     let mut projects: Vec<DiscoveredProject> = vec![];
 
-    for entry in WalkDir::new(root)
-        .follow_links(false)
-        .max_depth(depth)
-        .into_iter()
-        .filter_entry(|e| !should_skip(e))
-    {
-        let entry = entry.with_context(|| format!("Failed to read entry in {}", root.display()))?;
+    for item in rules.walker(root, &[]).max_depth(Some(depth)).build() {
+        let Some(entry) = rules::walk_entry(item, root)? else {
+            continue;
+        };
         let p = entry.path();
+        let Some(ft) = entry.file_type() else {
+            continue;
+        };
 
-        if entry.file_type().is_dir() && entry.file_name() == ".git" {
-            upsert(
-                &mut projects,
-                p.parent().unwrap_or(root).to_path_buf(),
-                ".git".into(),
-            );
+        // `.git` is pruned by the skip set, so probe for it from its parent
+        if ft.is_dir() && p.join(".git").exists() {
+            upsert(&mut projects, p.to_path_buf(), ".git".into());
             continue;
         }
-        if entry.file_type().is_file() {
+        if ft.is_file() {
             let rel = p.strip_prefix(root).unwrap_or(p);
-            if gs.is_match(rel) {
+            if rules.is_marker(rel) {
                 upsert(
                     &mut projects,
                     p.parent().unwrap_or(root).to_path_buf(),
@@ -82,7 +48,139 @@ pub fn discover_projects(root: &Path, depth: usize) -> Result<Vec<DiscoveredProj
         }
     }
     projects.sort_by_key(|d| d.root.clone());
-    Ok(projects)
+    Ok(resolve_nesting(projects))
+}
+
+/// Fold workspace members into their workspace root and link the remaining
+/// nested projects to their nearest enclosing project.
+/// Expects `projects` sorted by root so parents precede their descendants.
+fn resolve_nesting(projects: Vec<DiscoveredProject>) -> Vec<DiscoveredProject> {
+    let mut absorbed = vec![false; projects.len()];
+    let mut members: Vec<Vec<PathBuf>> = vec![vec![]; projects.len()];
+
+    for i in 0..projects.len() {
+        if absorbed[i] {
+            continue;
+        }
+        let ws_root = &projects[i].root;
+        let patterns = workspace_member_patterns(ws_root);
+        if patterns.is_empty() {
+            continue;
+        }
+        let mut b = GlobSetBuilder::new();
+        for pat in &patterns {
+            let pat = pat.trim_start_matches("./").trim_end_matches('/');
+            if let Ok(g) = GlobBuilder::new(pat).literal_separator(true).build() {
+                b.add(g);
+            }
+        }
+        let Ok(gs) = b.build() else {
+            continue;
+        };
+        for j in (i + 1)..projects.len() {
+            let Ok(rel) = projects[j].root.strip_prefix(ws_root) else {
+                continue;
+            };
+            if !absorbed[j] && gs.is_match(rel) {
+                absorbed[j] = true;
+                members[i].push(projects[j].root.clone());
+            }
+        }
+    }
+
+    let mut out: Vec<DiscoveredProject> = vec![];
+    for (i, mut p) in projects.into_iter().enumerate() {
+        if absorbed[i] {
+            continue;
+        }
+        p.members = std::mem::take(&mut members[i]);
+        // Sorted order means the last enclosing project seen is the nearest one
+        p.parent = out
+            .iter()
+            .rev()
+            .find(|q| p.root != q.root && p.root.starts_with(&q.root))
+            .map(|q| q.root.clone());
+        out.push(p);
+    }
+    out
+}
+
+/// Member globs declared by a Cargo workspace, npm/yarn workspaces,
+/// pnpm-workspace.yaml or .gitmodules in `dir` (relative to `dir`).
+fn workspace_member_patterns(dir: &Path) -> Vec<String> {
+    let mut patterns = vec![];
+
+    if let Ok(content) = fs::read_to_string(dir.join("Cargo.toml")) {
+        if let Ok(doc) = content.parse::<toml::Table>() {
+            if let Some(ms) = doc
+                .get("workspace")
+                .and_then(|w| w.get("members"))
+                .and_then(|m| m.as_array())
+            {
+                patterns.extend(ms.iter().filter_map(|m| m.as_str()).map(String::from));
+            }
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join("package.json")) {
+        if let Ok(doc) = serde_json::from_str::<serde_json::Value>(&content) {
+            // Either `"workspaces": [..]` or `"workspaces": { "packages": [..] }`
+            let ws = doc.get("workspaces");
+            let list = ws.and_then(|w| w.as_array()).or_else(|| {
+                ws.and_then(|w| w.get("packages"))
+                    .and_then(|p| p.as_array())
+            });
+            if let Some(ms) = list {
+                patterns.extend(ms.iter().filter_map(|m| m.as_str()).map(String::from));
+            }
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join("pnpm-workspace.yaml")) {
+        patterns.extend(parse_pnpm_packages(&content));
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join(".gitmodules")) {
+        patterns.extend(parse_gitmodule_paths(&content));
+    }
+
+    // Exclusions (`!pattern`) are not supported; drop them rather than match everything
+    patterns.retain(|p| !p.starts_with('!') && !p.is_empty());
+    patterns
+}
+
+/// Minimal reader for the `packages:` list of pnpm-workspace.yaml
+fn parse_pnpm_packages(content: &str) -> Vec<String> {
+    let mut out = vec![];
+    let mut in_packages = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) && !trimmed.starts_with('-') {
+            in_packages = trimmed == "packages:";
+            continue;
+        }
+        if in_packages {
+            if let Some(item) = trimmed.strip_prefix('-') {
+                let item = item.trim().trim_matches(|c| c == '\'' || c == '"');
+                out.push(item.to_string());
+            }
+        }
+    }
+    out
+}
+
+/// `path = <dir>` entries from a .gitmodules file
+fn parse_gitmodule_paths(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|l| {
+            let (k, v) = l.trim().split_once('=')?;
+            (k.trim() == "path").then(|| v.trim().to_string())
+        })
+        .collect()
 }
 
 fn upsert(projects: &mut Vec<DiscoveredProject>, root: PathBuf, marker: String) {
@@ -94,6 +192,60 @@ fn upsert(projects: &mut Vec<DiscoveredProject>, root: PathBuf, marker: String)
         projects.push(DiscoveredProject {
             root,
             markers: vec![marker],
+            parent: None,
+            members: vec![],
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{discover_projects, parse_gitmodule_paths, parse_pnpm_packages};
+    use crate::scan::rules::ScanRules;
+    use std::fs;
+
+    #[test]
+    fn cargo_workspace_members_fold_into_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path().join("ws");
+        fs::create_dir_all(ws.join("crates").join("a")).unwrap();
+        fs::create_dir_all(ws.join("crates").join("b")).unwrap();
+        fs::write(
+            ws.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        fs::write(ws.join("crates/a/Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+        fs::write(ws.join("crates/b/Cargo.toml"), "[package]\nname = \"b\"\n").unwrap();
+
+        let found = discover_projects(tmp.path(), 6, &ScanRules::default()).unwrap();
+        assert_eq!(found.len(), 1, "members should be folded: {found:?}");
+        assert_eq!(found[0].root, ws);
+        assert_eq!(found[0].members.len(), 2);
+    }
+
+    #[test]
+    fn nested_projects_record_nearest_parent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outer = tmp.path().join("outer");
+        let inner = outer.join("tools").join("inner");
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(outer.join("README.md"), "outer").unwrap();
+        fs::write(inner.join("package.json"), "{}").unwrap();
+
+        let found = discover_projects(tmp.path(), 6, &ScanRules::default()).unwrap();
+        let inner_p = found.iter().find(|p| p.root == inner).unwrap();
+        assert_eq!(inner_p.parent.as_deref(), Some(outer.as_path()));
+        let outer_p = found.iter().find(|p| p.root == outer).unwrap();
+        assert!(outer_p.parent.is_none());
+    }
+
+    #[test]
+    fn parses_pnpm_and_gitmodules() {
+        let pnpm = "packages:\n  - 'packages/*'\n  - \"apps/web\"\ncatalog:\n  - nope\n";
+        assert_eq!(parse_pnpm_packages(pnpm), vec!["packages/*", "apps/web"]);
+
+        let gm = "[submodule \"vendor/lib\"]\n\tpath = vendor/lib\n\turl = https://example.com/lib.git\n";
+        assert_eq!(parse_gitmodule_paths(gm), vec!["vendor/lib"]);
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::Path;
+
+/// A manifest-based ecosystem detector: a project belongs to `ecosystem`
+/// when any file with one of `files` names or `extensions` is found.
+pub struct Detector {
+    pub ecosystem: &'static str,
+    pub files: &'static [&'static str],
+    pub extensions: &'static [&'static str],
+}
+
+pub const DETECTORS: &[Detector] = &[
+    Detector {
+        ecosystem: "rust",
+        files: &["Cargo.toml"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "node",
+        files: &["package.json"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "python",
+        files: &[
+            "pyproject.toml",
+            "pytest.ini",
+            "setup.py",
+            "requirements.txt",
+            "Pipfile",
+        ],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "go",
+        files: &["go.mod"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "java",
+        files: &["pom.xml", "build.gradle", "settings.gradle"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "kotlin",
+        files: &["build.gradle.kts", "settings.gradle.kts"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "cpp",
+        files: &["CMakeLists.txt", "meson.build"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "ruby",
+        files: &["Gemfile"],
+        extensions: &["gemspec"],
+    },
+    Detector {
+        ecosystem: "swift",
+        files: &["Package.swift"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "elixir",
+        files: &["mix.exs"],
+        extensions: &[],
+    },
+    Detector {
+        ecosystem: "terraform",
+        files: &[],
+        extensions: &["tf"],
+    },
+];
+
+/// Ecosystem indicated by a single file, if any
+pub fn detect(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    let ext = path.extension().and_then(|e| e.to_str());
+    DETECTORS
+        .iter()
+        .find(|d| d.files.contains(&name) || ext.map(|e| d.extensions.contains(&e)) == Some(true))
+        .map(|d| d.ecosystem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::detect;
+    use std::path::Path;
+
+    #[test]
+    fn detects_manifests_and_extensions() {
+        assert_eq!(detect(Path::new("a/Cargo.toml")), Some("rust"));
+        assert_eq!(detect(Path::new("go.mod")), Some("go"));
+        assert_eq!(detect(Path::new("build.gradle.kts")), Some("kotlin"));
+        assert_eq!(detect(Path::new("CMakeLists.txt")), Some("cpp"));
+        assert_eq!(detect(Path::new("infra/main.tf")), Some("terraform"));
+        assert_eq!(detect(Path::new("orbit.gemspec")), Some("ruby"));
+        assert_eq!(detect(Path::new("mix.exs")), Some("elixir"));
+        assert_eq!(detect(Path::new("src/main.rs")), None);
+    }
+}
@@ -0,0 +1,1015 @@
+use chrono::{DateTime, Local, TimeZone};
+use flate2::read::ZlibDecoder;
+use ignore::WalkBuilder;
+use sha1::{Digest, Sha1};
+use std::cell::{RefCell, RefMut};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::model::project::GitInfo;
+
+/// Upper bound on commits visited when computing ahead/behind
+const MAX_WALK: usize = 20_000;
+/// Upper bound on chained pack deltas
+const MAX_DELTA_DEPTH: usize = 64;
+
+const OBJ_COMMIT: u8 = 1;
+const OBJ_TREE: u8 = 2;
+const OBJ_BLOB: u8 = 3;
+const OBJ_TAG: u8 = 4;
+const OBJ_OFS_DELTA: u8 = 6;
+const OBJ_REF_DELTA: u8 = 7;
+
+const MODE_GITLINK: u32 = 0o160000;
+const MODE_SYMLINK: u32 = 0o120000;
+
+/// Read branch, HEAD, worktree state, stashes and upstream divergence for the
+/// repository whose work tree is `work_tree`, straight from its `.git` directory.
+/// Never touches the network or spawns `git`. Returns `None` when there is no
+/// repository; individual fields are `None` when they can't be determined.
+pub fn read_git_info(work_tree: &Path) -> Option<GitInfo> {
+    read_git_info_with_untracked(work_tree, None)
+}
+
+/// Like [`read_git_info`], but takes the untracked-file count from a previous
+/// read instead of walking the work tree. Census passes it for projects whose
+/// tree stamp (which includes [`index_stamp`]) is unchanged.
+pub fn read_git_info_with_untracked(work_tree: &Path, untracked: Option<u32>) -> Option<GitInfo> {
+    let repo = Repo::open(work_tree)?;
+    let mut info = GitInfo::default();
+
+    let head = fs::read_to_string(repo.git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    if let Some(r) = head.strip_prefix("ref: ") {
+        info.branch = r.strip_prefix("refs/heads/").map(String::from);
+        info.head = repo.resolve_ref(r);
+    } else if is_hex_id(head) {
+        info.head = Some(head.to_string());
+    }
+
+    let head_commit = info.head.as_deref().and_then(|h| repo.commit(h));
+    info.head_date = head_commit.as_ref().and_then(|c| c.date());
+
+    let index = repo.read_index();
+    if let Some(entries) = &index {
+        info.dirty = worktree_dirty(&repo, entries)
+            || head_commit
+                .as_ref()
+                .map(|c| staged_changes(&repo, &c.tree, entries))
+                .unwrap_or(!entries.is_empty());
+        info.untracked = untracked.unwrap_or_else(|| count_untracked(&repo.work_tree, entries));
+    }
+
+    info.stashes = fs::read_to_string(repo.common_dir.join("logs").join("refs").join("stash"))
+        .map(|s| s.lines().filter(|l| !l.trim().is_empty()).count() as u32)
+        .unwrap_or(0);
+
+    if let Some(branch) = &info.branch {
+        if let Some(upstream) = repo.upstream_ref(branch) {
+            if let (Some(local), Some(remote)) = (&info.head, repo.resolve_ref(&upstream)) {
+                if let Some((ahead, behind)) = repo.ahead_behind(local, &remote) {
+                    info.ahead = Some(ahead);
+                    info.behind = Some(behind);
+                }
+            }
+            info.upstream = Some(
+                upstream
+                    .strip_prefix("refs/remotes/")
+                    .unwrap_or(&upstream)
+                    .to_string(),
+            );
+        }
+    }
+    Some(info)
+}
+
+/// Size and mtime of the repository's index, which changes on every stage,
+/// unstage and commit; `None` outside a repository
+pub fn index_stamp(work_tree: &Path) -> Option<String> {
+    let md = fs::metadata(git_dir(work_tree)?.join("index")).ok()?;
+    let nanos = md
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    Some(format!("{}:{}", md.len(), nanos))
+}
+
+/// The `.git` directory of `work_tree`, following `gitdir:` files
+fn git_dir(work_tree: &Path) -> Option<PathBuf> {
+    let dot = work_tree.join(".git");
+    if dot.is_dir() {
+        Some(dot)
+    } else if dot.is_file() {
+        // Submodules and linked worktrees: `gitdir: <path>`
+        let content = fs::read_to_string(&dot).ok()?;
+        let target = content.trim().strip_prefix("gitdir:")?.trim();
+        Some(work_tree.join(target))
+    } else {
+        None
+    }
+}
+
+fn is_hex_id(s: &str) -> bool {
+    s.len() == 40 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<[u8; 20]> {
+    if !is_hex_id(s) {
+        return None;
+    }
+    let mut out = [0u8; 20];
+    for (i, o) in out.iter_mut().enumerate() {
+        *o = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+struct Repo {
+    work_tree: PathBuf,
+    /// Per-worktree directory (HEAD, index)
+    git_dir: PathBuf,
+    /// Shared directory (objects, refs, config); differs from git_dir for linked worktrees
+    common_dir: PathBuf,
+    packs: Vec<PackIndex>,
+}
+
+struct Commit {
+    tree: String,
+    parents: Vec<String>,
+    time: i64,
+}
+
+impl Commit {
+    fn date(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_opt(self.time, 0).single()
+    }
+}
+
+struct IndexEntry {
+    path: String,
+    mode: u32,
+    size: u32,
+    mtime: (u32, u32),
+    id: [u8; 20],
+    stage: u16,
+}
+
+impl Repo {
+    fn open(work_tree: &Path) -> Option<Self> {
+        let git_dir = git_dir(work_tree)?;
+        let common_dir = fs::read_to_string(git_dir.join("commondir"))
+            .map(|c| git_dir.join(c.trim()))
+            .unwrap_or_else(|_| git_dir.clone());
+        let packs = PackIndex::load_all(&common_dir.join("objects").join("pack"));
+        Some(Self {
+            work_tree: work_tree.to_path_buf(),
+            git_dir,
+            common_dir,
+            packs,
+        })
+    }
+
+    /// Resolve a (possibly symbolic) ref name to a commit id
+    fn resolve_ref(&self, name: &str) -> Option<String> {
+        let mut name = name.to_string();
+        for _ in 0..10 {
+            let loose = [self.git_dir.join(&name), self.common_dir.join(&name)]
+                .into_iter()
+                .find_map(|p| fs::read_to_string(p).ok());
+            match loose {
+                Some(content) => {
+                    let content = content.trim();
+                    match content.strip_prefix("ref: ") {
+                        Some(next) => name = next.to_string(),
+                        None => return is_hex_id(content).then(|| content.to_string()),
+                    }
+                }
+                None => return self.packed_ref(&name),
+            }
+        }
+        None
+    }
+
+    fn packed_ref(&self, name: &str) -> Option<String> {
+        let content = fs::read_to_string(self.common_dir.join("packed-refs")).ok()?;
+        content
+            .lines()
+            .filter(|l| !l.starts_with('#') && !l.starts_with('^'))
+            .find_map(|l| {
+                let (id, r) = l.split_once(' ')?;
+                (r.trim() == name && is_hex_id(id)).then(|| id.to_string())
+            })
+    }
+
+    /// `refs/remotes/<remote>/<branch>` configured as the upstream of `branch`
+    fn upstream_ref(&self, branch: &str) -> Option<String> {
+        let config = fs::read_to_string(self.common_dir.join("config")).ok()?;
+        let header = format!("[branch \"{}\"]", branch);
+        let mut in_section = false;
+        let mut remote = None;
+        let mut merge = None;
+        for line in config.lines() {
+            let t = line.trim();
+            if t.starts_with('[') {
+                in_section = t == header;
+                continue;
+            }
+            if !in_section {
+                continue;
+            }
+            if let Some((k, v)) = t.split_once('=') {
+                match k.trim() {
+                    "remote" => remote = Some(v.trim().to_string()),
+                    "merge" => merge = Some(v.trim().to_string()),
+                    _ => {}
+                }
+            }
+        }
+        let merge = merge?;
+        match remote?.as_str() {
+            // Tracking another local branch
+            "." => Some(merge),
+            r => Some(format!(
+                "refs/remotes/{}/{}",
+                r,
+                merge.strip_prefix("refs/heads/").unwrap_or(&merge)
+            )),
+        }
+    }
+
+    fn read_object(&self, id: &str) -> Option<(u8, Vec<u8>)> {
+        // Ids come from refs and commit headers on disk; never slice unchecked ones
+        if !is_hex_id(id) {
+            return None;
+        }
+        let loose = self
+            .common_dir
+            .join("objects")
+            .join(&id[..2])
+            .join(&id[2..]);
+        if let Ok(f) = File::open(&loose) {
+            let mut data = Vec::new();
+            ZlibDecoder::new(f).read_to_end(&mut data).ok()?;
+            let nul = data.iter().position(|&b| b == 0)?;
+            let kind = match data[..nul].split(|&b| b == b' ').next()? {
+                b"commit" => OBJ_COMMIT,
+                b"tree" => OBJ_TREE,
+                b"blob" => OBJ_BLOB,
+                b"tag" => OBJ_TAG,
+                _ => return None,
+            };
+            return Some((kind, data[nul + 1..].to_vec()));
+        }
+        let raw = from_hex(id)?;
+        self.packs
+            .iter()
+            .find_map(|p| p.find(&raw).map(|off| (p, off)))
+            .and_then(|(p, off)| self.read_packed(p, off, 0))
+    }
+
+    fn read_packed(&self, pack: &PackIndex, offset: u64, depth: usize) -> Option<(u8, Vec<u8>)> {
+        if depth > MAX_DELTA_DEPTH {
+            return None;
+        }
+        enum Base {
+            Offset(u64),
+            Id([u8; 20]),
+        }
+        // Read this entry fully before resolving its base, which reuses the handle
+        let (kind, base, data) = {
+            let mut f = pack.handle()?;
+            f.seek(SeekFrom::Start(offset)).ok()?;
+
+            let mut byte = read_u8(&mut *f)?;
+            let kind = (byte >> 4) & 0x7;
+            let mut size = (byte & 0x0f) as u64;
+            let mut shift = 4;
+            while byte & 0x80 != 0 {
+                byte = read_u8(&mut *f)?;
+                size |= ((byte & 0x7f) as u64).checked_shl(shift)?;
+                shift += 7;
+            }
+
+            let base = match kind {
+                OBJ_OFS_DELTA => {
+                    let mut b = read_u8(&mut *f)?;
+                    let mut rel = (b & 0x7f) as u64;
+                    while b & 0x80 != 0 {
+                        b = read_u8(&mut *f)?;
+                        rel = rel.checked_add(1)?.checked_mul(0x80)? | (b & 0x7f) as u64;
+                    }
+                    Some(Base::Offset(offset.checked_sub(rel)?))
+                }
+                OBJ_REF_DELTA => {
+                    let mut id = [0u8; 20];
+                    f.read_exact(&mut id).ok()?;
+                    Some(Base::Id(id))
+                }
+                _ => None,
+            };
+
+            // `size` comes from the pack; let the decoder grow the buffer
+            let mut data = Vec::new();
+            ZlibDecoder::new(&mut *f)
+                .take(size)
+                .read_to_end(&mut data)
+                .ok()?;
+            (kind, base, data)
+        };
+
+        let base = match base {
+            Some(Base::Offset(at)) => Some(self.read_packed(pack, at, depth + 1)?),
+            Some(Base::Id(id)) => Some(self.read_object(&to_hex(&id))?),
+            None => None,
+        };
+
+        match base {
+            Some((base_kind, base_data)) => Some((base_kind, apply_delta(&base_data, &data)?)),
+            None => Some((kind, data)),
+        }
+    }
+
+    fn commit(&self, id: &str) -> Option<Commit> {
+        let (kind, data) = self.read_object(id)?;
+        if kind != OBJ_COMMIT {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&data);
+        let mut commit = Commit {
+            tree: String::new(),
+            parents: vec![],
+            time: 0,
+        };
+        for line in text.lines() {
+            if line.is_empty() {
+                break;
+            }
+            if let Some(t) = line.strip_prefix("tree ").filter(|t| is_hex_id(t)) {
+                commit.tree = t.to_string();
+            } else if let Some(p) = line.strip_prefix("parent ").filter(|p| is_hex_id(p)) {
+                commit.parents.push(p.to_string());
+            } else if let Some(c) = line.strip_prefix("committer ") {
+                // `Name <email> <unix-seconds> <tz>`
+                let mut parts = c.rsplitn(3, ' ');
+                let _tz = parts.next();
+                commit.time = parts.next().and_then(|t| t.parse().ok()).unwrap_or(0);
+            }
+        }
+        Some(commit)
+    }
+
+    /// Flatten a tree into `path -> (mode, blob id)`
+    fn flatten_tree(&self, id: &str, prefix: &str, out: &mut HashMap<String, (u32, String)>) {
+        let Some((OBJ_TREE, data)) = self.read_object(id) else {
+            return;
+        };
+        let mut rest = &data[..];
+        while let Some(sp) = rest.iter().position(|&b| b == b' ') {
+            let mode = std::str::from_utf8(&rest[..sp])
+                .ok()
+                .and_then(|m| u32::from_str_radix(m, 8).ok())
+                .unwrap_or(0);
+            rest = &rest[sp + 1..];
+            let Some(nul) = rest.iter().position(|&b| b == 0) else {
+                return;
+            };
+            let name = String::from_utf8_lossy(&rest[..nul]).to_string();
+            if rest.len() < nul + 21 {
+                return;
+            }
+            let child = to_hex(&rest[nul + 1..nul + 21]);
+            rest = &rest[nul + 21..];
+
+            let path = format!("{}{}", prefix, name);
+            if mode == 0o40000 {
+                self.flatten_tree(&child, &format!("{}/", path), out);
+            } else {
+                out.insert(path, (mode, child));
+            }
+        }
+    }
+
+    /// Commits reachable from `local` but not `remote`, and vice versa.
+    /// Walks both histories newest-first until they meet (like `git rev-list --count`).
+    fn ahead_behind(&self, local: &str, remote: &str) -> Option<(u32, u32)> {
+        const LEFT: u8 = 1;
+        const RIGHT: u8 = 2;
+        if local == remote {
+            return Some((0, 0));
+        }
+        let mut flags: HashMap<String, u8> = HashMap::new();
+        let mut commits: HashMap<String, Commit> = HashMap::new();
+        let mut heap: BinaryHeap<(i64, String)> = BinaryHeap::new();
+
+        for (id, flag) in [(local, LEFT), (remote, RIGHT)] {
+            let c = self.commit(id)?;
+            heap.push((c.time, id.to_string()));
+            commits.insert(id.to_string(), c);
+            flags.insert(id.to_string(), flag);
+        }
+
+        // Heap entries per commit, and how many entries are not yet reachable
+        // from both sides; the walk is done when that reaches zero
+        let mut queued: HashMap<String, usize> = HashMap::new();
+        let mut partial = 0usize;
+        for (_, id) in heap.iter() {
+            *queued.entry(id.clone()).or_default() += 1;
+            partial += 1;
+        }
+
+        // Once every queued entry is shared, keep walking while a shared entry
+        // could still reach a one-sided commit: commits with equal times may
+        // have been visited before their descendants
+        let mut floor: Option<i64> = None;
+        let mut visited = 0usize;
+        while let Some((_, id)) = heap.pop() {
+            visited += 1;
+            if visited > MAX_WALK {
+                return None;
+            }
+            let f = flags[&id];
+            if let Some(n) = queued.get_mut(&id) {
+                *n -= 1;
+            }
+            if f != LEFT | RIGHT {
+                partial -= 1;
+            }
+            let parents = commits
+                .get(&id)
+                .map(|c| c.parents.clone())
+                .unwrap_or_default();
+            for p in parents {
+                let pf = flags.get(&p).copied().unwrap_or(0);
+                if pf | f == pf {
+                    continue;
+                }
+                flags.insert(p.clone(), pf | f);
+                if pf | f == LEFT | RIGHT {
+                    // Entries already queued for `p` are settled now
+                    partial -= queued.get(&p).copied().unwrap_or(0);
+                }
+                if !commits.contains_key(&p) {
+                    commits.insert(p.clone(), self.commit(&p)?);
+                }
+                *queued.entry(p.clone()).or_default() += 1;
+                if pf | f != LEFT | RIGHT {
+                    partial += 1;
+                }
+                heap.push((commits[&p].time, p));
+            }
+            if partial == 0 {
+                let floor = *floor.get_or_insert_with(|| {
+                    flags
+                        .iter()
+                        .filter(|(_, &f)| f != LEFT | RIGHT)
+                        .filter_map(|(id, _)| commits.get(id))
+                        .map(|c| c.time)
+                        .min()
+                        .unwrap_or(i64::MAX)
+                });
+                if heap.peek().is_none_or(|(t, _)| *t < floor) {
+                    break;
+                }
+            }
+        }
+
+        let ahead = flags.values().filter(|&&f| f == LEFT).count() as u32;
+        let behind = flags.values().filter(|&&f| f == RIGHT).count() as u32;
+        Some((ahead, behind))
+    }
+
+    fn read_index(&self) -> Option<Vec<IndexEntry>> {
+        let data = match fs::read(self.git_dir.join("index")) {
+            Ok(d) => d,
+            // Fresh repository with nothing staged yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Some(vec![]),
+            Err(_) => return None,
+        };
+        parse_index(&data)
+    }
+}
+
+fn read_u8(r: &mut impl Read) -> Option<u8> {
+    let mut b = [0u8; 1];
+    r.read_exact(&mut b).ok()?;
+    Some(b[0])
+}
+
+fn be32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Option<usize> {
+    let mut out = 0usize;
+    let mut shift = 0;
+    loop {
+        let b = *data.get(*pos)?;
+        *pos += 1;
+        out |= ((b & 0x7f) as usize).checked_shl(shift)?;
+        shift += 7;
+        if b & 0x80 == 0 {
+            return Some(out);
+        }
+    }
+}
+
+fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    let mut pos = 0;
+    let _src_len = read_varint(delta, &mut pos)?;
+    let dst_len = read_varint(delta, &mut pos)?;
+    let mut out = Vec::with_capacity(dst_len.min(base.len() + delta.len()));
+    while pos < delta.len() {
+        let op = delta[pos];
+        pos += 1;
+        if op & 0x80 != 0 {
+            let mut off = 0usize;
+            let mut len = 0usize;
+            for i in 0..4 {
+                if op & (1 << i) != 0 {
+                    off |= (*delta.get(pos)? as usize) << (8 * i);
+                    pos += 1;
+                }
+            }
+            for i in 0..3 {
+                if op & (0x10 << i) != 0 {
+                    len |= (*delta.get(pos)? as usize) << (8 * i);
+                    pos += 1;
+                }
+            }
+            if len == 0 {
+                len = 0x10000;
+            }
+            out.extend_from_slice(base.get(off..off + len)?);
+        } else if op != 0 {
+            let n = op as usize;
+            out.extend_from_slice(delta.get(pos..pos + n)?);
+            pos += n;
+        } else {
+            return None;
+        }
+    }
+    (out.len() == dst_len).then_some(out)
+}
+
+/// Version-2 pack index (`objects/pack/*.idx`)
+struct PackIndex {
+    pack_path: PathBuf,
+    /// Opened on first use and kept for the rest of the read
+    pack: RefCell<Option<BufReader<File>>>,
+    fanout: Vec<u32>,
+    ids: Vec<u8>,
+    offsets: Vec<u8>,
+    large_offsets: Vec<u8>,
+}
+
+impl PackIndex {
+    fn load_all(dir: &Path) -> Vec<PackIndex> {
+        let Ok(rd) = fs::read_dir(dir) else {
+            return vec![];
+        };
+        rd.filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("idx"))
+            .filter_map(|p| PackIndex::load(&p))
+            .collect()
+    }
+
+    fn load(idx_path: &Path) -> Option<PackIndex> {
+        let data = fs::read(idx_path).ok()?;
+        if data.get(..4)? != b"\xfftOc" || be32(&data, 4)? != 2 {
+            return None;
+        }
+        let fanout: Vec<u32> = (0..256)
+            .map(|i| be32(&data, 8 + i * 4))
+            .collect::<Option<_>>()?;
+        let n = *fanout.last()? as usize;
+        let ids_at = 8 + 256 * 4;
+        let offs_at = ids_at + n * 20 + n * 4;
+        let large_at = offs_at + n * 4;
+        Some(PackIndex {
+            pack_path: idx_path.with_extension("pack"),
+            pack: RefCell::new(None),
+            fanout,
+            ids: data.get(ids_at..ids_at + n * 20)?.to_vec(),
+            offsets: data.get(offs_at..large_at)?.to_vec(),
+            large_offsets: data.get(large_at..data.len().saturating_sub(40))?.to_vec(),
+        })
+    }
+
+    fn handle(&self) -> Option<RefMut<'_, BufReader<File>>> {
+        let mut h = self.pack.borrow_mut();
+        if h.is_none() {
+            *h = Some(BufReader::new(File::open(&self.pack_path).ok()?));
+        }
+        Some(RefMut::map(h, |h| h.as_mut().expect("pack opened above")))
+    }
+
+    fn find(&self, id: &[u8; 20]) -> Option<u64> {
+        let first = id[0] as usize;
+        let lo = if first == 0 {
+            0
+        } else {
+            self.fanout[first - 1] as usize
+        };
+        let hi = self.fanout[first] as usize;
+        let (mut lo, mut hi) = (lo, hi);
+        let pos = loop {
+            if lo >= hi {
+                return None;
+            }
+            let mid = lo + (hi - lo) / 2;
+            match self.ids.get(mid * 20..mid * 20 + 20)?.cmp(&id[..]) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => break mid,
+            }
+        };
+        let off = be32(&self.offsets, pos * 4)?;
+        if off & 0x8000_0000 == 0 {
+            return Some(off as u64);
+        }
+        let at = (off & 0x7fff_ffff) as usize * 8;
+        Some(u64::from_be_bytes(
+            self.large_offsets.get(at..at + 8)?.try_into().ok()?,
+        ))
+    }
+}
+
+/// Parse a v2/v3/v4 `.git/index`
+fn parse_index(data: &[u8]) -> Option<Vec<IndexEntry>> {
+    if data.get(..4)? != b"DIRC" {
+        return None;
+    }
+    let version = be32(data, 4)?;
+    let count = be32(data, 8)? as usize;
+    let mut pos = 12;
+    // Every entry takes at least 62 bytes, whatever the header claims
+    let mut entries = Vec::with_capacity(count.min(data.len() / 62));
+    let mut prev_path: Vec<u8> = vec![];
+
+    for _ in 0..count {
+        let start = pos;
+        let mtime = (be32(data, pos + 8)?, be32(data, pos + 12)?);
+        let mode = be32(data, pos + 24)?;
+        let size = be32(data, pos + 36)?;
+        let mut id = [0u8; 20];
+        id.copy_from_slice(data.get(pos + 40..pos + 60)?);
+        let flags = u16::from_be_bytes(data.get(pos + 60..pos + 62)?.try_into().ok()?);
+        pos += 62;
+        if version >= 3 && flags & 0x4000 != 0 {
+            pos += 2;
+        }
+
+        let path = if version >= 4 {
+            let strip = read_varint(data, &mut pos)?;
+            let nul = pos + data.get(pos..)?.iter().position(|&b| b == 0)?;
+            let keep = prev_path.len().checked_sub(strip)?;
+            let mut p = prev_path[..keep].to_vec();
+            p.extend_from_slice(&data[pos..nul]);
+            pos = nul + 1;
+            p
+        } else {
+            let nul = pos + data.get(pos..)?.iter().position(|&b| b == 0)?;
+            let p = data[pos..nul].to_vec();
+            // Entries are NUL-padded to a multiple of 8 bytes
+            let len = nul - start;
+            pos = start + (len + 8) / 8 * 8;
+            p
+        };
+
+        entries.push(IndexEntry {
+            path: String::from_utf8_lossy(&path).to_string(),
+            mode,
+            size,
+            mtime,
+            id,
+            stage: (flags >> 12) & 0x3,
+        });
+        prev_path = path;
+    }
+    Some(entries)
+}
+
+fn blob_id(content: &[u8]) -> [u8; 20] {
+    let mut h = Sha1::new();
+    h.update(format!("blob {}\0", content.len()).as_bytes());
+    h.update(content);
+    h.finalize().into()
+}
+
+/// Tracked files that differ from the index (missing, or stat changed and content differs)
+fn worktree_dirty(repo: &Repo, entries: &[IndexEntry]) -> bool {
+    entries.iter().any(|e| {
+        if e.stage != 0 {
+            // Unresolved merge conflict
+            return true;
+        }
+        if e.mode == MODE_GITLINK {
+            return false;
+        }
+        let p = repo.work_tree.join(&e.path);
+        let Ok(md) = fs::symlink_metadata(&p) else {
+            return true;
+        };
+        let mtime = md
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| (d.as_secs() as u32, d.subsec_nanos()));
+        if md.len() as u32 == e.size && mtime == Some(e.mtime) {
+            return false;
+        }
+        let content = if e.mode == MODE_SYMLINK {
+            fs::read_link(&p)
+                .ok()
+                .map(|t| t.to_string_lossy().into_owned().into_bytes())
+        } else {
+            fs::read(&p).ok()
+        };
+        content.map(|c| blob_id(&c) != e.id).unwrap_or(true)
+    })
+}
+
+/// Index differs from the HEAD tree (something is staged)
+fn staged_changes(repo: &Repo, tree: &str, entries: &[IndexEntry]) -> bool {
+    let mut head = HashMap::new();
+    repo.flatten_tree(tree, "", &mut head);
+    if head.len() != entries.len() {
+        return true;
+    }
+    entries.iter().any(|e| match head.get(&e.path) {
+        Some((_, id)) => *id != to_hex(&e.id),
+        None => true,
+    })
+}
+
+/// Files in the work tree that are neither tracked nor ignored.
+/// Nested repositories are skipped entirely.
+fn count_untracked(work_tree: &Path, entries: &[IndexEntry]) -> u32 {
+    let tracked: HashSet<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+    let root = work_tree.to_path_buf();
+    let mut wb = WalkBuilder::new(work_tree);
+    wb.standard_filters(false)
+        .hidden(false)
+        .follow_links(false)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .parents(false)
+        .filter_entry(move |e| {
+            if e.file_name() == ".git" {
+                return false;
+            }
+            let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            !(is_dir && e.path() != root && e.path().join(".git").exists())
+        });
+
+    let mut n = 0u32;
+    for e in wb.build().filter_map(|e| e.ok()) {
+        if !e.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+        let Ok(rel) = e.path().strip_prefix(work_tree) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if !tracked.contains(rel.as_str()) {
+            n += 1;
+        }
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_delta, index_stamp, parse_index, read_git_info, read_git_info_with_untracked,
+        read_varint,
+    };
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let out = Command::new("git")
+            .args([
+                "-c",
+                "user.name=Orbit",
+                "-c",
+                "user.email=orbit@example.com",
+            ])
+            .args([
+                "-c",
+                "init.defaultBranch=main",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("run git");
+        assert!(
+            out.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    fn commit_file(dir: &Path, name: &str, content: &str) {
+        fs::write(dir.join(name), content).unwrap();
+        git(dir, &["add", name]);
+        git(dir, &["commit", "-q", "-m", name]);
+    }
+
+    #[test]
+    fn reads_branch_head_and_worktree_state() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        git(repo, &["init", "-q"]);
+        assert!(read_git_info(repo).unwrap().head.is_none());
+
+        commit_file(repo, "README.md", "hello\n");
+        let clean = read_git_info(repo).unwrap();
+        assert_eq!(clean.branch.as_deref(), Some("main"));
+        assert_eq!(clean.head.as_ref().map(|h| h.len()), Some(40));
+        assert!(clean.head_date.is_some());
+        assert!(!clean.dirty);
+        assert_eq!(clean.untracked, 0);
+
+        fs::write(repo.join("README.md"), "hello, world\n").unwrap();
+        fs::write(repo.join("new.txt"), "x").unwrap();
+        fs::write(repo.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(repo.join("debug.log"), "x").unwrap();
+        let dirty = read_git_info(repo).unwrap();
+        assert!(dirty.dirty);
+        assert_eq!(dirty.untracked, 2, "new.txt and .gitignore");
+
+        git(repo, &["stash", "-q"]);
+        let stashed = read_git_info(repo).unwrap();
+        assert!(!stashed.dirty);
+        assert_eq!(stashed.stashes, 1);
+
+        fs::write(repo.join("staged.txt"), "s").unwrap();
+        let before_add = index_stamp(repo).unwrap();
+        git(repo, &["add", "staged.txt"]);
+        assert!(read_git_info(repo).unwrap().dirty);
+        assert_ne!(index_stamp(repo).unwrap(), before_add);
+        let reused = read_git_info_with_untracked(repo, Some(7)).unwrap();
+        assert_eq!(reused.untracked, 7, "count is taken as given");
+    }
+
+    #[test]
+    fn computes_ahead_behind_from_packed_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let origin = tmp.path().join("origin");
+        fs::create_dir_all(&origin).unwrap();
+        git(&origin, &["init", "-q"]);
+        commit_file(&origin, "a.txt", "a");
+
+        git(tmp.path(), &["clone", "-q", "origin", "clone"]);
+        let clone = tmp.path().join("clone");
+        commit_file(&origin, "b.txt", "b");
+        git(&clone, &["fetch", "-q"]);
+        commit_file(&clone, "c.txt", "c");
+        commit_file(&clone, "d.txt", "d");
+        // Move everything into packfiles (and packed-refs)
+        git(&clone, &["gc", "-q", "--aggressive"]);
+
+        let info = read_git_info(&clone).unwrap();
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert_eq!(info.upstream.as_deref(), Some("origin/main"));
+        assert_eq!(info.ahead, Some(2));
+        assert_eq!(info.behind, Some(1));
+        assert!(info.head_date.is_some());
+        assert!(!info.dirty);
+    }
+
+    #[test]
+    fn ahead_behind_matches_git_across_merges() {
+        let tmp = tempfile::tempdir().unwrap();
+        let origin = tmp.path().join("origin");
+        fs::create_dir_all(&origin).unwrap();
+        git(&origin, &["init", "-q"]);
+        commit_file(&origin, "a.txt", "a");
+        git(tmp.path(), &["clone", "-q", "origin", "clone"]);
+        let clone = tmp.path().join("clone");
+        for i in 0..3 {
+            commit_file(&origin, &format!("o{i}.txt"), "o");
+        }
+        git(&clone, &["checkout", "-q", "-b", "topic"]);
+        commit_file(&clone, "t.txt", "t");
+        git(&clone, &["checkout", "-q", "main"]);
+        commit_file(&clone, "m.txt", "m");
+        git(&clone, &["merge", "-q", "--no-edit", "topic"]);
+        git(&clone, &["fetch", "-q"]);
+        git(&clone, &["merge", "-q", "--no-edit", "origin/main~1"]);
+
+        let out = Command::new("git")
+            .args(["rev-list", "--left-right", "--count", "main...origin/main"])
+            .current_dir(&clone)
+            .output()
+            .unwrap();
+        let counts = String::from_utf8_lossy(&out.stdout);
+        let expected: Vec<u32> = counts
+            .split_whitespace()
+            .map(|n| n.parse().unwrap())
+            .collect();
+        let info = read_git_info(&clone).unwrap();
+        assert_eq!(info.ahead, Some(expected[0]));
+        assert_eq!(info.behind, Some(expected[1]));
+    }
+
+    #[test]
+    fn malformed_commit_headers_do_not_panic() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        git(repo, &["init", "-q"]);
+        commit_file(repo, "README.md", "hello\n");
+        // No `tree` line, and a parent that isn't an id (multi-byte at the split point)
+        let body = "parent aé0000000000000000000000000000000000000\n\
+                    committer Orbit <orbit@example.com> 1700000000 +0000\n\nbroken\n";
+        fs::write(repo.join("bad.txt"), body).unwrap();
+        let out = Command::new("git")
+            .args([
+                "hash-object",
+                "-t",
+                "commit",
+                "--literally",
+                "-w",
+                "bad.txt",
+            ])
+            .current_dir(repo)
+            .output()
+            .unwrap();
+        let id = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        assert_eq!(id.len(), 40);
+        fs::remove_file(repo.join("bad.txt")).unwrap();
+        fs::write(
+            repo.join(".git").join("refs").join("heads").join("main"),
+            &id,
+        )
+        .unwrap();
+
+        let info = read_git_info(repo).unwrap();
+        assert_eq!(info.head.as_deref(), Some(id.as_str()));
+        assert!(info.dirty, "a commit without a tree can't match the index");
+    }
+
+    #[test]
+    fn garbage_pack_and_index_headers_yield_none() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        git(repo, &["init", "-q"]);
+        commit_file(repo, "README.md", "hello\n");
+        git(repo, &["gc", "-q"]);
+        let pack_dir = repo.join(".git").join("objects").join("pack");
+        let pack = fs::read_dir(&pack_dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .find(|p| p.extension().is_some_and(|e| e == "pack"))
+            .unwrap();
+        // The first entry (the commit) claims a size past u64 in 11 varint bytes
+        let mut data = fs::read(&pack).unwrap();
+        data[12..23].copy_from_slice(&[
+            0x9f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+        ]);
+        let mut perms = fs::metadata(&pack).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        perms.set_readonly(false);
+        fs::set_permissions(&pack, perms).unwrap();
+        fs::write(&pack, data).unwrap();
+        // ...and the index claims four billion entries it doesn't have
+        let mut index = b"DIRC".to_vec();
+        index.extend_from_slice(&[0, 0, 0, 2, 0xff, 0xff, 0xff, 0xff]);
+        fs::write(repo.join(".git").join("index"), &index).unwrap();
+
+        let info = read_git_info(repo).unwrap();
+        assert!(info.head.is_some());
+        assert!(info.head_date.is_none(), "the commit can't be read");
+
+        assert!(parse_index(&index).is_none());
+        let mut pos = 0;
+        assert!(read_varint(&[0xff; 11], &mut pos).is_none());
+        // Delta header: source length 1, destination length 2^56
+        let delta = [
+            0x01, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01, 0x01, b'x',
+        ];
+        assert!(apply_delta(b"a", &delta).is_none());
+    }
+
+    #[test]
+    fn no_repository_yields_none() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(read_git_info(tmp.path()).is_none());
+    }
+}
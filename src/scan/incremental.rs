@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use blake3::Hasher;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::index::store::atomic_write;
+use crate::model::project::ProjectEntry;
+use crate::scan::rules::{self, ScanRules};
+
+/// Bumped when cached entries gain fields that must be recomputed
+pub const CACHE_VERSION: u32 = 3;
+
+/// Per-project stat cache that lets a census skip projects whose tree is unchanged.
+/// Stored at `<root>/.orbit/census_cache.json`.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CensusCache {
+    pub version: u32,
+    /// `--since` value the cached summaries were computed with
+    pub since: Option<String>,
+    /// `[artifacts]` config the cached artifact counts were computed with
+    #[serde(default)]
+    pub artifact_rules: String,
+    pub projects: BTreeMap<String, CachedProject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedProject {
+    pub stamp: String,
+    pub entry: ProjectEntry,
+}
+
+impl CensusCache {
+    /// Return the cached entry for `path` if its tree stamp still matches.
+    pub fn lookup(&self, path: &str, stamp: &str) -> Option<&ProjectEntry> {
+        self.projects
+            .get(path)
+            .filter(|c| c.stamp == stamp)
+            .map(|c| &c.entry)
+    }
+}
+
+pub fn cache_path(root: &Path) -> PathBuf {
+    root.join(".orbit").join("census_cache.json")
+}
+
+pub fn load_cache(root: &Path) -> Result<CensusCache> {
+    let p = cache_path(root);
+    if !p.exists() {
+        return Ok(CensusCache::default());
+    }
+    let content =
+        fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", p.display()))
+}
+
+pub fn save_cache(root: &Path, cache: &CensusCache) -> Result<()> {
+    let p = cache_path(root);
+    if let Some(parent) = p.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    let content = serde_json::to_string(cache).context("Failed to serialize census cache")?;
+    atomic_write(&p, &content)
+}
+
+/// Cheap change detector for a project tree.
+///
+/// Hashes every entry name plus the mtime of every entry and the size of every
+/// file. Nothing is read, so this is much cheaper than a full summarize, and
+/// in-place edits anywhere in the tree change the stamp.
+/// Walks the same tree as summarization (`rules`, minus `exclude` subtrees).
+pub fn tree_stamp(project_root: &Path, rules: &ScanRules, exclude: &[PathBuf]) -> Result<String> {
+    let mut hasher = Hasher::new();
+
+    for item in rules.walker(project_root, exclude).build() {
+        let Some(entry) = rules::walk_entry(item, project_root)? else {
+            continue;
+        };
+        let Some(ft) = entry.file_type() else {
+            continue;
+        };
+        let rel = entry
+            .path()
+            .strip_prefix(project_root)
+            .unwrap_or(entry.path());
+        hasher.update(rel.to_string_lossy().as_bytes());
+        hasher.update(b"\n");
+
+        let md = entry
+            .metadata()
+            .with_context(|| format!("Failed to read metadata for {}", entry.path().display()))?;
+        let nanos = md
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        hasher.update(&nanos.to_le_bytes());
+        if ft.is_file() {
+            hasher.update(&md.len().to_le_bytes());
+        }
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tree_stamp;
+    use crate::scan::rules::ScanRules;
+    use std::fs;
+
+    #[test]
+    fn stamp_is_stable_for_unchanged_tree() {
+        let tmp = tempfile::tempdir().unwrap();
+        let rules = ScanRules::default();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src").join("lib.rs"), "fn a() {}").unwrap();
+        let a = tree_stamp(tmp.path(), &rules, &[]).unwrap();
+        let b = tree_stamp(tmp.path(), &rules, &[]).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn stamp_changes_when_files_are_added_or_root_files_edited() {
+        let tmp = tempfile::tempdir().unwrap();
+        let rules = ScanRules::default();
+        fs::write(tmp.path().join("README.md"), "hello").unwrap();
+        let before = tree_stamp(tmp.path(), &rules, &[]).unwrap();
+
+        fs::create_dir_all(tmp.path().join("docs")).unwrap();
+        fs::write(tmp.path().join("docs").join("plan.md"), "x").unwrap();
+        let added = tree_stamp(tmp.path(), &rules, &[]).unwrap();
+        assert_ne!(before, added);
+
+        fs::write(tmp.path().join("README.md"), "hello, world").unwrap();
+        let edited = tree_stamp(tmp.path(), &rules, &[]).unwrap();
+        assert_ne!(added, edited);
+    }
+
+    #[test]
+    fn stamp_changes_when_nested_files_are_edited_in_place() {
+        let tmp = tempfile::tempdir().unwrap();
+        let rules = ScanRules::default();
+        let lib = tmp.path().join("src").join("lib.rs");
+        fs::create_dir_all(lib.parent().unwrap()).unwrap();
+        fs::write(&lib, "fn a() {}").unwrap();
+        let before = tree_stamp(tmp.path(), &rules, &[]).unwrap();
+
+        // Same size, new mtime, parent directory untouched
+        let dir_mtime = fs::metadata(lib.parent().unwrap())
+            .unwrap()
+            .modified()
+            .unwrap();
+        fs::write(&lib, "fn b() {}").unwrap();
+        let f = fs::File::options().write(true).open(&lib).unwrap();
+        f.set_modified(dir_mtime + std::time::Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(
+            fs::metadata(lib.parent().unwrap())
+                .unwrap()
+                .modified()
+                .unwrap(),
+            dir_mtime
+        );
+        assert_ne!(before, tree_stamp(tmp.path(), &rules, &[]).unwrap());
+    }
+}
@@ -0,0 +1,268 @@
+use std::fs;
+use std::path::Path;
+
+use crate::model::project::LocCounts;
+
+/// Files larger than this are assumed generated/binary and not counted
+const MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+struct Language {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+}
+
+const C_STYLE: Option<(&str, &str)> = Some(("/*", "*/"));
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        name: "Rust",
+        extensions: &["rs"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Python",
+        extensions: &["py", "pyi"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    Language {
+        name: "JavaScript",
+        extensions: &["js", "jsx", "mjs", "cjs"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "TypeScript",
+        extensions: &["ts", "tsx", "mts", "cts"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Go",
+        extensions: &["go"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Java",
+        extensions: &["java"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Kotlin",
+        extensions: &["kt", "kts"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "C",
+        extensions: &["c", "h"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "C++",
+        extensions: &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "C#",
+        extensions: &["cs"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Swift",
+        extensions: &["swift"],
+        line_comments: &["//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Ruby",
+        extensions: &["rb"],
+        line_comments: &["#"],
+        block_comment: Some(("=begin", "=end")),
+    },
+    Language {
+        name: "Elixir",
+        extensions: &["ex", "exs"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    Language {
+        name: "Terraform",
+        extensions: &["tf", "tfvars"],
+        line_comments: &["#", "//"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Shell",
+        extensions: &["sh", "bash", "zsh"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    Language {
+        name: "SQL",
+        extensions: &["sql"],
+        line_comments: &["--"],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "Lua",
+        extensions: &["lua"],
+        line_comments: &["--"],
+        block_comment: Some(("--[[", "]]")),
+    },
+    Language {
+        name: "HTML",
+        extensions: &["html", "htm"],
+        line_comments: &[],
+        block_comment: Some(("<!--", "-->")),
+    },
+    Language {
+        name: "CSS",
+        extensions: &["css", "scss"],
+        line_comments: &[],
+        block_comment: C_STYLE,
+    },
+    Language {
+        name: "TOML",
+        extensions: &["toml"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    Language {
+        name: "YAML",
+        extensions: &["yaml", "yml"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    Language {
+        name: "JSON",
+        extensions: &["json"],
+        line_comments: &[],
+        block_comment: None,
+    },
+    Language {
+        name: "Markdown",
+        extensions: &["md", "markdown"],
+        line_comments: &[],
+        block_comment: Some(("<!--", "-->")),
+    },
+];
+
+fn language_for(path: &Path) -> Option<&'static Language> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|l| l.extensions.contains(&ext.as_str()))
+}
+
+/// Count lines of `path` if it is a recognized source file.
+/// Returns the language name and counts; unreadable or oversized files yield `None`.
+pub fn count_file(path: &Path, len: u64) -> Option<(&'static str, LocCounts)> {
+    let lang = language_for(path)?;
+    if len > MAX_FILE_BYTES {
+        return None;
+    }
+    let bytes = fs::read(path).ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    let mut counts = count_lines(&text, lang);
+    counts.files = 1;
+    Some((lang.name, counts))
+}
+
+/// Classify each line as blank, comment or code. Strings aren't tokenized, so
+/// comment markers inside string literals count as code (cloc-style heuristic).
+fn count_lines(text: &str, lang: &Language) -> LocCounts {
+    let mut c = LocCounts::default();
+    let mut in_block: Option<&str> = None;
+
+    for line in text.lines() {
+        let t = line.trim();
+        if t.is_empty() {
+            c.blank += 1;
+            continue;
+        }
+        if let Some(end) = in_block {
+            c.comment += 1;
+            if t.contains(end) {
+                in_block = None;
+            }
+            continue;
+        }
+        // Block openers first: Lua's `--[[` also starts with its line comment `--`
+        if let Some((start, end)) = lang.block_comment {
+            if let Some(rest) = t.strip_prefix(start) {
+                c.comment += 1;
+                if !rest.contains(end) {
+                    in_block = Some(end);
+                }
+                continue;
+            }
+        }
+        if lang.line_comments.iter().any(|p| t.starts_with(p)) {
+            c.comment += 1;
+            continue;
+        }
+        c.code += 1;
+        // Code followed by an unterminated block comment opener
+        if let Some((start, end)) = lang.block_comment {
+            if let Some(i) = t.rfind(start) {
+                if !t[i + start.len()..].contains(end) {
+                    in_block = Some(end);
+                }
+            }
+        }
+    }
+    c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_lines, language_for};
+    use crate::model::project::LocCounts;
+    use std::path::Path;
+
+    #[test]
+    fn counts_rust_code_comments_and_blanks() {
+        let src = "// header\nfn main() {\n\n    /* block\n       still */\n    let x = 1; /* trailing\n    */\n}\n";
+        let lang = language_for(Path::new("main.rs")).unwrap();
+        assert_eq!(
+            count_lines(src, lang),
+            LocCounts {
+                files: 0,
+                code: 3,
+                comment: 4,
+                blank: 1
+            }
+        );
+    }
+
+    #[test]
+    fn counts_python_hash_comments() {
+        let src = "#!/usr/bin/env python\nimport os\n\n# note\nprint(os)\n";
+        let lang = language_for(Path::new("x.py")).unwrap();
+        let c = count_lines(src, lang);
+        assert_eq!((c.code, c.comment, c.blank), (2, 2, 1));
+    }
+
+    #[test]
+    fn counts_lua_block_comments() {
+        let src = "-- line\n--[[ block\nlocal hidden = 1\n]]\nlocal x = 1\n--[[ one line ]]\n";
+        let lang = language_for(Path::new("init.lua")).unwrap();
+        let c = count_lines(src, lang);
+        assert_eq!((c.code, c.comment, c.blank), (1, 5, 0));
+    }
+
+    #[test]
+    fn unknown_extensions_are_ignored() {
+        assert!(language_for(Path::new("photo.png")).is_none());
+        assert!(language_for(Path::new("Makefile")).is_none());
+    }
+}
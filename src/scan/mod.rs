@@ -1,5 +1,15 @@
 pub mod artifacts;
+pub mod build_dirs;
 pub mod census;
+pub mod delta;
 pub mod discover;
+pub mod ecosystem;
 pub mod fingerprint;
+pub mod git;
+pub mod incremental;
+pub mod loc;
 pub mod progress;
+pub mod rules;
+pub mod similarity;
+pub mod treediff;
+pub mod watch;
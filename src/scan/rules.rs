@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::OrbitConfig;
+use crate::scan::artifacts::ArtifactRules;
+
+/// Directory names never descended into (build outputs, VCS internals, vendored deps,
+/// Orbit's own state)
+pub const DEFAULT_SKIP_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    ".git",
+    ".orbit",
+    "__pycache__",
+    "venv",
+    ".venv",
+    "dist",
+    "build",
+    ".next",
+    "vendor",
+];
+
+/// Files whose presence marks a directory as a project root
+pub const DEFAULT_MARKERS: &[&str] = &[
+    "**/CLAUDE.md",
+    "**/AGENT.md",
+    "**/HANDOFF*.md",
+    "**/*handoff*.md",
+    "**/*export*.md",
+    "**/*session*.md",
+    "**/README.md",
+    "**/Cargo.toml",
+    "**/pyproject.toml",
+    "**/package.json",
+];
+
+/// Per-directory ignore file honored alongside `.gitignore` (same syntax)
+pub const ORBIT_IGNORE_FILE: &str = ".orbitignore";
+
+/// Compiled skip set, marker globset and artifact rules shared by discovery,
+/// summarization and snapshot copying. Built once per run from the layered config.
+#[derive(Debug, Clone)]
+pub struct ScanRules {
+    skip_dirs: Arc<HashSet<String>>,
+    markers: GlobSet,
+    artifacts: ArtifactRules,
+    /// Honor .gitignore, global git excludes and .orbitignore
+    respect_ignores: bool,
+}
+
+impl Default for ScanRules {
+    fn default() -> Self {
+        Self::from_config(&OrbitConfig::default()).expect("default scan rules are valid")
+    }
+}
+
+impl ScanRules {
+    pub fn from_config(cfg: &OrbitConfig) -> Result<Self> {
+        let skip_dirs = DEFAULT_SKIP_DIRS
+            .iter()
+            .map(|s| s.to_string())
+            .chain(cfg.scan.skip_dirs.iter().cloned())
+            .collect();
+
+        let mut b = GlobSetBuilder::new();
+        for m in DEFAULT_MARKERS {
+            b.add(Glob::new(m).expect("valid glob"));
+        }
+        for m in &cfg.scan.markers {
+            // Bare file names (`go.mod`) match at any depth, like the defaults
+            let pat = if m.contains('/') {
+                m.clone()
+            } else {
+                format!("**/{m}")
+            };
+            b.add(Glob::new(&pat).with_context(|| format!("Invalid marker glob '{}'", m))?);
+        }
+        let markers = b.build().context("Failed to build marker globset")?;
+
+        Ok(Self {
+            skip_dirs: Arc::new(skip_dirs),
+            markers,
+            artifacts: cfg.artifacts.compile()?,
+            respect_ignores: true,
+        })
+    }
+
+    /// Toggle .gitignore/.orbitignore handling (off for `--no-ignore` audits)
+    pub fn with_ignore_files(mut self, respect: bool) -> Self {
+        self.respect_ignores = respect;
+        self
+    }
+
+    /// True for directories that should not be descended into
+    pub fn should_skip(&self, entry: &walkdir::DirEntry) -> bool {
+        entry.file_type().is_dir() && is_skip_dir(&self.skip_dirs, entry.file_name())
+    }
+
+    /// Walker over `root` that prunes skip dirs and `exclude` subtrees and,
+    /// unless disabled, paths matched by ignore files. Hidden files are included.
+    pub fn walker(&self, root: &Path, exclude: &[PathBuf]) -> WalkBuilder {
+        let mut wb = WalkBuilder::new(root);
+        wb.standard_filters(false)
+            .hidden(false)
+            .follow_links(false)
+            .sort_by_file_name(|a, b| a.cmp(b));
+        if self.respect_ignores {
+            wb.git_ignore(true)
+                .git_global(true)
+                .git_exclude(true)
+                .require_git(false)
+                .parents(true)
+                .add_custom_ignore_filename(ORBIT_IGNORE_FILE);
+        }
+        let skip_dirs = Arc::clone(&self.skip_dirs);
+        let exclude = exclude.to_vec();
+        wb.filter_entry(move |e| {
+            let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            !(is_dir
+                && e.depth() > 0
+                && (is_skip_dir(&skip_dirs, e.file_name())
+                    || exclude.iter().any(|x| x.as_path() == e.path())))
+        });
+        wb
+    }
+
+    /// True when `rel` lies inside a skipped directory
+    pub fn skips_path(&self, rel: &Path) -> bool {
+        rel.parent()
+            .map(|d| {
+                d.components()
+                    .any(|c| is_skip_dir(&self.skip_dirs, c.as_os_str()))
+            })
+            .unwrap_or(false)
+    }
+
+    /// True when `rel` (relative to the scan root) is a project marker file
+    pub fn is_marker(&self, rel: &Path) -> bool {
+        self.markers.is_match(rel)
+    }
+
+    /// Artifact category of `rel` (relative to its project), if any rule matches
+    pub fn artifact_category(&self, rel: &Path) -> Option<&str> {
+        self.artifacts.category(rel)
+    }
+}
+
+fn is_skip_dir(skip_dirs: &HashSet<String>, name: &OsStr) -> bool {
+    name.to_str()
+        .map(|s| skip_dirs.contains(s))
+        .unwrap_or(false)
+}
+
+/// Unwrap a walker item. Unreadable paths are errors; malformed ignore-file
+/// lines are tolerated (`Ok(None)`) so one bad pattern doesn't abort a census.
+pub fn walk_entry(
+    item: std::result::Result<ignore::DirEntry, ignore::Error>,
+    root: &Path,
+) -> Result<Option<ignore::DirEntry>> {
+    match item {
+        Ok(e) => Ok(Some(e)),
+        Err(e) if e.io_error().is_none() => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read entry in {}", root.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScanRules;
+    use crate::config::OrbitConfig;
+    use std::fs;
+    use std::path::Path;
+
+    fn walked(rules: &ScanRules, root: &Path) -> Vec<String> {
+        let mut out: Vec<String> = rules
+            .walker(root, &[])
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+            .map(|e| {
+                e.path()
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn walker_honors_gitignore_and_orbitignore() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("coverage")).unwrap();
+        fs::create_dir_all(root.join("dumps")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join(".gitignore"), "coverage/\n").unwrap();
+        fs::write(root.join(".orbitignore"), "dumps/\n").unwrap();
+        fs::write(root.join("coverage").join("lcov.info"), "x").unwrap();
+        fs::write(root.join("dumps").join("db.sql"), "x").unwrap();
+        fs::write(root.join("target").join("out.bin"), "x").unwrap();
+        fs::create_dir_all(root.join(".orbit").join("exports")).unwrap();
+        fs::write(root.join(".orbit").join("exports").join("delta.md"), "x").unwrap();
+        fs::write(root.join("main.rs"), "x").unwrap();
+
+        let rules = ScanRules::default();
+        assert_eq!(
+            walked(&rules, root),
+            vec![".gitignore", ".orbitignore", "main.rs"]
+        );
+
+        let audit = ScanRules::default().with_ignore_files(false);
+        assert_eq!(
+            walked(&audit, root),
+            vec![
+                ".gitignore",
+                ".orbitignore",
+                "coverage/lcov.info",
+                "dumps/db.sql",
+                "main.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn config_extends_default_markers() {
+        let mut cfg = OrbitConfig::default();
+        cfg.scan.markers = vec!["go.mod".into(), "infra/*.tf".into()];
+        let rules = ScanRules::from_config(&cfg).unwrap();
+        assert!(rules.is_marker(Path::new("svc/go.mod")));
+        assert!(rules.is_marker(Path::new("infra/main.tf")));
+        assert!(rules.is_marker(Path::new("app/Cargo.toml")));
+        assert!(!rules.is_marker(Path::new("app/pom.xml")));
+    }
+
+    #[test]
+    fn invalid_marker_glob_is_rejected() {
+        let mut cfg = OrbitConfig::default();
+        cfg.scan.markers = vec!["a/[".into()];
+        assert!(ScanRules::from_config(&cfg).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::model::project::ProjectEntry;
+
+/// Projects at or above this estimated similarity are grouped as near-duplicates
+pub const DEFAULT_THRESHOLD: f64 = 0.85;
+
+/// Number of MinHash slots; the Jaccard estimate has a standard error of ~1/sqrt(64)
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Files above this size are keyed by path and size instead of hashed in full
+const MAX_HASH_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Content digest used for per-file comparison: blake3 of the file, or its
+/// length for very large files
+pub fn file_digest(path: &Path, len: u64) -> Result<String> {
+    if len > MAX_HASH_BYTES {
+        return Ok(format!("size:{len}"));
+    }
+    let data =
+        fs::read(path).with_context(|| format!("Failed to read {} for hashing", path.display()))?;
+    Ok(blake3::hash(&data).to_hex().to_string())
+}
+
+/// Builds a MinHash signature over `(relative path, content digest)` tokens,
+/// so two trees sharing most files at the same paths score close to 1.0.
+pub struct MinHasher {
+    mins: [u32; SIGNATURE_LEN],
+    items: usize,
+}
+
+impl Default for MinHasher {
+    fn default() -> Self {
+        Self {
+            mins: [u32::MAX; SIGNATURE_LEN],
+            items: 0,
+        }
+    }
+}
+
+impl MinHasher {
+    pub fn add_file(&mut self, rel: &str, digest: &str) {
+        let mut h = blake3::Hasher::new();
+        h.update(rel.as_bytes());
+        h.update(b"\0");
+        h.update(digest.as_bytes());
+        let bytes = h.finalize();
+        let token = u64::from_le_bytes(bytes.as_bytes()[..8].try_into().expect("8 bytes"));
+        for (i, slot) in self.mins.iter_mut().enumerate() {
+            let v =
+                (splitmix64(token ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)) >> 32) as u32;
+            if v < *slot {
+                *slot = v;
+            }
+        }
+        self.items += 1;
+    }
+
+    /// The signature, or `None` for an empty tree (which would match every other empty tree)
+    pub fn finish(self) -> Option<Vec<u32>> {
+        (self.items > 0).then(|| self.mins.to_vec())
+    }
+}
+
+fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Estimated Jaccard similarity of two signatures (fraction of equal slots)
+pub fn estimate(a: &[u32], b: &[u32]) -> f64 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let same = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    same as f64 / a.len() as f64
+}
+
+/// Similarity between two indexed projects. Uses MinHash signatures when both
+/// have one; indexes written before signatures existed fall back to exact
+/// fingerprint equality.
+pub fn similarity(a: &ProjectEntry, b: &ProjectEntry) -> Option<f64> {
+    match (&a.minhash, &b.minhash) {
+        (Some(x), Some(y)) => Some(estimate(x, y)),
+        _ => match (&a.fingerprint, &b.fingerprint) {
+            (Some(x), Some(y)) if x == y => Some(1.0),
+            _ => None,
+        },
+    }
+}
+
+/// Group project indices whose pairwise similarity reaches `threshold`
+/// (single-linkage). Only groups with two or more members are returned,
+/// each sorted by index.
+pub fn group_similar(projects: &[ProjectEntry], threshold: f64) -> Vec<Vec<usize>> {
+    let n = projects.len();
+    let mut parent: Vec<usize> = (0..n).collect();
+
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for i in 0..n {
+        for j in i + 1..n {
+            if similarity(&projects[i], &projects[j]).is_some_and(|s| s >= threshold) {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..n {
+        let r = find(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+    groups.into_values().filter(|g| g.len() >= 2).collect()
+}
+
+/// Relative path key used for tokens and reports
+pub fn rel_key(project_root: &Path, p: &Path) -> String {
+    p.strip_prefix(project_root)
+        .unwrap_or(p)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate, MinHasher};
+
+    fn signature(files: &[(String, String)]) -> Vec<u32> {
+        let mut m = MinHasher::default();
+        for (rel, digest) in files {
+            m.add_file(rel, digest);
+        }
+        m.finish().unwrap()
+    }
+
+    #[test]
+    fn minhash_tracks_jaccard() {
+        let base: Vec<(String, String)> = (0..200)
+            .map(|i| (format!("src/f{i}.rs"), format!("h{i}")))
+            .collect();
+        let mut edited = base.clone();
+        edited[0].1 = "changed".into();
+        let unrelated: Vec<(String, String)> = (0..200)
+            .map(|i| (format!("lib/g{i}.py"), format!("x{i}")))
+            .collect();
+        let (a, b, c) = (signature(&base), signature(&edited), signature(&unrelated));
+
+        assert_eq!(estimate(&a, &a), 1.0);
+        assert!(estimate(&a, &b) > 0.85, "one edit: {}", estimate(&a, &b));
+        assert!(estimate(&a, &c) < 0.1, "unrelated: {}", estimate(&a, &c));
+    }
+
+    #[test]
+    fn empty_tree_has_no_signature() {
+        assert!(MinHasher::default().finish().is_none());
+    }
+}
@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scan::rules::{self, ScanRules};
+use crate::scan::similarity::{file_digest, rel_key};
+
+/// Text files larger than this are reported as changed without an inline diff
+const MAX_DIFF_BYTES: u64 = 1024 * 1024;
+
+/// Content digest plus the stat fields shown in diff reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStat {
+    pub digest: String,
+    pub size: u64,
+    pub mtime: Option<DateTime<Local>>,
+}
+
+/// Per-file stats keyed by `/`-separated path relative to `project_root`
+pub fn file_stats(
+    project_root: &Path,
+    rules: &ScanRules,
+    exclude: &[PathBuf],
+) -> Result<BTreeMap<String, FileStat>> {
+    let mut out = BTreeMap::new();
+    for item in rules.walker(project_root, exclude).build() {
+        let Some(entry) = rules::walk_entry(item, project_root)? else {
+            continue;
+        };
+        if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+        let p = entry.path();
+        let md = entry
+            .metadata()
+            .with_context(|| format!("Failed to read metadata for {}", p.display()))?;
+        out.insert(
+            rel_key(project_root, p),
+            FileStat {
+                digest: file_digest(p, md.len())?,
+                size: md.len(),
+                mtime: md.modified().ok().map(DateTime::<Local>::from),
+            },
+        );
+    }
+    Ok(out)
+}
+
+/// A file present in both trees with different content
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedFile {
+    pub path: String,
+    pub left: FileStat,
+    pub right: FileStat,
+}
+
+/// Which files differ between two project trees
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileDiff {
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+    pub changed: Vec<ChangedFile>,
+    pub unchanged: usize,
+}
+
+impl FileDiff {
+    pub fn between(left: &BTreeMap<String, FileStat>, right: &BTreeMap<String, FileStat>) -> Self {
+        let mut d = FileDiff::default();
+        for (path, l) in left {
+            match right.get(path) {
+                None => d.only_left.push(path.clone()),
+                Some(r) if r.digest != l.digest => d.changed.push(ChangedFile {
+                    path: path.clone(),
+                    left: l.clone(),
+                    right: r.clone(),
+                }),
+                Some(_) => d.unchanged += 1,
+            }
+        }
+        d.only_right = right
+            .keys()
+            .filter(|p| !left.contains_key(*p))
+            .cloned()
+            .collect();
+        d
+    }
+
+    /// Compare two project directories
+    pub fn of_trees(
+        left: (&Path, &[PathBuf]),
+        right: (&Path, &[PathBuf]),
+        rules: &ScanRules,
+    ) -> Result<Self> {
+        Ok(Self::between(
+            &file_stats(left.0, rules, left.1)?,
+            &file_stats(right.0, rules, right.1)?,
+        ))
+    }
+
+    /// Exact Jaccard similarity over `(path, digest)` tokens
+    pub fn jaccard(&self) -> f64 {
+        let union =
+            self.unchanged + self.only_left.len() + self.only_right.len() + 2 * self.changed.len();
+        if union == 0 {
+            1.0
+        } else {
+            self.unchanged as f64 / union as f64
+        }
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Unified diff of two files labelled `left_label`/`right_label`.
+/// Returns `None` for binary (NUL bytes or invalid UTF-8) or oversized files.
+pub fn unified_diff(
+    left: &Path,
+    right: &Path,
+    left_label: &str,
+    right_label: &str,
+) -> Result<Option<String>> {
+    let (Some(a), Some(b)) = (read_text(left)?, read_text(right)?) else {
+        return Ok(None);
+    };
+    Ok(Some(
+        TextDiff::from_lines(&a, &b)
+            .unified_diff()
+            .context_radius(3)
+            .header(left_label, right_label)
+            .to_string(),
+    ))
+}
+
+fn read_text(p: &Path) -> Result<Option<String>> {
+    let len = fs::metadata(p)
+        .with_context(|| format!("Failed to read metadata for {}", p.display()))?
+        .len();
+    if len > MAX_DIFF_BYTES {
+        return Ok(None);
+    }
+    let data = fs::read(p).with_context(|| format!("Failed to read {}", p.display()))?;
+    if data.contains(&0) {
+        return Ok(None);
+    }
+    Ok(String::from_utf8(data).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unified_diff, FileDiff};
+    use crate::scan::rules::ScanRules;
+    use std::fs;
+
+    #[test]
+    fn file_diff_reports_added_removed_and_changed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (l, r) = (tmp.path().join("left"), tmp.path().join("right"));
+        for d in [&l, &r] {
+            fs::create_dir_all(d.join("src")).unwrap();
+            fs::write(d.join("src").join("lib.rs"), "pub fn a() {}\n").unwrap();
+        }
+        fs::write(l.join("README.md"), "v1\n").unwrap();
+        fs::write(r.join("README.md"), "v22\n").unwrap();
+        fs::write(l.join("old.txt"), "x").unwrap();
+        fs::write(r.join("new.txt"), "y").unwrap();
+
+        let d = FileDiff::of_trees((&l, &[]), (&r, &[]), &ScanRules::default()).unwrap();
+        assert_eq!(d.only_left, vec!["old.txt"]);
+        assert_eq!(d.only_right, vec!["new.txt"]);
+        assert_eq!(d.changed.len(), 1);
+        assert_eq!(d.changed[0].path, "README.md");
+        assert_eq!((d.changed[0].left.size, d.changed[0].right.size), (3, 4));
+        assert_eq!(d.unchanged, 1);
+        assert!((d.jaccard() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unified_diff_for_text_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (a, b) = (tmp.path().join("a.md"), tmp.path().join("b.md"));
+        fs::write(&a, "one\ntwo\nthree\n").unwrap();
+        fs::write(&b, "one\n2\nthree\n").unwrap();
+        let patch = unified_diff(&a, &b, "left/a.md", "right/a.md")
+            .unwrap()
+            .unwrap();
+        assert!(patch.starts_with("--- left/a.md\n+++ right/a.md\n"));
+        assert!(patch.contains("-two\n+2\n"));
+
+        let bin = tmp.path().join("c.bin");
+        fs::write(&bin, [0u8, 159, 146, 150]).unwrap();
+        assert!(unified_diff(&a, &bin, "a", "c").unwrap().is_none());
+    }
+}
@@ -0,0 +1,449 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::index::catalog;
+use crate::index::store::{self, OrbitIndex};
+use crate::scan::census::{self, CensusOptions};
+use crate::scan::incremental;
+use crate::scan::rules::ScanRules;
+
+pub const DEFAULT_DEBOUNCE_MS: u64 = 500;
+
+/// A burst that never pauses is still flushed after this many debounce windows
+const MAX_WAIT_WINDOWS: u32 = 10;
+
+/// Collects changed paths until no new change arrived for one window
+#[derive(Debug)]
+pub struct Debouncer {
+    window: Duration,
+    pending: BTreeSet<PathBuf>,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: BTreeSet::new(),
+            first: None,
+            last: None,
+        }
+    }
+
+    pub fn push(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path);
+        self.first.get_or_insert(now);
+        self.last = Some(now);
+    }
+
+    /// Changed paths not yet handed out
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The settled batch, once the burst has gone quiet (or waited too long)
+    pub fn take_ready(&mut self, now: Instant) -> Option<Vec<PathBuf>> {
+        let (first, last) = (self.first?, self.last?);
+        if now - last < self.window && now - first < self.window * MAX_WAIT_WINDOWS {
+            return None;
+        }
+        self.first = None;
+        self.last = None;
+        Some(std::mem::take(&mut self.pending).into_iter().collect())
+    }
+}
+
+/// What a batch of changed paths means for the index
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct WatchPlan {
+    /// Indexed projects to re-summarize
+    pub projects: BTreeSet<String>,
+    /// Projects may have appeared or disappeared: rerun discovery
+    pub rescan: bool,
+    /// An artifact changed, so the catalog must be rebuilt
+    pub artifacts: bool,
+}
+
+impl WatchPlan {
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty() && !self.rescan
+    }
+}
+
+/// Changes under `.git` that alter branch, HEAD or worktree state
+fn git_state_change(rel: &Path) -> bool {
+    let mut after_git = rel
+        .components()
+        .skip_while(|c| c.as_os_str() != ".git")
+        .skip(1);
+    matches!(
+        after_git.next().and_then(|c| c.as_os_str().to_str()),
+        Some("HEAD" | "index" | "refs")
+    )
+}
+
+/// Map changed paths to the deepest indexed project containing each one.
+/// Orbit's own `.orbit/` files and skipped directories are ignored.
+pub fn plan(
+    root: &Path,
+    idx: &store::OrbitIndex,
+    rules: &ScanRules,
+    changed: &[PathBuf],
+) -> WatchPlan {
+    let mut out = WatchPlan::default();
+    for p in changed {
+        let rel = p.strip_prefix(root).unwrap_or(p);
+        if rel.components().next() == Some(Component::Normal(".orbit".as_ref())) {
+            continue;
+        }
+        let in_git = rel.components().any(|c| c.as_os_str() == ".git");
+        if (in_git && !git_state_change(rel)) || (!in_git && rules.skips_path(rel)) {
+            continue;
+        }
+        let owner = idx
+            .projects
+            .iter()
+            .filter(|e| e.path == "." || rel.starts_with(&e.path))
+            .max_by_key(|e| if e.path == "." { 0 } else { e.path.len() });
+
+        if !in_git && rules.is_marker(rel) {
+            // A marker outside any project root may start a new project, and a
+            // deleted root marker may end one
+            let dir = rel.parent().unwrap_or(Path::new(""));
+            let is_root_marker = owner.is_some_and(|e| {
+                Path::new(&e.path) == dir || (e.path == "." && dir.as_os_str().is_empty())
+            });
+            if !is_root_marker || !p.exists() {
+                out.rescan = true;
+            }
+        }
+        let Some(owner) = owner else {
+            continue;
+        };
+        // A project directory itself vanished
+        if Path::new(&owner.path) == rel && !p.exists() {
+            out.rescan = true;
+        }
+        let in_project = if owner.path == "." {
+            rel
+        } else {
+            rel.strip_prefix(&owner.path).unwrap_or(rel)
+        };
+        if rules.artifact_category(in_project).is_some() {
+            out.artifacts = true;
+        }
+        out.projects.insert(owner.path.clone());
+    }
+    out
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct WatchReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub rescanned: bool,
+}
+
+/// Census options for a rescan: keep what the last census measured, and its
+/// `--since` window
+fn rescan_options(root: &Path, before: &OrbitIndex, depth: usize) -> CensusOptions {
+    CensusOptions {
+        depth,
+        since: incremental::load_cache(root).ok().and_then(|c| c.since),
+        loc: before.projects.iter().any(|p| p.loc.is_some()),
+        reclaim: before.projects.iter().any(|p| p.build_dirs.is_some()),
+        similarity: before.projects.iter().any(|p| p.minhash.is_some()),
+        record: false,
+        ..Default::default()
+    }
+}
+
+/// Bring the index (and, when needed, the artifact catalog) up to date with `plan`
+pub fn apply(root: &Path, plan: &WatchPlan, depth: usize) -> Result<WatchReport> {
+    if plan.rescan {
+        let before = store::load(root)?;
+        let opts = rescan_options(root, &before, depth);
+        let after = census::update_index(root.to_string_lossy().as_ref(), &opts, None)?;
+        let old: BTreeSet<&str> = before.projects.iter().map(|p| p.path.as_str()).collect();
+        let new: BTreeSet<&str> = after.projects.iter().map(|p| p.path.as_str()).collect();
+        return Ok(WatchReport {
+            added: new.difference(&old).map(|p| p.to_string()).collect(),
+            updated: plan
+                .projects
+                .iter()
+                .filter(|p| new.contains(p.as_str()))
+                .cloned()
+                .collect(),
+            removed: old.difference(&new).map(|p| p.to_string()).collect(),
+            rescanned: true,
+        });
+    }
+    let r = census::refresh_projects(root, &plan.projects)?;
+    if plan.artifacts || !r.removed.is_empty() {
+        catalog::refresh(root)?;
+    }
+    Ok(WatchReport {
+        added: vec![],
+        updated: r.updated,
+        removed: r.removed,
+        rescanned: false,
+    })
+}
+
+/// Recursive filesystem watch on a root, with debounced batches
+pub struct LiveWatcher {
+    _watcher: RecommendedWatcher,
+    rx: mpsc::Receiver<notify::Result<notify::Event>>,
+    debouncer: Debouncer,
+}
+
+impl LiveWatcher {
+    pub fn start(root: &Path, debounce: Duration) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(tx).context("Failed to create filesystem watcher")?;
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", root.display()))?;
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            debouncer: Debouncer::new(debounce),
+        })
+    }
+
+    /// Drain queued notifications without blocking; returns a batch of changed
+    /// paths once the burst has settled
+    pub fn poll(&mut self) -> Option<Vec<PathBuf>> {
+        let now = Instant::now();
+        while let Ok(ev) = self.rx.try_recv() {
+            // Watcher errors (e.g. an inotify queue overflow) are reported as
+            // events without paths; the next change picks things up again
+            let Ok(ev) = ev else {
+                continue;
+            };
+            if matches!(ev.kind, EventKind::Access(_)) {
+                continue;
+            }
+            for p in ev.paths {
+                self.debouncer.push(p, now);
+            }
+        }
+        self.debouncer.take_ready(now)
+    }
+
+    /// Changes seen but not yet applied (the index is stale while this is non-zero)
+    pub fn pending(&self) -> usize {
+        self.debouncer.pending()
+    }
+}
+
+pub fn handle_watch(
+    root_str: &str,
+    depth: usize,
+    debounce_ms: u64,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+    if store::load(root)?.projects.is_empty() && !dry_run {
+        let opts = CensusOptions {
+            depth,
+            ..Default::default()
+        };
+        census::update_index(root_str, &opts, None)?;
+    }
+    let rules = ScanRules::from_config(&config::load_config(root)?)?;
+    let mut watcher = LiveWatcher::start(root, Duration::from_millis(debounce_ms))?;
+    if !json_output {
+        println!(
+            "Watching {} (debounce {} ms); Ctrl-C to stop",
+            root.display(),
+            debounce_ms
+        );
+    }
+
+    loop {
+        std::thread::sleep(Duration::from_millis(100));
+        let Some(changed) = watcher.poll() else {
+            continue;
+        };
+        let idx = match store::load(root) {
+            Ok(idx) => idx,
+            Err(e) => {
+                report_error(&e, json_output);
+                continue;
+            }
+        };
+        let plan = plan(root, &idx, &rules, &changed);
+        if plan.is_empty() {
+            continue;
+        }
+        if dry_run {
+            let projects: Vec<&str> = plan.projects.iter().map(String::as_str).collect();
+            println!(
+                "[dry-run] would {} {}",
+                if plan.rescan { "rescan;" } else { "update" },
+                projects.join(", ")
+            );
+            continue;
+        }
+        // A failed update (e.g. a lock timeout) is retried by the next change
+        let report = match apply(root, &plan, depth) {
+            Ok(r) => r,
+            Err(e) => {
+                report_error(&e, json_output);
+                continue;
+            }
+        };
+        if json_output {
+            println!(
+                "{}",
+                serde_json::json!({ "at": Local::now(), "changed": changed.len(), "update": report })
+            );
+            continue;
+        }
+        let mut msg = format!("[{}]", Local::now().format("%H:%M:%S"));
+        if report.rescanned {
+            msg.push_str(" rescanned;");
+        }
+        if !report.added.is_empty() {
+            msg.push_str(&format!(" added {}", report.added.join(", ")));
+        }
+        if !report.updated.is_empty() {
+            msg.push_str(&format!(" updated {}", report.updated.join(", ")));
+        }
+        if !report.removed.is_empty() {
+            msg.push_str(&format!(" removed {}", report.removed.join(", ")));
+        }
+        println!("{}", msg);
+    }
+}
+
+fn report_error(e: &anyhow::Error, json_output: bool) {
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "at": Local::now(), "error": format!("{:#}", e) })
+        );
+    } else {
+        eprintln!(
+            "[{}] update failed: {:#}",
+            Local::now().format("%H:%M:%S"),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan, rescan_options, Debouncer};
+    use crate::index::store::OrbitIndex;
+    use crate::model::project::ProjectEntry;
+    use crate::scan::incremental::{self, CensusCache};
+    use crate::scan::rules::ScanRules;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn bursts_are_flushed_once_quiet_or_after_max_wait() {
+        let w = Duration::from_millis(100);
+        let t0 = Instant::now();
+        let mut d = Debouncer::new(w);
+        assert!(d.take_ready(t0).is_none());
+
+        d.push(PathBuf::from("a"), t0);
+        d.push(PathBuf::from("b"), t0 + Duration::from_millis(50));
+        d.push(PathBuf::from("a"), t0 + Duration::from_millis(90));
+        assert_eq!(d.pending(), 2);
+        assert!(d.take_ready(t0 + Duration::from_millis(150)).is_none());
+        let batch = d.take_ready(t0 + Duration::from_millis(200)).unwrap();
+        assert_eq!(batch, vec![PathBuf::from("a"), PathBuf::from("b")]);
+        assert_eq!(d.pending(), 0);
+
+        // A steady stream is still flushed after ten windows
+        let t1 = t0 + Duration::from_secs(10);
+        for i in 0..=10 {
+            d.push(PathBuf::from("c"), t1 + w * i);
+        }
+        assert!(d.take_ready(t1 + w * 9).is_none());
+        assert!(d.take_ready(t1 + w * 10).is_some());
+    }
+
+    #[test]
+    fn rescans_keep_line_counts_and_the_since_window() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let measured = ProjectEntry {
+            loc: Some(Default::default()),
+            ..ProjectEntry::stub("app")
+        };
+        let idx = OrbitIndex {
+            projects: vec![measured, ProjectEntry::stub("lib")],
+            ..Default::default()
+        };
+        let opts = rescan_options(root, &idx, 3);
+        assert!(opts.loc && !opts.reclaim && !opts.similarity && !opts.record);
+        assert_eq!((opts.depth, opts.since), (3, None));
+
+        let cache = CensusCache {
+            since: Some("2025-01-01".into()),
+            ..Default::default()
+        };
+        incremental::save_cache(root, &cache).unwrap();
+        let opts = rescan_options(root, &idx, 3);
+        assert_eq!(opts.since.as_deref(), Some("2025-01-01"));
+    }
+
+    #[test]
+    fn changes_map_to_the_deepest_project() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("app/svc/src")).unwrap();
+        fs::write(root.join("app/svc/src/lib.rs"), "").unwrap();
+        fs::write(root.join("app/HANDOFF.md"), "").unwrap();
+        let idx = OrbitIndex {
+            projects: vec![ProjectEntry::stub("app"), ProjectEntry::stub("app/svc")],
+            ..Default::default()
+        };
+        let rules = ScanRules::default();
+        let p = |rels: &[&str]| {
+            let changed: Vec<PathBuf> = rels.iter().map(|r| root.join(r)).collect();
+            plan(root, &idx, &rules, &changed)
+        };
+
+        let one = p(&["app/svc/src/lib.rs"]);
+        assert_eq!(
+            one.projects.into_iter().collect::<Vec<_>>(),
+            vec!["app/svc"]
+        );
+        assert!(!one.rescan && !one.artifacts);
+
+        let art = p(&["app/HANDOFF.md", "app/.git/HEAD"]);
+        assert_eq!(art.projects.into_iter().collect::<Vec<_>>(), vec!["app"]);
+        assert!(art.artifacts);
+
+        let ignored = p(&[
+            ".orbit/index.json",
+            "app/target/debug/out",
+            "app/.git/objects/ab/cd",
+        ]);
+        assert!(ignored.is_empty());
+
+        assert!(
+            p(&["tools/Cargo.toml"]).rescan,
+            "new project outside the index"
+        );
+        assert!(p(&["app/svc/Cargo.toml"]).rescan, "root marker deleted");
+    }
+}
@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::config;
+use crate::snapshot::inspect::{parse_id, snapshot_ids, snapshots_root};
+use crate::snapshot::{manifest, objects};
+
+/// Which snapshots `orbit snap gc` keeps
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct Retention {
+    /// The newest N snapshots
+    pub keep_last: usize,
+    /// The newest snapshot of each day within the last M days
+    pub keep_daily_days: u32,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            keep_daily_days: 30,
+        }
+    }
+}
+
+/// Ids (sorted oldest first) that survive `r`. Ids without a parsable
+/// timestamp are always kept.
+pub fn retained(ids: &[String], r: &Retention, now: DateTime<Local>) -> BTreeSet<String> {
+    let mut keep: BTreeSet<String> = ids.iter().rev().take(r.keep_last).cloned().collect();
+    let cutoff = now - Duration::days(r.keep_daily_days as i64);
+    let mut days = BTreeSet::new();
+    for id in ids.iter().rev() {
+        match parse_id(id).0 {
+            None => {
+                keep.insert(id.clone());
+            }
+            Some(t) if t >= cutoff && days.insert(t.date_naive()) => {
+                keep.insert(id.clone());
+            }
+            Some(_) => {}
+        }
+    }
+    keep
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct GcReport {
+    pub retention: Option<Retention>,
+    pub kept: Vec<String>,
+    pub removed_snapshots: Vec<String>,
+    pub removed_objects: usize,
+    /// Partial blobs left by an interrupted `orbit snap`
+    pub removed_temp_files: usize,
+    pub freed_bytes: u64,
+}
+
+fn dir_bytes(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Drop snapshots outside the retention policy, then every blob no remaining
+/// manifest references. With `dry_run` only the report is computed.
+pub fn gc(root: &Path, r: &Retention, now: DateTime<Local>, dry_run: bool) -> Result<GcReport> {
+    // A snapshot being taken has stored blobs its manifest doesn't list yet
+    let _lock = if dry_run {
+        None
+    } else {
+        Some(objects::lock(root)?)
+    };
+    let ids = snapshot_ids(root)?;
+    let keep = retained(&ids, r, now);
+    let mut report = GcReport {
+        retention: Some(*r),
+        ..Default::default()
+    };
+
+    let mut referenced = BTreeSet::new();
+    for id in &ids {
+        let dir = snapshots_root(root).join(id);
+        if keep.contains(id) {
+            if let Some(m) = manifest::load(&dir)? {
+                referenced.extend(m.artifacts.into_iter().map(|a| a.hash));
+            }
+            report.kept.push(id.clone());
+            continue;
+        }
+        report.freed_bytes += dir_bytes(&dir);
+        if !dry_run {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to delete snapshot {}", dir.display()))?;
+        }
+        report.removed_snapshots.push(id.clone());
+    }
+
+    for hash in objects::list(root)? {
+        if referenced.contains(&hash) {
+            continue;
+        }
+        report.removed_objects += 1;
+        report.freed_bytes += if dry_run {
+            fs::metadata(objects::object_path(root, &hash))
+                .map(|m| m.len())
+                .unwrap_or(0)
+        } else {
+            objects::remove(root, &hash)?
+        };
+    }
+    // Under the lock no `put` is running, so every temp file is left over
+    for p in objects::temp_files(root)? {
+        report.removed_temp_files += 1;
+        report.freed_bytes += fs::metadata(&p).map(|m| m.len()).unwrap_or(0);
+        if !dry_run {
+            fs::remove_file(&p).with_context(|| format!("Failed to delete {}", p.display()))?;
+        }
+    }
+    Ok(report)
+}
+
+pub fn handle_gc(
+    root_str: &str,
+    keep_last: Option<usize>,
+    keep_daily_days: Option<u32>,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<()> {
+    let root = Path::new(root_str);
+    let mut r = config::load_config(root)?.snapshots.retention();
+    if let Some(n) = keep_last {
+        r.keep_last = n;
+    }
+    if let Some(d) = keep_daily_days {
+        r.keep_daily_days = d;
+    }
+    let report = gc(root, &r, Local::now(), dry_run)?;
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "dry_run": dry_run, "gc": report })
+        );
+        return Ok(());
+    }
+    let prefix = if dry_run {
+        "[dry-run] would remove"
+    } else {
+        "Removed"
+    };
+    println!(
+        "{} {} snapshots and {} unreferenced objects ({} bytes); keeping {} (last {}, daily for {} days)",
+        prefix,
+        report.removed_snapshots.len(),
+        report.removed_objects,
+        report.freed_bytes,
+        report.kept.len(),
+        r.keep_last,
+        r.keep_daily_days
+    );
+    for id in &report.removed_snapshots {
+        println!("  - {}", id);
+    }
+    if report.removed_temp_files > 0 {
+        println!(
+            "  - {} leftover temp files in the object store",
+            report.removed_temp_files
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gc, retained, Retention};
+    use crate::snapshot::manifest::{self, ManifestEntry, SnapshotManifest};
+    use crate::snapshot::objects;
+    use chrono::{Local, TimeZone};
+    use std::fs;
+    use std::path::Path;
+
+    fn snap(root: &Path, id: &str, blobs: &[&[u8]]) {
+        let dir = root.join(".orbit").join("snapshots").join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.json"), "{}").unwrap();
+        let artifacts = blobs
+            .iter()
+            .enumerate()
+            .map(|(i, b)| ManifestEntry {
+                project: "app".into(),
+                path: format!("a{i}.md"),
+                category: None,
+                hash: objects::put(root, b).unwrap(),
+                size: b.len() as u64,
+                mtime: None,
+            })
+            .collect();
+        manifest::save(
+            &dir,
+            &SnapshotManifest {
+                version: 2,
+                created_at: Local::now(),
+                artifacts,
+                collisions: vec![],
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn retention_keeps_last_n_and_one_per_recent_day() {
+        let ids: Vec<String> = [
+            "2025-05-01_10-00-00_s",
+            "2025-06-01_09-00-00_s",
+            "2025-06-01_18-00-00_s",
+            "2025-06-02_09-00-00_s",
+            "2025-06-03_09-00-00_s",
+            "manual",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let now = Local.with_ymd_and_hms(2025, 6, 4, 12, 0, 0).unwrap();
+        let r = Retention {
+            keep_last: 1,
+            keep_daily_days: 7,
+        };
+        let keep: Vec<String> = retained(&ids, &r, now).into_iter().collect();
+        assert_eq!(
+            keep,
+            vec![
+                "2025-06-01_18-00-00_s",
+                "2025-06-02_09-00-00_s",
+                "2025-06-03_09-00-00_s",
+                "manual"
+            ]
+        );
+    }
+
+    #[test]
+    fn gc_prunes_snapshots_and_unreferenced_blobs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        snap(root, "2025-01-01_00-00-00_old", &[b"shared", b"only-old"]);
+        snap(root, "2025-01-02_00-00-00_new", &[b"shared"]);
+        let r = Retention {
+            keep_last: 1,
+            keep_daily_days: 0,
+        };
+
+        let dry = gc(root, &r, Local::now(), true).unwrap();
+        assert_eq!(dry.removed_snapshots, vec!["2025-01-01_00-00-00_old"]);
+        assert_eq!(dry.removed_objects, 1);
+        assert_eq!(objects::list(root).unwrap().len(), 2);
+
+        let crashed =
+            objects::object_path(root, &blake3::hash(b"shared").to_hex()).with_extension("tmp4242");
+        fs::write(&crashed, "partial").unwrap();
+        let done = gc(root, &r, Local::now(), false).unwrap();
+        assert_eq!(done.removed_temp_files, 1);
+        assert!(!crashed.exists());
+        assert_eq!(objects::list(root).unwrap().len(), 1);
+        assert!(!root
+            .join(".orbit/snapshots/2025-01-01_00-00-00_old")
+            .exists());
+        assert_eq!(
+            objects::get(root, &blake3::hash(b"shared").to_hex()).unwrap(),
+            b"shared"
+        );
+    }
+}
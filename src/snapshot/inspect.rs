@@ -0,0 +1,584 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::focus::{self, Focus};
+use crate::index::journal::{self, Target};
+use crate::index::migrate;
+use crate::index::store::{self, OrbitIndex};
+use crate::model::project::sync_pinned_flags;
+use crate::snapshot::manifest::{self, Collision};
+
+/// `<root>/.orbit/snapshots`, one directory per snapshot
+pub fn snapshots_root(root: &Path) -> PathBuf {
+    root.join(".orbit").join("snapshots")
+}
+
+/// A snapshot directory as listed by `orbit snap list`
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    /// Directory name, `<timestamp>_<label>`
+    pub id: String,
+    pub label: String,
+    pub created_at: Option<DateTime<Local>>,
+    pub projects: usize,
+    pub pinned: usize,
+    pub artifacts: usize,
+}
+
+/// Everything stored in one snapshot
+#[derive(Debug)]
+pub struct Snapshot {
+    pub info: SnapshotInfo,
+    pub dir: PathBuf,
+    pub focus: Focus,
+    pub index: OrbitIndex,
+    /// Workspace-relative artifact path → content hash
+    pub artifacts: BTreeMap<String, String>,
+    pub collisions: Vec<Collision>,
+}
+
+/// Split `2025-01-02_03-04-05_label` into its time and label
+pub fn parse_id(id: &str) -> (Option<DateTime<Local>>, String) {
+    let (ts, label) = id.split_at(id.len().min(19));
+    let created = NaiveDateTime::parse_from_str(ts, "%Y-%m-%d_%H-%M-%S")
+        .ok()
+        .and_then(|n| Local.from_local_datetime(&n).single());
+    match created {
+        Some(c) => (Some(c), label.trim_start_matches('_').to_string()),
+        None => (None, id.to_string()),
+    }
+}
+
+/// Snapshot ids, oldest first
+pub fn snapshot_ids(root: &Path) -> Result<Vec<String>> {
+    let dir = snapshots_root(root);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut ids = Vec::new();
+    for e in fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let e = e.with_context(|| format!("Failed to read entry in {}", dir.display()))?;
+        if e.path().join("index.json").is_file() {
+            ids.push(e.file_name().to_string_lossy().to_string());
+        }
+    }
+    ids.sort();
+    Ok(ids)
+}
+
+/// Resolve `latest`, an exact id, or a unique id prefix
+pub fn resolve(root: &Path, id: &str) -> Result<String> {
+    let ids = snapshot_ids(root)?;
+    if id == "latest" {
+        return ids.last().cloned().context("No snapshots yet");
+    }
+    if ids.iter().any(|x| x == id) {
+        return Ok(id.to_string());
+    }
+    let matches: Vec<&String> = ids.iter().filter(|x| x.starts_with(id)).collect();
+    match matches.as_slice() {
+        [one] => Ok(one.to_string()),
+        [] => bail!("No snapshot matches '{}' (see `orbit snap list`)", id),
+        many => bail!(
+            "Snapshot id '{}' is ambiguous: {}",
+            id,
+            many.iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(p: &Path) -> Result<T> {
+    let content =
+        fs::read_to_string(p).with_context(|| format!("Failed to read {}", p.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", p.display()))
+}
+
+/// Snapshots keep the index in the schema they were taken with
+fn read_index(p: &Path) -> Result<OrbitIndex> {
+    let m = migrate::migrate_index(read_json(p)?)
+        .with_context(|| format!("Failed to migrate {}", p.display()))?;
+    Ok(m.doc)
+}
+
+/// Hash the copies kept by snapshots taken before the object store
+fn artifact_hashes(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut out = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(out);
+    }
+    for e in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let e = e.with_context(|| format!("Failed to read entry in {}", dir.display()))?;
+        let p = e.path();
+        if p.is_file() {
+            let data = fs::read(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+            out.insert(
+                e.file_name().to_string_lossy().to_string(),
+                blake3::hash(&data).to_hex().to_string(),
+            );
+        }
+    }
+    Ok(out)
+}
+
+pub fn load_snapshot(root: &Path, id: &str) -> Result<Snapshot> {
+    let id = resolve(root, id)?;
+    let dir = snapshots_root(root).join(&id);
+    let index = read_index(&dir.join("index.json"))?;
+    let focus_path = dir.join("focus.json");
+    let focus: Focus = if focus_path.is_file() {
+        read_json(&focus_path)?
+    } else {
+        Focus::default()
+    };
+    let (artifacts, collisions) = match manifest::load(&dir)? {
+        Some(m) => (
+            m.artifacts
+                .iter()
+                .map(|a| (a.key(), a.hash.clone()))
+                .collect(),
+            m.collisions,
+        ),
+        None => (artifact_hashes(&dir.join("artifacts"))?, vec![]),
+    };
+    let (created_at, label) = parse_id(&id);
+    Ok(Snapshot {
+        info: SnapshotInfo {
+            id,
+            label,
+            created_at,
+            projects: index.projects.len(),
+            pinned: focus.pinned.len(),
+            artifacts: artifacts.len(),
+        },
+        dir,
+        focus,
+        index,
+        artifacts,
+        collisions,
+    })
+}
+
+/// A snapshot directory that couldn't be loaded; listed instead of failing the listing
+#[derive(Debug, Clone, Serialize)]
+pub struct UnreadableSnapshot {
+    pub id: String,
+    pub error: String,
+}
+
+/// Readable snapshots, oldest first, and the ones that failed to load
+pub fn list_snapshots(root: &Path) -> Result<(Vec<SnapshotInfo>, Vec<UnreadableSnapshot>)> {
+    let mut snaps = Vec::new();
+    let mut unreadable = Vec::new();
+    for id in snapshot_ids(root)? {
+        match load_snapshot(root, &id) {
+            Ok(s) => snaps.push(s.info),
+            Err(e) => unreadable.push(UnreadableSnapshot {
+                id,
+                error: format!("{e:#}"),
+            }),
+        }
+    }
+    Ok((snaps, unreadable))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reclassified {
+    pub path: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// What changed between two snapshots
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SnapDiff {
+    pub projects_added: Vec<String>,
+    pub projects_removed: Vec<String>,
+    pub reclassified: Vec<Reclassified>,
+    pub pins_added: Vec<String>,
+    pub pins_removed: Vec<String>,
+    pub artifacts_added: Vec<String>,
+    pub artifacts_removed: Vec<String>,
+    pub artifacts_changed: Vec<String>,
+}
+
+impl SnapDiff {
+    pub fn between(a: &Snapshot, b: &Snapshot) -> Self {
+        let kinds = |s: &Snapshot| -> BTreeMap<String, String> {
+            s.index
+                .projects
+                .iter()
+                .map(|p| (p.path.clone(), format!("{:?}", p.kind)))
+                .collect()
+        };
+        let (ka, kb) = (kinds(a), kinds(b));
+        let only = |x: &[String], y: &[String]| -> Vec<String> {
+            x.iter().filter(|p| !y.contains(p)).cloned().collect()
+        };
+        SnapDiff {
+            projects_added: kb
+                .keys()
+                .filter(|p| !ka.contains_key(*p))
+                .cloned()
+                .collect(),
+            projects_removed: ka
+                .keys()
+                .filter(|p| !kb.contains_key(*p))
+                .cloned()
+                .collect(),
+            reclassified: ka
+                .iter()
+                .filter_map(|(p, from)| {
+                    let to = kb.get(p)?;
+                    (to != from).then(|| Reclassified {
+                        path: p.clone(),
+                        from: from.clone(),
+                        to: to.clone(),
+                    })
+                })
+                .collect(),
+            pins_added: only(&b.focus.pinned, &a.focus.pinned),
+            pins_removed: only(&a.focus.pinned, &b.focus.pinned),
+            artifacts_added: b
+                .artifacts
+                .keys()
+                .filter(|n| !a.artifacts.contains_key(*n))
+                .cloned()
+                .collect(),
+            artifacts_removed: a
+                .artifacts
+                .keys()
+                .filter(|n| !b.artifacts.contains_key(*n))
+                .cloned()
+                .collect(),
+            artifacts_changed: a
+                .artifacts
+                .iter()
+                .filter(|(n, h)| b.artifacts.get(*n).is_some_and(|x| x != *h))
+                .map(|(n, _)| n.clone())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.projects_added.is_empty()
+            && self.projects_removed.is_empty()
+            && self.reclassified.is_empty()
+            && self.pins_added.is_empty()
+            && self.pins_removed.is_empty()
+            && self.artifacts_added.is_empty()
+            && self.artifacts_removed.is_empty()
+            && self.artifacts_changed.is_empty()
+    }
+}
+
+/// Write the snapshot's focus and index back as the current state (journaled,
+/// so `orbit undo` reverts it)
+pub fn restore(root: &Path, snap: &Snapshot) -> Result<()> {
+    let _focus_lock = focus::lock(root)?;
+    let before_focus = focus::load_focus(root).unwrap_or_default();
+    focus::save_focus(root, &snap.focus)?;
+    focus::record_change(root, "snap restore", &before_focus, &snap.focus)?;
+
+    let _index_lock = store::lock(root)?;
+    let before_index = serde_json::to_value(store::load(root)?)?;
+    let before_count = before_index["projects"].as_array().map_or(0, |a| a.len());
+    let mut idx = read_index(&snap.dir.join("index.json"))?;
+    sync_pinned_flags(&mut idx.projects, &snap.focus.pinned);
+    store::save(root, &idx)?;
+    journal::record(
+        root,
+        "snap restore",
+        Target::Index,
+        format!(
+            "{} → {} projects from {}",
+            before_count,
+            idx.projects.len(),
+            snap.info.id
+        ),
+        &before_index,
+        &idx,
+    )?;
+    Ok(())
+}
+
+fn time_label(t: Option<DateTime<Local>>) -> String {
+    t.map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "N/A".into())
+}
+
+pub fn handle_list(root_str: &str, json_output: bool) -> Result<()> {
+    let (snaps, unreadable) = list_snapshots(Path::new(root_str))?;
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "snapshots": snaps, "unreadable": unreadable })
+        );
+        return Ok(());
+    }
+    if snaps.is_empty() && unreadable.is_empty() {
+        println!("No snapshots yet (create one with `orbit snap`).");
+    }
+    for s in &snaps {
+        println!(
+            "{}  {}  {} projects  {} pinned  {} artifacts",
+            s.id,
+            time_label(s.created_at),
+            s.projects,
+            s.pinned,
+            s.artifacts
+        );
+    }
+    for u in &unreadable {
+        println!("{}  unreadable: {}", u.id, u.error);
+    }
+    Ok(())
+}
+
+pub fn handle_show(root_str: &str, id: &str, json_output: bool) -> Result<()> {
+    let s = load_snapshot(Path::new(root_str), id)?;
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "snapshot": s.info,
+                "pinned": s.focus.pinned,
+                "projects": s.index.projects.iter().map(|p| &p.path).collect::<Vec<_>>(),
+                "artifacts": s.artifacts,
+                "collisions": s.collisions,
+            })
+        );
+        return Ok(());
+    }
+    println!("Snapshot {}", s.info.id);
+    println!("  created: {}", time_label(s.info.created_at));
+    println!("  label:   {}", s.info.label);
+    println!(
+        "  index:   {} projects (census {})",
+        s.info.projects,
+        time_label(s.index.generated_at)
+    );
+    println!("Pinned:");
+    for p in &s.focus.pinned {
+        println!("  ★ {}", p);
+    }
+    println!("Artifacts:");
+    for (name, hash) in &s.artifacts {
+        println!("  {}  {}", hash.get(..12).unwrap_or(hash), name);
+    }
+    if !s.collisions.is_empty() {
+        println!("Collisions:");
+        for c in &s.collisions {
+            println!(
+                "  {:?}: {} (from {})",
+                c.kind,
+                c.paths.join(", "),
+                c.projects.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_diff(root_str: &str, a: &str, b: &str, json_output: bool) -> Result<()> {
+    let root = Path::new(root_str);
+    let (sa, sb) = (load_snapshot(root, a)?, load_snapshot(root, b)?);
+    let d = SnapDiff::between(&sa, &sb);
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "from": sa.info.id, "to": sb.info.id, "diff": d })
+        );
+        return Ok(());
+    }
+    println!("{} → {}", sa.info.id, sb.info.id);
+    if d.is_empty() {
+        println!("  no differences");
+        return Ok(());
+    }
+    let section = |title: &str, sign: &str, items: &[String]| {
+        for i in items {
+            println!("  {} {:<10} {}", sign, title, i);
+        }
+    };
+    section("project", "+", &d.projects_added);
+    section("project", "-", &d.projects_removed);
+    for r in &d.reclassified {
+        println!("  ~ {:<10} {} ({} → {})", "kind", r.path, r.from, r.to);
+    }
+    section("pin", "+", &d.pins_added);
+    section("pin", "-", &d.pins_removed);
+    section("artifact", "+", &d.artifacts_added);
+    section("artifact", "-", &d.artifacts_removed);
+    section("artifact", "~", &d.artifacts_changed);
+    Ok(())
+}
+
+pub fn handle_restore(root_str: &str, id: &str, dry_run: bool, json_output: bool) -> Result<()> {
+    let root = Path::new(root_str);
+    let s = load_snapshot(root, id)?;
+    if dry_run {
+        if json_output {
+            println!(
+                "{}",
+                serde_json::json!({ "dry_run": true, "restore": s.info })
+            );
+        } else {
+            println!(
+                "[dry-run] would restore focus ({} pinned) and index ({} projects) from {}",
+                s.info.pinned, s.info.projects, s.info.id
+            );
+        }
+        return Ok(());
+    }
+    restore(root, &s)?;
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "status": "restored", "snapshot": s.info })
+        );
+    } else {
+        println!(
+            "Restored focus ({} pinned) and index ({} projects) from {}. Revert with `orbit undo` (twice).",
+            s.info.pinned, s.info.projects, s.info.id
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_snapshots, load_snapshot, parse_id, resolve, SnapDiff};
+    use std::fs;
+    use std::path::Path;
+
+    fn write_snap(
+        root: &Path,
+        id: &str,
+        projects: &[(&str, &str)],
+        pins: &[&str],
+        arts: &[(&str, &str)],
+    ) {
+        let dir = root.join(".orbit").join("snapshots").join(id);
+        fs::create_dir_all(dir.join("artifacts")).unwrap();
+        let projects: Vec<_> = projects
+            .iter()
+            .map(|(p, kind)| {
+                serde_json::json!({
+                    "path": p, "kind": kind, "latest_mtime": null, "size_bytes": 0,
+                    "artifact_count": 0, "has_git": false, "fingerprint": null
+                })
+            })
+            .collect();
+        let idx = serde_json::json!({ "version": "0.5", "root": ".", "generated_at": null, "projects": projects });
+        fs::write(dir.join("index.json"), idx.to_string()).unwrap();
+        fs::write(
+            dir.join("focus.json"),
+            serde_json::json!({ "pinned": pins }).to_string(),
+        )
+        .unwrap();
+        for (name, body) in arts {
+            fs::write(dir.join("artifacts").join(name), body).unwrap();
+        }
+    }
+
+    #[test]
+    fn ids_parse_and_resolve_by_prefix() {
+        let (t, label) = parse_id("2025-03-04_05-06-07_weekly");
+        assert_eq!(label, "weekly");
+        assert_eq!(t.unwrap().format("%H:%M").to_string(), "05:06");
+
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write_snap(root, "2025-03-04_05-06-07_a", &[], &[], &[]);
+        write_snap(root, "2025-03-05_05-06-07_b", &[], &[], &[]);
+        assert_eq!(
+            resolve(root, "2025-03-05").unwrap(),
+            "2025-03-05_05-06-07_b"
+        );
+        assert_eq!(resolve(root, "latest").unwrap(), "2025-03-05_05-06-07_b");
+        assert!(resolve(root, "2025-03").is_err());
+        assert!(resolve(root, "nope").is_err());
+    }
+
+    #[test]
+    fn listing_skips_and_reports_unreadable_snapshots() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write_snap(
+            root,
+            "2025-03-04_05-06-07_good",
+            &[("app", "standalone")],
+            &[],
+            &[],
+        );
+        write_snap(root, "2025-03-05_05-06-07_bad", &[], &[], &[]);
+        fs::write(
+            root.join(".orbit/snapshots/2025-03-05_05-06-07_bad/index.json"),
+            "{ truncated",
+        )
+        .unwrap();
+
+        let (snaps, unreadable) = list_snapshots(root).unwrap();
+        assert_eq!(snaps.len(), 1);
+        assert_eq!(snaps[0].label, "good");
+        assert_eq!(unreadable.len(), 1);
+        assert_eq!(unreadable[0].id, "2025-03-05_05-06-07_bad");
+        assert!(
+            unreadable[0].error.contains("Failed to parse"),
+            "{}",
+            unreadable[0].error
+        );
+    }
+
+    #[test]
+    fn diff_reports_projects_kinds_pins_and_artifacts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write_snap(
+            root,
+            "2025-01-01_00-00-00_a",
+            &[("app", "active_standalone"), ("old", "standalone")],
+            &["app"],
+            &[("app__HANDOFF.md", "v1"), ("app__plan.md", "same")],
+        );
+        write_snap(
+            root,
+            "2025-01-02_00-00-00_b",
+            &[("app", "standalone"), ("new", "experimental")],
+            &["new"],
+            &[
+                ("app__HANDOFF.md", "v2"),
+                ("app__plan.md", "same"),
+                ("new__AGENT.md", "x"),
+            ],
+        );
+        let a = load_snapshot(root, "2025-01-01").unwrap();
+        let b = load_snapshot(root, "2025-01-02").unwrap();
+        let d = SnapDiff::between(&a, &b);
+        assert_eq!(d.projects_added, vec!["new"]);
+        assert_eq!(d.projects_removed, vec!["old"]);
+        assert_eq!(d.reclassified.len(), 1);
+        assert_eq!(
+            (
+                d.reclassified[0].from.as_str(),
+                d.reclassified[0].to.as_str()
+            ),
+            ("ActiveStandalone", "Standalone")
+        );
+        assert_eq!(
+            (d.pins_added.clone(), d.pins_removed.clone()),
+            (vec!["new".to_string()], vec!["app".to_string()])
+        );
+        assert_eq!(d.artifacts_added, vec!["new__AGENT.md"]);
+        assert_eq!(d.artifacts_changed, vec!["app__HANDOFF.md"]);
+        assert!(d.artifacts_removed.is_empty());
+        assert!(SnapDiff::between(&a, &a).is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::index::store::atomic_write;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Current manifest layout: artifacts keyed by project and relative path
+pub const MANIFEST_VERSION: u32 = 2;
+
+/// Per-snapshot list of artifacts; contents live in `.orbit/objects`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub created_at: DateTime<Local>,
+    pub artifacts: Vec<ManifestEntry>,
+    /// Artifacts that could not be stored under a distinct path
+    #[serde(default)]
+    pub collisions: Vec<Collision>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ManifestEntry {
+    /// Pinned project the artifact was found in (empty in version 1 manifests)
+    #[serde(default)]
+    pub project: String,
+    /// Path relative to `project`, `/`-separated (the flattened name in version 1)
+    #[serde(alias = "name")]
+    pub path: String,
+    /// Artifact rule category (absent in manifests written before categories)
+    #[serde(default)]
+    pub category: Option<String>,
+    /// blake3 of the content, i.e. the object address
+    pub hash: String,
+    pub size: u64,
+    #[serde(default)]
+    pub mtime: Option<DateTime<Local>>,
+}
+
+impl ManifestEntry {
+    /// Workspace-relative path, unique within a manifest
+    pub fn key(&self) -> String {
+        match self.project.as_str() {
+            "" | "." => self.path.clone(),
+            p => format!("{}/{}", p, self.path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionKind {
+    /// The same file reached through overlapping pins; the first copy is kept
+    SamePath,
+    /// Paths differing only in letter case, which clash on case-insensitive filesystems
+    CaseOnly,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Collision {
+    pub kind: CollisionKind,
+    /// Colliding workspace-relative paths
+    pub paths: Vec<String>,
+    /// Projects the colliding entries came from
+    pub projects: Vec<String>,
+    pub same_content: bool,
+}
+
+/// Drop entries whose workspace-relative path was already recorded and
+/// report them, plus paths that only differ in case (both are kept).
+pub fn resolve_collisions(entries: &mut Vec<ManifestEntry>) -> Vec<Collision> {
+    let mut out = Vec::new();
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    let mut kept: Vec<ManifestEntry> = Vec::new();
+    for e in entries.drain(..) {
+        let key = e.key();
+        if let Some(&i) = seen.get(&key) {
+            let first = &kept[i];
+            out.push(Collision {
+                kind: CollisionKind::SamePath,
+                paths: vec![key],
+                projects: vec![first.project.clone(), e.project.clone()],
+                same_content: first.hash == e.hash,
+            });
+            continue;
+        }
+        seen.insert(key, kept.len());
+        kept.push(e);
+    }
+
+    let mut by_lower: BTreeMap<String, Vec<&ManifestEntry>> = BTreeMap::new();
+    for e in &kept {
+        by_lower.entry(e.key().to_lowercase()).or_default().push(e);
+    }
+    for group in by_lower.into_values().filter(|g| g.len() > 1) {
+        out.push(Collision {
+            kind: CollisionKind::CaseOnly,
+            paths: group.iter().map(|e| e.key()).collect(),
+            projects: group.iter().map(|e| e.project.clone()).collect(),
+            same_content: group.iter().all(|e| e.hash == group[0].hash),
+        });
+    }
+
+    *entries = kept;
+    out
+}
+
+/// `None` for snapshots taken before manifests existed (they keep an `artifacts/` copy)
+pub fn load(snap_dir: &Path) -> Result<Option<SnapshotManifest>> {
+    let p = snap_dir.join(MANIFEST_FILE);
+    if !p.is_file() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&p).with_context(|| format!("Failed to read {}", p.display()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse {}", p.display()))
+}
+
+pub fn save(snap_dir: &Path, m: &SnapshotManifest) -> Result<()> {
+    let content = serde_json::to_string_pretty(m).context("Failed to serialize manifest")?;
+    atomic_write(&snap_dir.join(MANIFEST_FILE), &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_collisions, CollisionKind, ManifestEntry, SnapshotManifest};
+
+    fn entry(project: &str, path: &str, hash: &str) -> ManifestEntry {
+        ManifestEntry {
+            project: project.into(),
+            path: path.into(),
+            category: None,
+            hash: hash.into(),
+            size: 1,
+            mtime: None,
+        }
+    }
+
+    #[test]
+    fn overlapping_pins_and_case_clashes_are_reported() {
+        let mut entries = vec![
+            entry("app", "docs/HANDOFF.md", "h1"),
+            entry("app", "HANDOFF.md", "h2"),
+            entry("app/docs", "HANDOFF.md", "h1"),
+            entry("app", "notes/plan.md", "p1"),
+            entry("app", "notes/PLAN.md", "p2"),
+        ];
+        let c = resolve_collisions(&mut entries);
+        let keys: Vec<String> = entries.iter().map(|e| e.key()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "app/docs/HANDOFF.md",
+                "app/HANDOFF.md",
+                "app/notes/plan.md",
+                "app/notes/PLAN.md"
+            ]
+        );
+        assert_eq!(c.len(), 2);
+        assert_eq!(c[0].kind, CollisionKind::SamePath);
+        assert_eq!(c[0].projects, vec!["app", "app/docs"]);
+        assert!(c[0].same_content);
+        assert_eq!(c[1].kind, CollisionKind::CaseOnly);
+        assert!(!c[1].same_content);
+    }
+
+    #[test]
+    fn version_1_manifests_still_parse() {
+        let v1 = r#"{"version":1,"created_at":"2025-01-01T00:00:00+00:00",
+            "artifacts":[{"name":"app__HANDOFF.md","hash":"abc","size":3}]}"#;
+        let m: SnapshotManifest = serde_json::from_str(v1).unwrap();
+        assert_eq!(m.artifacts[0].key(), "app__HANDOFF.md");
+        assert!(m.collisions.is_empty());
+    }
+}
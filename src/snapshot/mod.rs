@@ -1 +1,5 @@
+pub mod gc;
+pub mod inspect;
+pub mod manifest;
+pub mod objects;
 pub mod quick;
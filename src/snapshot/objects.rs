@@ -0,0 +1,137 @@
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::index::lock::{self, FileLock};
+
+/// `<root>/.orbit/objects`: blake3-addressed blobs shared by all snapshots
+pub fn objects_root(root: &Path) -> PathBuf {
+    root.join(".orbit").join("objects")
+}
+
+/// `objects/ab/cdef…` (two-character fan-out keeps directories small)
+pub fn object_path(root: &Path, hash: &str) -> PathBuf {
+    let (dir, rest) = hash.split_at(2.min(hash.len()));
+    objects_root(root).join(dir).join(rest)
+}
+
+/// Store `data` once under its blake3 hash; returns the hash
+pub fn put(root: &Path, data: &[u8]) -> Result<String> {
+    let hash = blake3::hash(data).to_hex().to_string();
+    let p = object_path(root, &hash);
+    if p.is_file() {
+        return Ok(hash);
+    }
+    let parent = p.parent().expect("object path has a parent");
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    // Unique temp name so concurrent writers of the same blob don't clash
+    let tmp = p.with_extension(format!("tmp{}", std::process::id()));
+    let mut f =
+        fs::File::create(&tmp).with_context(|| format!("Failed to create {}", tmp.display()))?;
+    f.write_all(data)
+        .and_then(|_| f.sync_all())
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, &p)
+        .with_context(|| format!("Failed to rename {} to {}", tmp.display(), p.display()))?;
+    Ok(hash)
+}
+
+/// Read a blob and verify it still matches its address
+pub fn get(root: &Path, hash: &str) -> Result<Vec<u8>> {
+    let p = object_path(root, hash);
+    let data = fs::read(&p).with_context(|| format!("Failed to read object {}", p.display()))?;
+    if blake3::hash(&data).to_hex().as_str() != hash {
+        bail!("Object {} is corrupt (hash mismatch)", p.display());
+    }
+    Ok(data)
+}
+
+/// Lock the object store: held by `orbit snap` from the first blob until its
+/// manifest references them, and by `orbit snap gc`
+pub fn lock(root: &Path) -> Result<FileLock> {
+    lock::acquire(&objects_root(root))
+}
+
+/// `(fan-out prefix, file name)` of every file in the object store
+fn entries(root: &Path) -> Result<Vec<(String, String)>> {
+    let mut out = Vec::new();
+    let dir = objects_root(root);
+    if !dir.is_dir() {
+        return Ok(out);
+    }
+    for fan in fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let fan = fan.with_context(|| format!("Failed to read entry in {}", dir.display()))?;
+        if !fan.path().is_dir() {
+            continue;
+        }
+        let prefix = fan.file_name().to_string_lossy().to_string();
+        for e in fs::read_dir(fan.path())
+            .with_context(|| format!("Failed to read {}", fan.path().display()))?
+        {
+            let e = e.with_context(|| format!("Failed to read entry in {}", dir.display()))?;
+            out.push((prefix.clone(), e.file_name().to_string_lossy().to_string()));
+        }
+    }
+    Ok(out)
+}
+
+/// Every stored blob hash
+pub fn list(root: &Path) -> Result<BTreeSet<String>> {
+    Ok(entries(root)?
+        .into_iter()
+        .filter(|(_, name)| !name.contains('.'))
+        .map(|(prefix, name)| format!("{prefix}{name}"))
+        .collect())
+}
+
+/// Temp files [`put`] left behind when it was interrupted before the rename
+pub fn temp_files(root: &Path) -> Result<Vec<PathBuf>> {
+    Ok(entries(root)?
+        .into_iter()
+        .filter(|(_, name)| name.contains(".tmp"))
+        .map(|(prefix, name)| objects_root(root).join(prefix).join(name))
+        .collect())
+}
+
+/// Delete a blob; returns the bytes freed
+pub fn remove(root: &Path, hash: &str) -> Result<u64> {
+    let p = object_path(root, hash);
+    let len = fs::metadata(&p)
+        .with_context(|| format!("Failed to read metadata for {}", p.display()))?
+        .len();
+    fs::remove_file(&p).with_context(|| format!("Failed to delete {}", p.display()))?;
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, list, object_path, put, remove, temp_files};
+    use std::fs;
+
+    #[test]
+    fn blobs_are_stored_once_and_verified() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let a = put(root, b"handoff v1").unwrap();
+        assert_eq!(put(root, b"handoff v1").unwrap(), a);
+        let b = put(root, b"handoff v2").unwrap();
+        assert_eq!(list(root).unwrap().len(), 2);
+        assert_eq!(get(root, &a).unwrap(), b"handoff v1");
+
+        fs::write(object_path(root, &b), "tampered").unwrap();
+        assert!(get(root, &b).is_err());
+        assert_eq!(remove(root, &a).unwrap(), 10);
+        assert_eq!(
+            list(root).unwrap().into_iter().collect::<Vec<_>>(),
+            vec![b.clone()]
+        );
+
+        let crashed = object_path(root, &b).with_extension("tmp4242");
+        fs::write(&crashed, "partial").unwrap();
+        assert_eq!(list(root).unwrap().len(), 1);
+        assert_eq!(temp_files(root).unwrap(), vec![crashed]);
+    }
+}
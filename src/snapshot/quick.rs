@@ -1,16 +1,22 @@
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::{DateTime, Local};
 use std::fs;
 use std::path::Path;
 
+use crate::config;
 use crate::export::md::render_md;
 use crate::index::{focus::load_focus, store, whitelist};
+use crate::scan::rules::ScanRules;
+use crate::scan::similarity::rel_key;
+use crate::snapshot::manifest::{self, ManifestEntry, SnapshotManifest, MANIFEST_VERSION};
+use crate::snapshot::objects;
 
 pub fn snapshot_pinned(root_str: &str, label: Option<&str>, dry_run: bool) -> Result<()> {
     let root = Path::new(root_str);
     let focus = load_focus(root).unwrap_or_default();
-    let idx = store::load(root).unwrap_or_default();
+    let idx = store::load(root)?;
     let wl = whitelist::load_whitelist(root).unwrap_or_default();
+    let rules = ScanRules::from_config(&config::load_config(root)?)?;
 
     let ts = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
     let lab = label.unwrap_or("snapshot");
@@ -23,11 +29,17 @@ pub fn snapshot_pinned(root_str: &str, label: Option<&str>, dry_run: bool) -> Re
         println!("[dry-run] would create snapshot at {}", snap_dir.display());
         println!("  focus -> {}/focus.json", snap_dir.display());
         println!("  index -> {}/index.json", snap_dir.display());
-        println!("  artifacts dir -> {}/artifacts/", snap_dir.display());
+        println!(
+            "  artifacts -> {} (manifest {}/manifest.json)",
+            objects::objects_root(root).display(),
+            snap_dir.display()
+        );
         println!("  summary -> {}/summary.md", snap_dir.display());
         return Ok(());
     }
 
+    // Keep `snap gc` from collecting blobs before the manifest lists them
+    let _objects_lock = objects::lock(root)?;
     fs::create_dir_all(&snap_dir)
         .with_context(|| format!("Failed to create snapshot directory {}", snap_dir.display()))?;
 
@@ -45,16 +57,10 @@ pub fn snapshot_pinned(root_str: &str, label: Option<&str>, dry_run: bool) -> Re
     )
     .with_context(|| format!("Failed to write {}", index_path.display()))?;
 
-    let artifacts_dir = snap_dir.join("artifacts");
-    fs::create_dir_all(&artifacts_dir).with_context(|| {
-        format!(
-            "Failed to create artifacts directory {}",
-            artifacts_dir.display()
-        )
-    })?;
+    let mut artifacts = Vec::new();
     for p in focus.pinned.iter() {
         let pr = root.join(p);
-        if whitelist::is_protected(&pr, &wl) {
+        if whitelist::is_protected(root, &pr, &wl) {
             println!(
                 "[whitelist] skipping pinned project (protected): {}",
                 pr.display()
@@ -62,9 +68,32 @@ pub fn snapshot_pinned(root_str: &str, label: Option<&str>, dry_run: bool) -> Re
             continue;
         }
         if pr.is_dir() {
-            copy_md_artifacts(&pr, &artifacts_dir, p)?;
+            store_artifacts(root, &pr, p, &rules, &mut artifacts)?;
         }
     }
+    let collisions = manifest::resolve_collisions(&mut artifacts);
+    for c in &collisions {
+        println!(
+            "[collision] {:?}: {} (from {}){}",
+            c.kind,
+            c.paths.join(", "),
+            c.projects.join(", "),
+            if c.same_content {
+                ", identical content"
+            } else {
+                ""
+            }
+        );
+    }
+    manifest::save(
+        &snap_dir,
+        &SnapshotManifest {
+            version: MANIFEST_VERSION,
+            created_at: Local::now(),
+            artifacts,
+            collisions,
+        },
+    )?;
 
     let summary_path = snap_dir.join("summary.md");
     fs::write(&summary_path, render_md(&idx)?)
@@ -79,46 +108,54 @@ fn sanitize(s: &str) -> String {
         .collect()
 }
 
-fn copy_md_artifacts(project_root: &Path, out_dir: &Path, rel: &str) -> Result<()> {
+/// Store files matched by the artifact rules in the object store (once per
+/// content), recording each one's path relative to its pinned project
+fn store_artifacts(
+    root: &Path,
+    project_root: &Path,
+    rel: &str,
+    rules: &ScanRules,
+    out: &mut Vec<ManifestEntry>,
+) -> Result<()> {
     for entry in walkdir::WalkDir::new(project_root)
         .max_depth(6)
         .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !rules.should_skip(e))
     {
         let entry =
             entry.with_context(|| format!("Failed to read entry in {}", project_root.display()))?;
-        if entry.file_type().is_file() {
-            let p = entry.path();
-            if p.extension()
-                .and_then(|s| s.to_str())
-                .map(|e| e.eq_ignore_ascii_case("md"))
-                .unwrap_or(false)
-            {
-                let name = p.file_name().unwrap().to_string_lossy().to_string();
-                let lname = name.to_lowercase();
-                if lname.contains("handoff")
-                    || lname.contains("agent")
-                    || lname.contains("claude")
-                    || lname.contains("export")
-                    || lname.contains("plan")
-                    || lname.contains("session")
-                    || lname.contains("summary")
-                    || lname.contains("roadmap")
-                    || lname.contains("prompt")
-                {
-                    let target = out_dir.join(format!("{}__{}", sanitize(rel), name));
-                    fs::copy(p, &target).with_context(|| {
-                        format!("Failed to copy {} to {}", p.display(), target.display())
-                    })?;
-                }
-            }
+        if !entry.file_type().is_file() {
+            continue;
         }
+        let p = entry.path();
+        let key = rel_key(project_root, p);
+        let Some(category) = rules.artifact_category(Path::new(&key)) else {
+            continue;
+        };
+        let data = fs::read(p).with_context(|| format!("Failed to read {}", p.display()))?;
+        let mtime = entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .map(DateTime::<Local>::from);
+        out.push(ManifestEntry {
+            project: rel.to_string(),
+            path: key,
+            category: Some(category.to_string()),
+            hash: objects::put(root, &data)?,
+            size: data.len() as u64,
+            mtime,
+        });
     }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::sanitize;
+    use super::{sanitize, store_artifacts};
+    use crate::scan::rules::ScanRules;
+    use std::fs;
 
     #[test]
     fn sanitize_replaces_non_alnum() {
@@ -126,4 +163,32 @@ mod tests {
         assert_eq!(sanitize("abc123"), "abc123");
         assert_eq!(sanitize("a/b\\c"), "a_b_c");
     }
+
+    #[test]
+    fn same_named_artifacts_keep_their_relative_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let proj = root.join("app");
+        fs::create_dir_all(proj.join("docs")).unwrap();
+        fs::write(proj.join("HANDOFF.md"), "top").unwrap();
+        fs::write(proj.join("docs").join("HANDOFF.md"), "nested").unwrap();
+        fs::write(proj.join("decision-log.txt"), "census counts this too").unwrap();
+        fs::write(proj.join("README.md"), "not an artifact").unwrap();
+
+        let mut out = Vec::new();
+        store_artifacts(root, &proj, "app", &ScanRules::default(), &mut out).unwrap();
+        out.sort_by_key(|e| e.key());
+        let keys: Vec<String> = out.iter().map(|e| e.key()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "app/HANDOFF.md",
+                "app/decision-log.txt",
+                "app/docs/HANDOFF.md"
+            ]
+        );
+        assert_ne!(out[0].hash, out[2].hash);
+        assert_eq!(out[1].category.as_deref(), Some("plan"));
+        assert!(out.iter().all(|e| e.mtime.is_some()));
+    }
 }
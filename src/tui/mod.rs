@@ -11,7 +11,7 @@ use std::path::PathBuf;
 mod state;
 mod ui;
 
-pub fn run(root_str: &str, dry_run: bool) -> Result<()> {
+pub fn run(root_str: &str, dry_run: bool, live: bool, depth: usize) -> Result<()> {
     let root = PathBuf::from(root_str);
 
     enable_raw_mode()?;
@@ -19,9 +19,13 @@ pub fn run(root_str: &str, dry_run: bool) -> Result<()> {
     let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    let mut st = state::State::new(root, dry_run)?;
+    let mut st = state::State::new(root, dry_run, depth)?;
+    if live {
+        st.toggle_live()?;
+    }
 
     loop {
+        st.tick()?;
         terminal.draw(|f| ui::draw(f, &mut st))?;
 
         if event::poll(std::time::Duration::from_millis(200))? {
@@ -40,6 +44,18 @@ pub fn run(root_str: &str, dry_run: bool) -> Result<()> {
                     continue;
                 }
 
+                if st.diff_view.is_some() {
+                    match k.code {
+                        KeyCode::Esc | KeyCode::Char('q') => st.close_diff(),
+                        KeyCode::Up => st.up(),
+                        KeyCode::Down => st.down(),
+                        KeyCode::PageUp => st.scroll_diff(-10),
+                        KeyCode::PageDown => st.scroll_diff(10),
+                        _ => {}
+                    }
+                    continue;
+                }
+
                 match k.code {
                     KeyCode::Char('q') => {
                         let _ = st.save_session();
@@ -55,6 +71,12 @@ pub fn run(root_str: &str, dry_run: bool) -> Result<()> {
                     KeyCode::Char('f') => st.toggle_pin_selected()?,
                     KeyCode::Char('s') => st.snapshot()?,
                     KeyCode::Char('e') => st.export()?,
+                    KeyCode::Char('m') => st.mark_diff_left(),
+                    KeyCode::Char('d') => st.open_diff(),
+                    KeyCode::Char('a') => st.archive_selected()?,
+                    KeyCode::Char('u') => st.undo()?,
+                    KeyCode::Char('w') => st.toggle_live()?,
+                    KeyCode::Char('r') => st.next_root()?,
                     _ => {}
                 }
             }
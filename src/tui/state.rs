@@ -1,11 +1,35 @@
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
 
+use crate::cleanup::archive;
+use crate::config;
 use crate::feature;
-use crate::index::{focus, session, store};
+use crate::index::catalog::{self, ArtifactCatalog, ArtifactRecord};
+use crate::index::history::{self, HistoryRun, ProjectTrend};
+use crate::index::timeline::{self, TimelineEntry};
+use crate::index::{focus, journal, session, store, whitelist};
 use crate::model::project::{sync_pinned_flags, ProjectEntry, ProjectKind};
+use crate::scan::census::CensusOptions;
+use crate::scan::delta::{CensusDelta, DEFAULT_MIN_SIZE_CHANGE};
 use crate::scan::progress::Progress;
+use crate::scan::rules::ScanRules;
+use crate::scan::similarity;
+use crate::scan::treediff::{self, ChangedFile, FileDiff};
+use crate::scan::watch::{self, LiveWatcher};
+
+/// Census runs loaded for sparklines, and how many of the latest are drawn
+const TREND_DAYS: i64 = 90;
+const TREND_RUNS: usize = 40;
+
+fn load_history(root: &std::path::Path) -> Vec<HistoryRun> {
+    let since = chrono::Local::now() - chrono::Duration::days(TREND_DAYS);
+    history::load(root, Some(since)).map(|(runs, _)| runs).unwrap_or_default()
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum Checkbox {
@@ -13,18 +37,77 @@ pub enum Checkbox {
     Focus,
     Backups,
     Artifacts,
+    Uncommitted,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Panel {
     Home,
     Projects,
+    Artifacts,
     Duplicates,
+    Reclaimable,
+}
+
+/// Near-duplicate projects; `members[0]` (the newest) is the reference copy
+#[derive(Debug, Clone)]
+pub struct DupeGroup {
+    pub members: Vec<ProjectEntry>,
+    /// Similarity of each member to the reference (`None` if not comparable)
+    pub scores: Vec<Option<f64>>,
+}
+
+impl DupeGroup {
+    /// Lowest member-to-reference similarity in the group
+    pub fn min_score(&self) -> Option<f64> {
+        self.scores[1..].iter().flatten().copied().reduce(f64::min)
+    }
+}
+
+/// One row of the diff view's file list
+pub enum DiffEntry<'a> {
+    Changed(&'a ChangedFile),
+    OnlyLeft(&'a str),
+    OnlyRight(&'a str),
 }
 
+/// Side-by-side comparison of two duplicate copies (opened with `d`)
+pub struct DiffView {
+    /// Project paths being compared
+    pub left: String,
+    pub right: String,
+    pub diff: FileDiff,
+    pub selected: usize,
+    pub scroll: u16,
+    // unified diff of the selected changed file, keyed by `selected`
+    patch: Option<(usize, Result<Option<String>, String>)>,
+}
+
+impl DiffView {
+    /// Changed files first, then files only in the left, then only in the right copy
+    pub fn entries(&self) -> Vec<DiffEntry<'_>> {
+        self.diff
+            .changed
+            .iter()
+            .map(DiffEntry::Changed)
+            .chain(self.diff.only_left.iter().map(|p| DiffEntry::OnlyLeft(p)))
+            .chain(self.diff.only_right.iter().map(|p| DiffEntry::OnlyRight(p)))
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.diff.changed.len() + self.diff.only_left.len() + self.diff.only_right.len()
+    }
+}
+
+/// File differences behind a duplicate group, or why they couldn't be computed
+type DupeReport = Result<FileDiff, String>;
+
 pub struct State {
     pub root: PathBuf,
     pub dry_run: bool,
+    /// Census depth for refreshes and live rescans (`orbit tui --depth`)
+    pub depth: usize,
     pub panel: Panel,
     pub high_contrast: bool,
 
@@ -33,30 +116,60 @@ pub struct State {
     pub checkbox_cursor: usize,
 
     pub index: store::OrbitIndex,
+    /// Every root in the shared index (`r` cycles through them)
+    pub roots: Vec<store::RootSummary>,
     pub focus: focus::Focus,
+    pub similarity_threshold: f64,
+    rules: ScanRules,
+    pub catalog: ArtifactCatalog,
+    /// Recent census runs for the sparklines in the Projects detail pane
+    history: Vec<HistoryRun>,
 
     pub selected_project: usize,
     pub selected_dupe_group: usize,
     pub selected_dupe_item: usize,
+    pub selected_reclaim: usize,
+    pub selected_artifact: usize,
+    /// Duplicate copy marked with `m` as the left side of the next diff
+    pub diff_left: Option<String>,
+    pub diff_view: Option<DiffView>,
+    /// Duplicate awaiting a second `a` press before it is archived
+    pub pending_archive: Option<String>,
 
     // search
     pub search_mode: bool,
     pub search_buf: String,
     pub search_query: String,
+    /// Fuzzy filter of the Artifacts panel (kept apart from the project search)
+    pub artifact_query: String,
 
     // progress (for census)
     pub progress_log: Vec<String>,
 
+    /// Filesystem watcher while live mode is on (`w`)
+    pub live: Option<LiveWatcher>,
+
     // cached duplicate groups (invalidated on index change)
-    cached_dupe_groups: Option<Vec<(String, Vec<ProjectEntry>)>>,
+    cached_dupe_groups: Option<Vec<DupeGroup>>,
+    // file differences for the selected (group, item), computed on demand
+    cached_dupe_report: Option<((usize, usize), DupeReport)>,
+    // comparison running in the background for the selected (group, item)
+    dupe_report_job: Option<((usize, usize), mpsc::Receiver<DupeReport>)>,
     // cached filtered projects (invalidated on index/filter/search change)
     cached_filtered_projects: Option<Vec<ProjectEntry>>,
+    // first lines of the selected artifact, keyed by its workspace path
+    cached_artifact_preview: Option<(String, String)>,
 }
 
 impl State {
-    pub fn new(root: PathBuf, dry_run: bool) -> Result<Self> {
-        let index = store::load(&root).unwrap_or_default();
+    pub fn new(root: PathBuf, dry_run: bool, depth: usize) -> Result<Self> {
+        let index = store::load(&root)?;
+        let roots = store::load_shared()?.summaries();
         let focus = focus::load_focus(&root).unwrap_or_default();
+        let cfg = config::load_config(&root).unwrap_or_default();
+        let rules = ScanRules::from_config(&cfg).unwrap_or_default();
+        let catalog = catalog::load(&root).ok().flatten().unwrap_or_default();
+        let history = load_history(&root);
 
         let mut checked = HashSet::new();
         checked.insert(Checkbox::Active);
@@ -66,6 +179,7 @@ impl State {
         let mut state = Self {
             root,
             dry_run,
+            depth,
             panel: Panel::Home,
             high_contrast: false,
             checkboxes: vec![
@@ -73,20 +187,36 @@ impl State {
                 Checkbox::Focus,
                 Checkbox::Backups,
                 Checkbox::Artifacts,
+                Checkbox::Uncommitted,
             ],
             checked,
             checkbox_cursor: 0,
             index,
+            roots,
             focus,
+            similarity_threshold: cfg.similarity.threshold(),
+            rules,
+            catalog,
+            history,
             selected_project: 0,
             selected_dupe_group: 0,
             selected_dupe_item: 0,
+            selected_reclaim: 0,
+            selected_artifact: 0,
+            diff_left: None,
+            diff_view: None,
+            pending_archive: None,
             search_mode: false,
             search_buf: String::new(),
             search_query: String::new(),
+            artifact_query: String::new(),
             progress_log: Vec::new(),
+            live: None,
             cached_dupe_groups: None,
+            cached_dupe_report: None,
+            dupe_report_job: None,
             cached_filtered_projects: None,
+            cached_artifact_preview: None,
         };
 
         // Apply session if present
@@ -94,7 +224,9 @@ impl State {
             if let Some(l) = sess.lens.as_deref() {
                 state.panel = match l {
                     "projects" => Panel::Projects,
+                    "artifacts" => Panel::Artifacts,
                     "duplicates" => Panel::Duplicates,
+                    "reclaimable" => Panel::Reclaimable,
                     _ => Panel::Home,
                 };
             }
@@ -123,8 +255,10 @@ impl State {
     pub fn next_panel(&mut self) {
         self.panel = match self.panel {
             Panel::Home => Panel::Projects,
-            Panel::Projects => Panel::Duplicates,
-            Panel::Duplicates => Panel::Home,
+            Panel::Projects => Panel::Artifacts,
+            Panel::Artifacts => Panel::Duplicates,
+            Panel::Duplicates => Panel::Reclaimable,
+            Panel::Reclaimable => Panel::Home,
         };
     }
     pub fn prev_panel(&mut self) {
@@ -132,6 +266,13 @@ impl State {
     }
 
     pub fn up(&mut self) {
+        if let Some(v) = &mut self.diff_view {
+            if v.selected > 0 {
+                v.selected -= 1;
+                v.scroll = 0;
+            }
+            return;
+        }
         match self.panel {
             Panel::Home => {
                 if self.checkbox_cursor > 0 {
@@ -151,10 +292,23 @@ impl State {
                     self.selected_dupe_item = 0;
                 }
             }
+            Panel::Artifacts => {
+                self.selected_artifact = self.selected_artifact.saturating_sub(1);
+            }
+            Panel::Reclaimable => {
+                self.selected_reclaim = self.selected_reclaim.saturating_sub(1);
+            }
         }
     }
 
     pub fn down(&mut self) {
+        if let Some(v) = &mut self.diff_view {
+            if v.selected + 1 < v.len() {
+                v.selected += 1;
+                v.scroll = 0;
+            }
+            return;
+        }
         match self.panel {
             Panel::Home => {
                 if self.checkbox_cursor + 1 < self.checkboxes.len() {
@@ -179,7 +333,7 @@ impl State {
                         return;
                     }
                     let g = cur_group.min(groups.len() - 1);
-                    (groups.len(), groups[g].1.len())
+                    (groups.len(), groups[g].members.len())
                 };
                 if cur_item + 1 < item_count {
                     self.selected_dupe_item += 1;
@@ -188,6 +342,16 @@ impl State {
                     self.selected_dupe_item = 0;
                 }
             }
+            Panel::Artifacts => {
+                if self.selected_artifact + 1 < self.artifacts_filtered().len() {
+                    self.selected_artifact += 1;
+                }
+            }
+            Panel::Reclaimable => {
+                if self.selected_reclaim + 1 < self.reclaimable_projects().len() {
+                    self.selected_reclaim += 1;
+                }
+            }
         }
     }
 
@@ -207,27 +371,135 @@ impl State {
     pub fn primary_action(&mut self) -> Result<()> {
         // Home ENTER: refresh census
         let progress = Progress::new(feature::flags().progress);
-        crate::scan::census::run_census(
+        // Keep line counts, build-dir measurements and signatures once the index has them
+        let opts = CensusOptions {
+            depth: self.depth,
+            loc: self.index.projects.iter().any(|p| p.loc.is_some()),
+            reclaim: self.reclaim_measured(),
+            similarity: self.index.projects.iter().any(|p| p.minhash.is_some()),
+            ..Default::default()
+        };
+        let before = std::mem::take(&mut self.index);
+        let before_cat = std::mem::take(&mut self.catalog);
+        let had_catalog = catalog::catalog_path(&self.root).is_file();
+        crate::scan::census::update_index(
             self.root.to_string_lossy().as_ref(),
-            4,
-            None,
-            false,
+            &opts,
             Some(progress.clone()),
         )?;
         self.progress_log = progress.drain();
+        self.reload_index()?;
+        let delta = CensusDelta::between(
+            &before,
+            &self.index,
+            had_catalog.then_some(&before_cat),
+            &self.catalog,
+            DEFAULT_MIN_SIZE_CHANGE,
+        );
+        self.progress_log
+            .push(format!("census delta: {}", delta.summary()));
+        // re-sync pinned flags from focus (single source of truth)
+        store::save(&self.root, &self.index)?;
+        Ok(())
+    }
+
+    /// Reload index, focus and catalog from disk after they were rewritten
+    fn reload_index(&mut self) -> Result<()> {
         self.index = store::load(&self.root)?;
+        self.roots = store::load_shared()?.summaries();
         self.focus = focus::load_focus(&self.root)?;
-        // re-sync pinned flags from focus (single source of truth)
+        self.catalog = catalog::load(&self.root)?.unwrap_or_default();
+        self.cached_artifact_preview = None;
+        self.history = load_history(&self.root);
         sync_pinned_flags(&mut self.index.projects, &self.focus.pinned);
-        store::save(&self.root, &self.index)?;
         // Invalidate cache after index change
         self.invalidate_cache();
         Ok(())
     }
 
+    /// Switch to the next root in the shared index, reloading everything for it
+    pub fn next_root(&mut self) -> Result<()> {
+        let current = store::root_key(&self.root);
+        let next = match self.roots.iter().position(|r| r.root == current) {
+            Some(i) => self.roots[(i + 1) % self.roots.len()].root.clone(),
+            None => match self.roots.first() {
+                Some(r) => r.root.clone(),
+                None => return Ok(()),
+            },
+        };
+        if next == current {
+            self.progress_log.push("no other indexed roots".into());
+            return Ok(());
+        }
+        let _ = self.save_session();
+        let was_live = self.live.is_some();
+        let panel = self.panel;
+        *self = State::new(PathBuf::from(&next), self.dry_run, self.depth)?;
+        self.panel = panel;
+        if was_live {
+            self.toggle_live()?;
+        }
+        self.progress_log.push(format!("switched to root {}", next));
+        Ok(())
+    }
+
+    /// Toggle live mode: watch the root and apply changes as they settle
+    pub fn toggle_live(&mut self) -> Result<()> {
+        if self.live.take().is_some() {
+            self.progress_log.push("live mode off".into());
+            return Ok(());
+        }
+        if self.dry_run || feature::flags().dry_run {
+            self.progress_log
+                .push("[dry-run] live mode would rewrite the index as files change".into());
+            return Ok(());
+        }
+        let debounce = std::time::Duration::from_millis(watch::DEFAULT_DEBOUNCE_MS);
+        match LiveWatcher::start(&self.root, debounce) {
+            Ok(w) => {
+                self.live = Some(w);
+                self.progress_log.push("live mode on".into());
+            }
+            Err(e) => self.progress_log.push(format!("live mode failed: {:#}", e)),
+        }
+        Ok(())
+    }
+
+    /// Apply a settled batch of file changes in live mode (called every loop)
+    pub fn tick(&mut self) -> Result<()> {
+        let Some(changed) = self.live.as_mut().and_then(|w| w.poll()) else {
+            return Ok(());
+        };
+        let plan = watch::plan(&self.root, &self.index, &self.rules, &changed);
+        if plan.is_empty() {
+            return Ok(());
+        }
+        match watch::apply(&self.root, &plan, self.depth) {
+            Ok(r) => {
+                let mut msg = format!(
+                    "live: {} added, {} updated, {} removed",
+                    r.added.len(),
+                    r.updated.len(),
+                    r.removed.len()
+                );
+                if r.rescanned {
+                    msg.push_str(" (rescanned)");
+                }
+                self.progress_log.push(msg);
+                self.reload_index()?;
+            }
+            Err(e) => self
+                .progress_log
+                .push(format!("live update failed: {:#}", e)),
+        }
+        Ok(())
+    }
+
     /// Invalidate all cached computations (call after index changes)
     fn invalidate_cache(&mut self) {
         self.cached_dupe_groups = None;
+        self.cached_dupe_report = None;
+        self.dupe_report_job = None;
         self.cached_filtered_projects = None;
     }
 
@@ -237,7 +509,7 @@ impl State {
     }
 
     pub fn start_search(&mut self) {
-        if self.panel != Panel::Projects {
+        if !matches!(self.panel, Panel::Projects | Panel::Artifacts) {
             return;
         }
         self.search_mode = true;
@@ -249,6 +521,11 @@ impl State {
     }
     pub fn apply_search(&mut self) {
         self.search_mode = false;
+        if self.panel == Panel::Artifacts {
+            self.artifact_query = self.search_buf.clone();
+            self.selected_artifact = 0;
+            return;
+        }
         self.search_query = self.search_buf.clone();
         self.invalidate_filter_cache();
     }
@@ -257,7 +534,9 @@ impl State {
         let lens = match self.panel {
             Panel::Home => "home",
             Panel::Projects => "projects",
+            Panel::Artifacts => "artifacts",
             Panel::Duplicates => "duplicates",
+            Panel::Reclaimable => "reclaimable",
         };
         let selection = if let Panel::Projects = self.panel {
             let projects = self.compute_filtered_projects();
@@ -304,15 +583,24 @@ impl State {
             ps[idx.min(ps.len() - 1)].path.clone()
         };
 
-        if self.focus.pinned.iter().any(|p| p == &sel) {
+        // Re-read under the locks so pins made by other processes are kept
+        let _focus_lock = focus::lock(&self.root)?;
+        self.focus = focus::load_focus(&self.root)?;
+        let before = self.focus.clone();
+        let op = if self.focus.pinned.iter().any(|p| p == &sel) {
             self.focus.pinned.retain(|p| p != &sel);
+            "focus remove"
         } else {
             self.focus.pinned.push(sel);
             self.focus.pinned.sort();
-        }
+            "focus add"
+        };
         focus::save_focus(&self.root, &self.focus)?;
+        focus::record_change(&self.root, op, &before, &self.focus)?;
 
         // Sync pinned flags from focus (single source of truth)
+        let _index_lock = store::lock(&self.root)?;
+        self.index = store::load(&self.root)?;
         sync_pinned_flags(&mut self.index.projects, &self.focus.pinned);
         store::save(&self.root, &self.index)?;
         // Invalidate filter cache since pinned status changed
@@ -363,7 +651,10 @@ impl State {
             {
                 return true;
             }
-            if self.checked.contains(&Checkbox::Artifacts) && p.artifact_count > 0 {
+            if self.checked.contains(&Checkbox::Artifacts) && p.artifact_count() > 0 {
+                return true;
+            }
+            if self.checked.contains(&Checkbox::Uncommitted) && p.has_uncommitted_work() {
                 return true;
             }
             false
@@ -372,7 +663,9 @@ impl State {
         // live search filter
         if !self.search_query.trim().is_empty() {
             let q = self.search_query.to_lowercase();
-            ps.retain(|p| p.path.to_lowercase().contains(&q));
+            ps.retain(|p| {
+                p.path.to_lowercase().contains(&q) || p.ecosystems.iter().any(|e| e == &q)
+            });
         }
 
         ps.sort_by_key(|p| p.latest_mtime);
@@ -380,31 +673,376 @@ impl State {
         ps
     }
 
-    /// Returns groups: (fingerprint, [projects])
+    /// Size and activity of `project` over the most recent census runs
+    pub fn trend(&self, project: &str) -> Option<ProjectTrend> {
+        let recent = &self.history[self.history.len().saturating_sub(TREND_RUNS)..];
+        history::project_trend(recent, project).filter(|t| t.sizes.len() > 1)
+    }
+
+    /// Chronological artifacts of `project` from the catalog
+    pub fn timeline(&self, project: &str) -> Vec<TimelineEntry> {
+        timeline::for_project(&self.catalog, project)
+    }
+
+    /// Artifact filter in effect: the search being typed, else the applied one
+    pub fn artifact_filter(&self) -> &str {
+        if self.search_mode && self.panel == Panel::Artifacts {
+            &self.search_buf
+        } else {
+            &self.artifact_query
+        }
+    }
+
+    /// Catalogued artifacts fuzzy-matched against project, path, category and
+    /// title (best match first); newest first when there is no filter
+    pub fn artifacts_filtered(&self) -> Vec<&ArtifactRecord> {
+        let q = self.artifact_filter().trim();
+        if q.is_empty() {
+            let mut all: Vec<&ArtifactRecord> = self.catalog.artifacts.iter().collect();
+            all.sort_by_key(|a| std::cmp::Reverse(a.mtime));
+            return all;
+        }
+        let matcher = SkimMatcherV2::default();
+        let mut scored: Vec<(i64, &ArtifactRecord)> = self
+            .catalog
+            .artifacts
+            .iter()
+            .filter_map(|a| {
+                let hay = format!("{} {} {}", a.key(), a.category, a.label());
+                matcher.fuzzy_match(&hay, q).map(|s| (s, a))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.mtime.cmp(&a.1.mtime)));
+        scored.into_iter().map(|(_, a)| a).collect()
+    }
+
+    /// First lines of the selected artifact (read on demand, cached per selection)
+    pub fn artifact_preview(&mut self) -> Option<&str> {
+        let key = {
+            let list = self.artifacts_filtered();
+            if list.is_empty() {
+                return None;
+            }
+            list[self.selected_artifact.min(list.len() - 1)].key()
+        };
+        if self.cached_artifact_preview.as_ref().map(|(k, _)| k) != Some(&key) {
+            let text = std::fs::read(self.root.join(&key))
+                .map(|b| {
+                    String::from_utf8_lossy(&b)
+                        .lines()
+                        .take(40)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_else(|e| format!("(cannot read: {e})"));
+            self.cached_artifact_preview = Some((key, text));
+        }
+        self.cached_artifact_preview
+            .as_ref()
+            .map(|(_, t)| t.as_str())
+    }
+
+    /// Projects with measured build outputs, largest first, then least recently touched
+    pub fn reclaimable_projects(&self) -> Vec<&ProjectEntry> {
+        let mut ps: Vec<&ProjectEntry> = self
+            .index
+            .projects
+            .iter()
+            .filter(|p| p.reclaimable_bytes() > 0)
+            .collect();
+        ps.sort_by(|a, b| {
+            b.reclaimable_bytes()
+                .cmp(&a.reclaimable_bytes())
+                .then(a.latest_mtime.cmp(&b.latest_mtime))
+        });
+        ps
+    }
+
+    /// Whether any project was measured by `census --reclaim`
+    pub fn reclaim_measured(&self) -> bool {
+        self.index.projects.iter().any(|p| p.build_dirs.is_some())
+    }
+
+    /// Returns near-duplicate groups (similarity >= configured threshold)
     /// Results are cached and invalidated on index change
-    pub fn duplicate_groups(&mut self) -> &[(String, Vec<ProjectEntry>)] {
+    pub fn duplicate_groups(&mut self) -> &[DupeGroup] {
         if self.cached_dupe_groups.is_none() {
             self.cached_dupe_groups = Some(self.compute_duplicate_groups());
         }
         self.cached_dupe_groups.as_ref().unwrap()
     }
 
-    fn compute_duplicate_groups(&self) -> Vec<(String, Vec<ProjectEntry>)> {
-        let mut map: HashMap<String, Vec<ProjectEntry>> = HashMap::new();
-        for p in &self.index.projects {
-            if let Some(fp) = &p.fingerprint {
-                map.entry(fp.clone()).or_default().push(p.clone());
-            }
-        }
-        let mut groups: Vec<(String, Vec<ProjectEntry>)> =
-            map.into_iter().filter(|(_, v)| v.len() >= 2).collect();
+    fn compute_duplicate_groups(&self) -> Vec<DupeGroup> {
+        let ps = &self.index.projects;
+        let mut groups: Vec<DupeGroup> = similarity::group_similar(ps, self.similarity_threshold)
+            .into_iter()
+            .map(|idxs| {
+                let mut members: Vec<ProjectEntry> =
+                    idxs.into_iter().map(|i| ps[i].clone()).collect();
+                members.sort_by_key(|p| p.latest_mtime);
+                members.reverse();
+                let scores = members
+                    .iter()
+                    .map(|m| similarity::similarity(&members[0], m))
+                    .collect();
+                DupeGroup { members, scores }
+            })
+            .collect();
         // sort groups by most recent member
-        groups.sort_by_key(|(_, v)| v.iter().filter_map(|p| p.latest_mtime).max());
+        groups.sort_by_key(|g| g.members[0].latest_mtime);
         groups.reverse();
-        for (_, v) in groups.iter_mut() {
-            v.sort_by_key(|p| p.latest_mtime);
-            v.reverse();
-        }
         groups
     }
+
+    /// Files that differ between the selected duplicate and its group's
+    /// reference copy (or the next copy when the reference is selected).
+    /// Both trees are hashed on a background thread; `None` until it is done.
+    pub fn dupe_report(&mut self) -> Option<&Result<FileDiff, String>> {
+        let key = (self.selected_dupe_group, self.selected_dupe_item);
+        if let Some((k, rx)) = &self.dupe_report_job {
+            let done = match rx.try_recv() {
+                Ok(report) => Some(report),
+                Err(mpsc::TryRecvError::Empty) => None,
+                Err(mpsc::TryRecvError::Disconnected) => Some(Err("comparison stopped".into())),
+            };
+            if let Some(report) = done {
+                self.cached_dupe_report = Some((*k, report));
+                self.dupe_report_job = None;
+            }
+        }
+        let stale = self
+            .cached_dupe_report
+            .as_ref()
+            .map(|(k, _)| *k != key)
+            .unwrap_or(true);
+        let running = self
+            .dupe_report_job
+            .as_ref()
+            .is_some_and(|(k, _)| *k == key);
+        if stale && !running {
+            let (left, right) = {
+                let groups = self.duplicate_groups();
+                if groups.is_empty() {
+                    return None;
+                }
+                let g = &groups[key.0.min(groups.len() - 1)];
+                let item = key.1.min(g.members.len() - 1);
+                let other = if item == 0 { 1 } else { 0 };
+                (g.members[other].clone(), g.members[item].clone())
+            };
+            let left = (self.root.join(&left.path), self.child_roots(&left));
+            let right = (self.root.join(&right.path), self.child_roots(&right));
+            let rules = self.rules.clone();
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let report = FileDiff::of_trees((&left.0, &left.1), (&right.0, &right.1), &rules)
+                    .map_err(|e| format!("{:#}", e));
+                // The receiver is gone if the selection moved on meanwhile
+                let _ = tx.send(report);
+            });
+            self.dupe_report_job = Some((key, rx));
+        }
+        if stale {
+            return None;
+        }
+        self.cached_dupe_report.as_ref().map(|(_, r)| r)
+    }
+
+    fn diff_projects(&self, left: &ProjectEntry, right: &ProjectEntry) -> Result<FileDiff> {
+        let left_root = self.root.join(&left.path);
+        let right_root = self.root.join(&right.path);
+        FileDiff::of_trees(
+            (&left_root, &self.child_roots(left)),
+            (&right_root, &self.child_roots(right)),
+            &self.rules,
+        )
+    }
+
+    /// Nested projects are indexed separately; leave them out like census does
+    fn child_roots(&self, p: &ProjectEntry) -> Vec<PathBuf> {
+        self.index
+            .projects
+            .iter()
+            .filter(|c| c.parent.as_deref() == Some(p.path.as_str()))
+            .map(|c| self.root.join(&c.path))
+            .collect()
+    }
+
+    /// Selected duplicate group member, if any
+    fn selected_dupe(&mut self) -> Option<(DupeGroup, usize)> {
+        let (gi, ii) = (self.selected_dupe_group, self.selected_dupe_item);
+        let groups = self.duplicate_groups();
+        if groups.is_empty() {
+            return None;
+        }
+        let g = groups[gi.min(groups.len() - 1)].clone();
+        let item = ii.min(g.members.len() - 1);
+        Some((g, item))
+    }
+
+    /// Mark (or unmark) the selected duplicate as the left side of the diff view
+    pub fn mark_diff_left(&mut self) {
+        if self.panel != Panel::Duplicates {
+            return;
+        }
+        if let Some((g, item)) = self.selected_dupe() {
+            let path = g.members[item].path.clone();
+            self.diff_left = if self.diff_left.as_deref() == Some(path.as_str()) {
+                None
+            } else {
+                Some(path)
+            };
+        }
+    }
+
+    /// Open the diff view: marked copy (or the group's reference) vs the selected copy
+    pub fn open_diff(&mut self) {
+        if self.panel != Panel::Duplicates {
+            return;
+        }
+        let Some((g, item)) = self.selected_dupe() else {
+            return;
+        };
+        let right = g.members[item].clone();
+        let reference = &g.members[if item == 0 { 1 } else { 0 }];
+        let left = self
+            .diff_left
+            .as_ref()
+            .and_then(|m| {
+                g.members
+                    .iter()
+                    .find(|p| &p.path == m && p.path != right.path)
+            })
+            .unwrap_or(reference)
+            .clone();
+        // The Duplicates pane may already have compared this pair
+        let key = (self.selected_dupe_group, self.selected_dupe_item);
+        let cached = match &self.cached_dupe_report {
+            Some((k, Ok(d))) if *k == key && left.path == reference.path => Some(d.clone()),
+            _ => None,
+        };
+        match cached.map_or_else(|| self.diff_projects(&left, &right), Ok) {
+            Ok(diff) => {
+                self.diff_view = Some(DiffView {
+                    left: left.path,
+                    right: right.path,
+                    diff,
+                    selected: 0,
+                    scroll: 0,
+                    patch: None,
+                })
+            }
+            Err(e) => self.progress_log.push(format!("diff failed: {:#}", e)),
+        }
+    }
+
+    /// Archive the selected duplicate. The first press arms the action, the
+    /// second (on the same copy) runs it; pinned/whitelisted copies are refused.
+    pub fn archive_selected(&mut self) -> Result<()> {
+        if self.panel != Panel::Duplicates {
+            return Ok(());
+        }
+        let Some((g, item)) = self.selected_dupe() else {
+            return Ok(());
+        };
+        let path = g.members[item].path.clone();
+        if self.pending_archive.as_deref() != Some(path.as_str()) {
+            self.progress_log
+                .push(format!("press `a` again to archive {}", path));
+            self.pending_archive = Some(path);
+            return Ok(());
+        }
+        self.pending_archive = None;
+
+        let wl = whitelist::load_whitelist(&self.root)?;
+        let plan = archive::plan(
+            &self.root,
+            &self.index,
+            &self.focus,
+            &wl,
+            &archive::Selection::Paths(vec![path.clone()]),
+        );
+        if let Some(reason) = plan.refused().next().and_then(|i| i.refused.clone()) {
+            self.progress_log
+                .push(format!("archive refused for {}: {}", path, reason));
+            return Ok(());
+        }
+        if self.dry_run || feature::flags().dry_run {
+            self.progress_log.push(format!(
+                "[dry-run] would archive {} ({} bytes) into {}",
+                path,
+                plan.total_bytes(),
+                plan.dest.display()
+            ));
+            return Ok(());
+        }
+
+        let manifest = archive::execute(&self.root, &self.index, &plan)?;
+        let _lock = store::lock(&self.root)?;
+        self.index = store::load(&self.root)?;
+        archive::remove_from_index(&mut self.index, &manifest);
+        store::save(&self.root, &self.index)?;
+        archive::record_change(&self.root, &manifest, true)?;
+        self.invalidate_cache();
+        self.selected_dupe_item = 0;
+        self.progress_log.push(format!(
+            "archived {} (restore: orbit archive --restore {})",
+            path, manifest.id
+        ));
+        Ok(())
+    }
+
+    /// Revert the most recent journaled change and reload state from disk
+    pub fn undo(&mut self) -> Result<()> {
+        if self.dry_run || feature::flags().dry_run {
+            let entries = journal::load_journal(&self.root)?;
+            let msg = match journal::next_undo(&entries) {
+                Some(e) => format!("[dry-run] would undo #{} {} ({})", e.id, e.op, e.summary),
+                None => "nothing to undo".into(),
+            };
+            self.progress_log.push(msg);
+            return Ok(());
+        }
+        match journal::undo(&self.root, false) {
+            Ok(e) => {
+                self.index = store::load(&self.root)?;
+                self.focus = focus::load_focus(&self.root)?;
+                sync_pinned_flags(&mut self.index.projects, &self.focus.pinned);
+                self.invalidate_cache();
+                self.selected_dupe_item = 0;
+                self.progress_log.push(format!("undone: {}", e.summary));
+            }
+            Err(err) => self.progress_log.push(format!("undo failed: {err}")),
+        }
+        Ok(())
+    }
+
+    pub fn close_diff(&mut self) {
+        self.diff_view = None;
+    }
+
+    pub fn scroll_diff(&mut self, delta: i32) {
+        if let Some(v) = &mut self.diff_view {
+            v.scroll = (v.scroll as i32 + delta).clamp(0, u16::MAX as i32) as u16;
+        }
+    }
+
+    /// Unified diff for the selected changed file: `Ok(None)` for binary or
+    /// oversized files; `None` when the selection isn't a changed file
+    pub fn diff_patch(&mut self) -> Option<&Result<Option<String>, String>> {
+        let root = self.root.clone();
+        let v = self.diff_view.as_mut()?;
+        let file = v.diff.changed.get(v.selected)?;
+        if v.patch.as_ref().map(|(i, _)| *i) != Some(v.selected) {
+            let patch = treediff::unified_diff(
+                &root.join(&v.left).join(&file.path),
+                &root.join(&v.right).join(&file.path),
+                &format!("{}/{}", v.left, file.path),
+                &format!("{}/{}", v.right, file.path),
+            )
+            .map_err(|e| format!("{:#}", e));
+            v.patch = Some((v.selected, patch));
+        }
+        v.patch.as_ref().map(|(_, p)| p)
+    }
 }
@@ -1,6 +1,9 @@
 use ratatui::{prelude::*, widgets::*};
 
-use super::state::{Checkbox, Panel, State};
+use super::state::{Checkbox, DiffEntry, DupeGroup, Panel, State};
+use crate::index::history::{self, ProjectTrend};
+use crate::index::timeline::{DateSource, TimelineEntry};
+use crate::model::project::{size_label, GitInfo, ProjectEntry};
 
 fn cb_label(cb: Checkbox) -> &'static str {
     match cb {
@@ -8,9 +11,33 @@ fn cb_label(cb: Checkbox) -> &'static str {
         Checkbox::Focus => "Focus (pinned)",
         Checkbox::Backups => "Backups / duplicates",
         Checkbox::Artifacts => "Artifacts (.md)",
+        Checkbox::Uncommitted => "Uncommitted work (dirty, untracked, stashed, unpushed)",
     }
 }
 
+/// Compact git column: `main*?$ ↑2↓1` (dirty, untracked, stashed, ahead/behind)
+fn git_label(g: &GitInfo) -> String {
+    let mut s = g
+        .branch
+        .clone()
+        .or_else(|| g.head.as_ref().map(|h| h[..7.min(h.len())].to_string()))
+        .unwrap_or_else(|| "?".into());
+    if g.dirty {
+        s.push('*');
+    }
+    if g.untracked > 0 {
+        s.push('?');
+    }
+    if g.stashes > 0 {
+        s.push('$');
+    }
+    match (g.ahead, g.behind) {
+        (Some(a), Some(b)) if a > 0 || b > 0 => s.push_str(&format!(" ↑{}↓{}", a, b)),
+        _ => {}
+    }
+    s
+}
+
 fn draw_status(f: &mut Frame, st: &mut State, area: Rect) {
     let mut status = Vec::new();
     if let Some(last) = st.progress_log.last() {
@@ -18,7 +45,10 @@ fn draw_status(f: &mut Frame, st: &mut State, area: Rect) {
     } else {
         status.push("Progress: idle".into());
     }
-    status.push(format!("Dry-run: {}", if st.dry_run { "on" } else { "off" }));
+    status.push(format!(
+        "Dry-run: {}",
+        if st.dry_run { "on" } else { "off" }
+    ));
 
     let b = Block::default().borders(Borders::ALL);
     let p = Paragraph::new(status.join("   ")).block(b);
@@ -31,22 +61,30 @@ pub fn draw(f: &mut Frame, st: &mut State) {
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(9), // header
-                Constraint::Min(0),    // main
-                Constraint::Length(2), // footer/status
+                Constraint::Length(10), // header
+                Constraint::Min(0),     // main
+                Constraint::Length(2),  // footer/status
             ]
             .as_ref(),
         )
         .split(area);
 
     let header = Block::default()
-        .title("🪐 Orbit — SPACE toggle checkbox, / search, f pin, ENTER refresh census, s snapshot, e export, TAB panels, q quit")
+        .title("🪐 Orbit — SPACE toggle checkbox, / search, f pin, ENTER refresh census, s snapshot, e export, u undo, w live, r root, TAB panels, q quit")
         .borders(Borders::ALL);
     let hi = header.inner(layout[0]);
     f.render_widget(header, layout[0]);
 
     let mut lines: Vec<String> = vec![];
-    lines.push(format!("Root: {}", st.root.display()));
+    if st.roots.len() > 1 {
+        lines.push(format!(
+            "Root: {}   ({} indexed roots, r to switch)",
+            st.root.display(),
+            st.roots.len()
+        ));
+    } else {
+        lines.push(format!("Root: {}", st.root.display()));
+    }
     lines.push(format!(
         "Indexed: {}",
         st.index
@@ -59,6 +97,8 @@ pub fn draw(f: &mut Frame, st: &mut State) {
         st.panel,
         if st.search_mode {
             format!("/{}", st.search_buf)
+        } else if st.panel == Panel::Artifacts {
+            st.artifact_query.clone()
         } else {
             st.search_query.clone()
         }
@@ -66,6 +106,12 @@ pub fn draw(f: &mut Frame, st: &mut State) {
     if st.dry_run {
         lines.push("Mode: DRY-RUN (no writes)".into());
     }
+    if let Some(w) = &st.live {
+        lines.push(match w.pending() {
+            0 => "Live: watching for changes".into(),
+            n => format!("Live: STALE — {} changed paths pending", n),
+        });
+    }
     if !st.progress_log.is_empty() {
         lines.push("Recent progress:".into());
         for line in st.progress_log.iter().rev().take(3).rev() {
@@ -88,15 +134,19 @@ pub fn draw(f: &mut Frame, st: &mut State) {
     match st.panel {
         Panel::Home => draw_home(f, st, layout[1]),
         Panel::Projects => draw_projects(f, st, layout[1]),
+        Panel::Artifacts => draw_artifacts(f, st, layout[1]),
+        Panel::Duplicates if st.diff_view.is_some() => draw_diff(f, st, layout[1]),
         Panel::Duplicates => draw_dupes(f, st, layout[1]),
+        Panel::Reclaimable => draw_reclaim(f, st, layout[1]),
     }
 
     draw_status(f, st, layout[2]);
 }
 
 fn draw_home(f: &mut Frame, st: &mut State, area: Rect) {
-    let mut text = String::from(
-        "ENTER: run Census (depth=4) and refresh index.\nTAB: Projects and Duplicates panels.\n\nTip: Pin your current work with `f` (Projects panel).\n",
+    let mut text = format!(
+        "ENTER: run Census (depth={}) and refresh index.\nw: live mode (update the index as files change).\nTAB: Projects and Duplicates panels.\n\nTip: Pin your current work with `f` (Projects panel).\n",
+        st.depth
     );
     if st.dry_run {
         text.push_str("\nDRY-RUN is ON (no writes).\n");
@@ -113,9 +163,15 @@ fn draw_home(f: &mut Frame, st: &mut State, area: Rect) {
 }
 
 fn draw_projects(f: &mut Frame, st: &mut State, area: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(62), Constraint::Percentage(38)].as_ref())
+        .split(area);
+
     let b = Block::default()
         .title("Projects (filtered) — ↑/↓ select, f pin, / search")
         .borders(Borders::ALL);
+    let selected_project = st.selected_project;
     let ps = st.projects_filtered();
     let items: Vec<ListItem> = ps
         .iter()
@@ -129,24 +185,145 @@ fn draw_projects(f: &mut Frame, st: &mut State, area: Rect) {
                 .size_bytes
                 .map(|n| n.to_string())
                 .unwrap_or_else(|| "?".into());
+            let eco = if p.ecosystems.is_empty() {
+                "-".to_string()
+            } else {
+                p.ecosystems.join(",")
+            };
+            let git = p.git.as_ref().map(git_label).unwrap_or_else(|| "-".into());
             let line = format!(
-                "{} {:<46} {:?}  latest:{}  size:{}  artifacts:{}",
-                star, p.path, p.kind, lm, sz, p.artifact_count
+                "{} {:<46} {:?}  latest:{}  size:{}  artifacts:{}  eco:{}  git:{}",
+                star,
+                p.path,
+                p.kind,
+                lm,
+                sz,
+                p.artifact_count(),
+                eco,
+                git
             );
             ListItem::new(line)
         })
         .collect();
 
     let mut state = ListState::default();
-    if !items.is_empty() {
-        let sel = st.selected_project.min(items.len() - 1);
+    let selected = if items.is_empty() {
+        None
+    } else {
+        let sel = selected_project.min(items.len() - 1);
         state.select(Some(sel));
-    }
+        Some(ps[sel].clone())
+    };
     let list = List::new(items)
         .block(b)
         .highlight_symbol("▶ ")
         .highlight_style(Style::default().add_modifier(Modifier::BOLD));
-    f.render_stateful_widget(list, area, &mut state);
+    f.render_stateful_widget(list, layout[0], &mut state);
+
+    let detail = Paragraph::new(
+        selected
+            .as_ref()
+            .map(|p| project_detail(p, &st.timeline(&p.path), st.trend(&p.path).as_ref()))
+            .unwrap_or_default(),
+    )
+    .block(Block::default().title("Details").borders(Borders::ALL))
+    .wrap(Wrap { trim: false });
+    f.render_widget(detail, layout[1]);
+}
+
+/// Detail pane text for the selected project: identity, git state, LOC table
+fn project_detail(
+    p: &ProjectEntry,
+    timeline: &[TimelineEntry],
+    trend: Option<&ProjectTrend>,
+) -> String {
+    let mut t = String::new();
+    t.push_str(&format!("Path: {}\nKind: {:?}\n", p.path, p.kind));
+    if let Some(parent) = &p.parent {
+        t.push_str(&format!("Parent: {}\n", parent));
+    }
+    if !p.members.is_empty() {
+        t.push_str(&format!("Members: {}\n", p.members.join(", ")));
+    }
+    if let Some(g) = &p.git {
+        t.push_str(&format!("Git: {}", git_label(g)));
+        if let Some(up) = &g.upstream {
+            t.push_str(&format!("  ({})", up));
+        }
+        t.push('\n');
+    }
+    if !p.artifacts.is_empty() {
+        let cats: Vec<String> = p
+            .artifacts
+            .iter()
+            .map(|(c, n)| format!("{c} {n}"))
+            .collect();
+        t.push_str(&format!("Artifacts: {}\n", cats.join(", ")));
+    }
+    if let Some(tr) = trend {
+        t.push_str(&format!(
+            "Size:     {} {} over {} runs\nActivity: {}\n",
+            history::sparkline(&tr.sizes),
+            history::signed_size(tr.size_delta),
+            tr.sizes.len(),
+            history::activity_line(&tr.activity)
+        ));
+    }
+    if !timeline.is_empty() {
+        t.push_str("\nTimeline (oldest first):\n");
+        const SHOWN: usize = 10;
+        if timeline.len() > SHOWN {
+            t.push_str(&format!(" … {} earlier\n", timeline.len() - SHOWN));
+        }
+        for e in &timeline[timeline.len().saturating_sub(SHOWN)..] {
+            t.push_str(&format!(
+                " {}{} {:<10} {}\n",
+                e.date,
+                if e.source == DateSource::Modified {
+                    "~"
+                } else {
+                    " "
+                },
+                e.artifact.category,
+                e.artifact.label()
+            ));
+        }
+    }
+
+    t.push_str("\nLines of code:\n");
+    let Some(loc) = &p.loc else {
+        t.push_str(" not counted — run `orbit census --loc`\n");
+        return t;
+    };
+    if loc.is_empty() {
+        t.push_str(" no recognized source files\n");
+        return t;
+    }
+    t.push_str(&format!(
+        " {:<12} {:>6} {:>9} {:>8} {:>7}\n",
+        "language", "files", "code", "comment", "blank"
+    ));
+    let mut rows: Vec<_> = loc.iter().collect();
+    rows.sort_by(|a, b| b.1.code.cmp(&a.1.code).then(a.0.cmp(b.0)));
+    for (lang, c) in rows {
+        t.push_str(&format!(
+            " {:<12} {:>6} {:>9} {:>8} {:>7}\n",
+            lang, c.files, c.code, c.comment, c.blank
+        ));
+    }
+    if let Some(c) = p.loc_total() {
+        t.push_str(&format!(
+            " {:<12} {:>6} {:>9} {:>8} {:>7}\n",
+            "total", c.files, c.code, c.comment, c.blank
+        ));
+    }
+    t
+}
+
+fn pct(score: Option<f64>) -> String {
+    score
+        .map(|s| format!("{:.0}%", s * 100.0))
+        .unwrap_or_else(|| "?".into())
 }
 
 fn draw_dupes(f: &mut Frame, st: &mut State, area: Rect) {
@@ -158,28 +335,32 @@ fn draw_dupes(f: &mut Frame, st: &mut State, area: Rect) {
     // Copy selection indices before borrowing
     let sel_group = st.selected_dupe_group;
     let sel_item = st.selected_dupe_item;
+    let threshold = st.similarity_threshold;
 
     // Clone the groups data to avoid borrow conflicts
-    let groups: Vec<(String, Vec<_>)> = st.duplicate_groups().to_vec();
+    let groups: Vec<DupeGroup> = st.duplicate_groups().to_vec();
+    let report = st.dupe_report().cloned();
 
     let left_block = Block::default()
-        .title("Duplicate groups (by fingerprint)")
+        .title(format!(
+            "Near-duplicate groups (similarity ≥ {:.0}%) — m mark left, d diff, a archive",
+            threshold * 100.0
+        ))
         .borders(Borders::ALL);
     let right_block = Block::default().title("Why flagged").borders(Borders::ALL);
 
     let left_items: Vec<ListItem> = groups
         .iter()
-        .map(|(fp, v)| {
-            let newest = v
-                .iter()
-                .filter_map(|p| p.latest_mtime)
-                .max()
+        .map(|g| {
+            let newest = g.members[0]
+                .latest_mtime
                 .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
                 .unwrap_or_else(|| "N/A".into());
             ListItem::new(format!(
-                "{}…  copies:{}  newest:{}",
-                &fp[..8.min(fp.len())],
-                v.len(),
+                "{}  min:{}  copies:{}  newest:{}",
+                g.members[0].path,
+                pct(g.min_score()),
+                g.members.len(),
                 newest
             ))
         })
@@ -199,19 +380,23 @@ fn draw_dupes(f: &mut Frame, st: &mut State, area: Rect) {
     let mut expl = String::new();
     if groups.is_empty() {
         expl.push_str(
-            "No duplicate fingerprint groups found yet.\n\nRun Census (ENTER) to refresh index.",
+            "No near-duplicate groups found yet.\n\nRun Census (ENTER) to refresh index.",
         );
     } else {
-        let gidx = sel_group.min(groups.len() - 1);
-        let (fp, items) = &groups[gidx];
-        let iidx = sel_item.min(items.len() - 1);
-        let item = &items[iidx];
-
-        expl.push_str(&format!("Fingerprint: {}\n", fp));
-        expl.push_str("\nOrbit marks duplicates when multiple projects share the same lightweight fingerprint.\n");
-        expl.push_str("Basis currently includes:\n");
-        expl.push_str(" • Blake3 hash of sampled marker files (README/CLAUDE/AGENT/build files)\n");
-        expl.push_str(" • Structural hint (immediate child names)\n\n");
+        let g = &groups[sel_group.min(groups.len() - 1)];
+        let iidx = sel_item.min(g.members.len() - 1);
+        let item = &g.members[iidx];
+        let other = &g.members[if iidx == 0 { 1 } else { 0 }];
+
+        expl.push_str("Orbit groups projects whose file contents overlap by at least the\n");
+        expl.push_str(&format!(
+            "configured threshold ({:.0}%, `[similarity] threshold` in config.toml).\n",
+            threshold * 100.0
+        ));
+        expl.push_str(
+            "Similarity is a MinHash estimate over (path, content hash) of every file\n\
+             (`orbit census --similarity`); without it only identical copies are grouped.\n\n",
+        );
         expl.push_str("Selected copy:\n");
         expl.push_str(&format!(
             " • Path: {}\n • Kind: {:?}\n • Pinned: {}\n",
@@ -220,17 +405,50 @@ fn draw_dupes(f: &mut Frame, st: &mut State, area: Rect) {
         if let Some(lm) = item.latest_mtime {
             expl.push_str(&format!(" • Latest: {}\n", lm.to_rfc3339()));
         }
-        expl.push_str("\nCopies in this group:\n");
-        for p in items.iter().take(12) {
+        expl.push_str("\nCopies in this group (similarity to newest):\n");
+        for (p, score) in g.members.iter().zip(&g.scores).take(12) {
             expl.push_str(&format!(
-                " - {}{}\n",
+                " - {}{}  {}\n",
                 if p.pinned { "★ " } else { "" },
-                p.path
+                p.path,
+                pct(*score)
             ));
         }
-        if items.len() > 12 {
+        if g.members.len() > 12 {
             expl.push_str(" - …\n");
         }
+
+        expl.push_str(&format!("\nFiles differing from {}:\n", other.path));
+        match &report {
+            None => expl.push_str(" comparing…\n"),
+            Some(Err(e)) => expl.push_str(&format!(" (could not compare: {})\n", e)),
+            Some(Ok(d)) if d.is_identical() => {
+                expl.push_str(&format!(" none — {} files identical\n", d.unchanged))
+            }
+            Some(Ok(d)) => {
+                expl.push_str(&format!(
+                    " {} changed, {} only here, {} only in {}, {} identical (exact {:.0}%)\n",
+                    d.changed.len(),
+                    d.only_right.len(),
+                    d.only_left.len(),
+                    other.path,
+                    d.unchanged,
+                    d.jaccard() * 100.0
+                ));
+                let changed: Vec<String> = d.changed.iter().map(|c| c.path.clone()).collect();
+                for (mark, files) in [("~", &changed), ("+", &d.only_right), ("-", &d.only_left)] {
+                    for path in files.iter().take(8) {
+                        expl.push_str(&format!(" {} {}\n", mark, path));
+                    }
+                    if files.len() > 8 {
+                        expl.push_str(&format!(" {} … {} more\n", mark, files.len() - 8));
+                    }
+                }
+            }
+        }
+        if let Some(marked) = &st.diff_left {
+            expl.push_str(&format!("\nMarked for diff (left): {}\n", marked));
+        }
         expl.push_str("\nTip: Pin the intended 'real' one with `f` so Orbit won't auto-demote it.");
     }
 
@@ -239,3 +457,267 @@ fn draw_dupes(f: &mut Frame, st: &mut State, area: Rect) {
         .wrap(Wrap { trim: false });
     f.render_widget(right, layout[1]);
 }
+
+/// `1.5 GiB`-style size for the reclaim panel
+fn draw_artifacts(f: &mut Frame, st: &mut State, area: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+        .split(area);
+
+    let (items, selected, total) = {
+        let list = st.artifacts_filtered();
+        let items: Vec<ListItem> = list
+            .iter()
+            .map(|a| {
+                ListItem::new(format!(
+                    "{}  {:<10} {}  — {}",
+                    a.mtime
+                        .map(|d| d.format("%Y-%m-%d").to_string())
+                        .unwrap_or_else(|| "N/A".into()),
+                    a.category,
+                    a.key(),
+                    a.label()
+                ))
+            })
+            .collect();
+        let selected = (!list.is_empty())
+            .then(|| st.selected_artifact.min(list.len() - 1))
+            .map(|i| (i, list[i].clone()));
+        (items, selected, st.catalog.artifacts.len())
+    };
+
+    let mut state = ListState::default();
+    state.select(selected.as_ref().map(|(i, _)| *i));
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Artifacts ({} catalogued) — / fuzzy filter, `orbit grep-artifacts` for full text",
+                    total
+                ))
+                .borders(Borders::ALL),
+        )
+        .highlight_symbol("▶ ")
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+    f.render_stateful_widget(list, layout[0], &mut state);
+
+    let mut t = String::new();
+    match &selected {
+        None if total == 0 => {
+            t.push_str("No artifacts catalogued yet.\n\nPress ENTER on Home to run a census.")
+        }
+        None => t.push_str("No artifacts match the filter."),
+        Some((_, a)) => {
+            t.push_str(&format!(
+                "Path: {}\nCategory: {}\nTitle: {}\nSize: {}\nModified: {}\n\n",
+                a.key(),
+                a.category,
+                a.title.as_deref().unwrap_or("-"),
+                size_label(a.size),
+                a.mtime
+                    .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "N/A".into())
+            ));
+            if let Some(preview) = st.artifact_preview() {
+                t.push_str(preview);
+            }
+        }
+    }
+    let detail = Paragraph::new(t)
+        .block(Block::default().title("Preview").borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(detail, layout[1]);
+}
+
+/// Whole days since `t` (`?` when unknown)
+fn idle_days(t: Option<chrono::DateTime<chrono::Local>>) -> String {
+    t.map(|t| format!("{}d", (chrono::Local::now() - t).num_days()))
+        .unwrap_or_else(|| "?".into())
+}
+
+fn draw_reclaim(f: &mut Frame, st: &mut State, area: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+        .split(area);
+
+    let ps = st.reclaimable_projects();
+    let total: u64 = ps.iter().map(|p| p.reclaimable_bytes()).sum();
+    let items: Vec<ListItem> = ps
+        .iter()
+        .map(|p| {
+            ListItem::new(format!(
+                "{}{:<40} {:>10}  idle:{:>5}  dirs:{}",
+                if p.pinned { "★ " } else { "  " },
+                p.path,
+                size_label(p.reclaimable_bytes()),
+                idle_days(p.latest_mtime),
+                p.build_dirs.as_ref().map(|d| d.len()).unwrap_or(0)
+            ))
+        })
+        .collect();
+
+    let mut state = ListState::default();
+    let selected = if ps.is_empty() {
+        None
+    } else {
+        let sel = st.selected_reclaim.min(ps.len() - 1);
+        state.select(Some(sel));
+        Some(ps[sel])
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Reclaimable build outputs — {} total, largest first",
+                    size_label(total)
+                ))
+                .borders(Borders::ALL),
+        )
+        .highlight_symbol("▶ ")
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    let mut t = String::new();
+    match selected {
+        None if !st.reclaim_measured() => t.push_str(
+            "Build directories are not measured yet.\n\nRun `orbit census --reclaim`; ENTER keeps\nmeasuring once the index has them.",
+        ),
+        None => t.push_str("No build outputs found."),
+        Some(p) => {
+            t.push_str(&format!(
+                "Path: {}\nSources last touched: {} ({} ago)\n\n",
+                p.path,
+                p.latest_mtime
+                    .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "N/A".into()),
+                idle_days(p.latest_mtime)
+            ));
+            for d in p.build_dirs.iter().flatten() {
+                t.push_str(&format!(
+                    " {:<28} {:<7} {:>10}  {} files  touched {}\n",
+                    d.path,
+                    d.kind,
+                    size_label(d.size_bytes),
+                    d.files,
+                    idle_days(d.last_touched)
+                ));
+            }
+            t.push_str("\nDelete with `orbit clean-artifacts --older-than <days>`\n(pins and whitelist are respected; try --dry-run first).");
+        }
+    }
+    f.render_stateful_widget(list, layout[0], &mut state);
+    let detail = Paragraph::new(t)
+        .block(
+            Block::default()
+                .title("Build directories")
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(detail, layout[1]);
+}
+
+fn stat_label(size: u64, mtime: Option<chrono::DateTime<chrono::Local>>) -> String {
+    format!(
+        "{}B {}",
+        size,
+        mtime
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "N/A".into())
+    )
+}
+
+fn draw_diff(f: &mut Frame, st: &mut State, area: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(area);
+
+    let patch = st.diff_patch().cloned();
+    let Some(v) = &st.diff_view else {
+        return;
+    };
+
+    let entries = v.entries();
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|e| match e {
+            DiffEntry::Changed(c) => ListItem::new(format!(
+                "~ {}  {} → {}",
+                c.path,
+                stat_label(c.left.size, c.left.mtime),
+                stat_label(c.right.size, c.right.mtime)
+            )),
+            DiffEntry::OnlyLeft(p) => {
+                ListItem::new(format!("- {}", p)).style(Style::default().fg(Color::Red))
+            }
+            DiffEntry::OnlyRight(p) => {
+                ListItem::new(format!("+ {}", p)).style(Style::default().fg(Color::Green))
+            }
+        })
+        .collect();
+    let left_block = Block::default()
+        .title(format!(
+            "{} ↔ {} — {} changed, {} only left, {} only right, {} identical",
+            v.left,
+            v.right,
+            v.diff.changed.len(),
+            v.diff.only_left.len(),
+            v.diff.only_right.len(),
+            v.diff.unchanged
+        ))
+        .borders(Borders::ALL);
+    let mut lstate = ListState::default();
+    if !items.is_empty() {
+        lstate.select(Some(v.selected.min(items.len() - 1)));
+    }
+    let list = List::new(items)
+        .block(left_block)
+        .highlight_symbol("▶ ")
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+    f.render_stateful_widget(list, layout[0], &mut lstate);
+
+    let lines: Vec<Line> = match (entries.get(v.selected), &patch) {
+        (None, _) => vec![Line::from(format!(
+            "No differences: all {} files are identical.",
+            v.diff.unchanged
+        ))],
+        (Some(DiffEntry::OnlyLeft(p)), _) => {
+            vec![Line::from(format!("{} exists only in {}", p, v.left))]
+        }
+        (Some(DiffEntry::OnlyRight(p)), _) => {
+            vec![Line::from(format!("{} exists only in {}", p, v.right))]
+        }
+        (Some(DiffEntry::Changed(_)), Some(Err(e))) => {
+            vec![Line::from(format!("Could not diff: {}", e))]
+        }
+        (Some(DiffEntry::Changed(_)), Some(Ok(None)) | None) => {
+            vec![Line::from("Binary or large file — no inline diff.")]
+        }
+        (Some(DiffEntry::Changed(_)), Some(Ok(Some(text)))) => text
+            .lines()
+            .map(|l| {
+                let style = if l.starts_with("+++") || l.starts_with("---") {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else if l.starts_with('+') {
+                    Style::default().fg(Color::Green)
+                } else if l.starts_with('-') {
+                    Style::default().fg(Color::Red)
+                } else if l.starts_with("@@") {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default()
+                };
+                Line::styled(l.to_string(), style)
+            })
+            .collect(),
+    };
+    let right = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Unified diff — ↑/↓ file, PgUp/PgDn scroll, Esc close")
+                .borders(Borders::ALL),
+        )
+        .scroll((v.scroll, 0));
+    f.render_widget(right, layout[1]);
+}
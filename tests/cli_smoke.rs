@@ -21,6 +21,7 @@ fn cli_smoke_census_focus_export_status() {
         .arg("4")
         .arg("--since")
         .arg("1970-01-01")
+        .arg("--loc")
         .output()
         .expect("run census");
     assert!(
@@ -84,6 +85,11 @@ fn cli_smoke_census_focus_export_status() {
     assert!(exports_dir.join("summary.md").is_file());
     assert!(exports_dir.join("index.json").is_file());
     assert!(exports_dir.join("index.csv").is_file());
+    let loc_csv = std::fs::read_to_string(exports_dir.join("loc.csv")).expect("read loc.csv");
+    assert!(
+        loc_csv.contains("proj1,Markdown,1,1,0,0"),
+        "unexpected loc.csv: {loc_csv}"
+    );
 
     let snap = Command::new(bin)
         .arg("--root")
@@ -116,7 +122,7 @@ fn cli_smoke_census_focus_export_status() {
     assert!(snap_dir.join("focus.json").is_file());
     assert!(snap_dir.join("index.json").is_file());
     assert!(snap_dir.join("summary.md").is_file());
-    assert!(snap_dir.join("artifacts").is_dir());
+    assert!(snap_dir.join("manifest.json").is_file());
 
     let status = Command::new(bin)
         .arg("--root")
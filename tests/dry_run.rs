@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::Path;
 
+use orbit::cleanup::archive::handle_archive;
 use orbit::export::all::export_all;
 use orbit::snapshot::quick::snapshot_pinned;
 
@@ -25,6 +26,34 @@ fn export_dry_run_creates_nothing() {
     assert!(!exports_dir.exists(), "dry-run should not create exports dir");
 }
 
+#[test]
+fn archive_dry_run_creates_nothing() {
+    let tmp = tempfile::tempdir().expect("tempdir");
+    let root = tmp.path();
+    let proj = root.join("site_backup");
+    fs::create_dir_all(&proj).unwrap();
+    fs::write(proj.join("README.md"), "hi").unwrap();
+
+    handle_archive(
+        root.to_string_lossy().as_ref(),
+        vec!["site_backup".into()],
+        false,
+        None,
+        false,
+        true,
+        false,
+    )
+    .unwrap();
+    assert!(
+        proj.join("README.md").is_file(),
+        "dry-run must not move projects"
+    );
+    assert!(
+        !root.join(".orbit").exists(),
+        "dry-run should not create .orbit"
+    );
+}
+
 #[test]
 fn snapshot_respects_whitelist() {
     let tmp = tempfile::tempdir().expect("tempdir");
@@ -44,10 +73,11 @@ fn snapshot_respects_whitelist() {
 
     snapshot_pinned(root.to_string_lossy().as_ref(), Some("test"), false).unwrap();
     let snap_root = find_snapshot_dir(root);
-    let artifacts = snap_root.join("artifacts");
-    assert!(artifacts.exists());
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(snap_root.join("manifest.json")).unwrap())
+            .unwrap();
     // Protected project should not appear
-    let copied = fs::read_dir(&artifacts).unwrap().count();
+    let copied = manifest["artifacts"].as_array().unwrap().len();
     assert_eq!(copied, 0, "whitelisted project should be skipped");
 }
 
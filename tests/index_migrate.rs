@@ -0,0 +1,143 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+fn orbit(home: &Path, root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_orbit"))
+        .env("HOME", home)
+        .arg("--root")
+        .arg(root)
+        .args(args)
+        .output()
+        .expect("run orbit")
+}
+
+fn backups(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .starts_with("index.json.bak-")
+        })
+        .count()
+}
+
+#[test]
+fn legacy_index_is_migrated_and_check_reports_it() {
+    let td = TempDir::new().expect("tempdir");
+    let (home, root) = (td.path().join("home"), td.path().join("ws"));
+    let orbit_dir = home.join(".orbit");
+    std::fs::create_dir_all(&orbit_dir).unwrap();
+    std::fs::create_dir_all(&root).unwrap();
+    let legacy = serde_json::json!({
+        "version": "0.5",
+        "root": root.display().to_string(),
+        "generated_at": null,
+        "projects": [{ "path": "api", "kind": "standalone", "pinned": false, "has_git": false, "has_rust": true }]
+    });
+    std::fs::write(orbit_dir.join("index.json"), legacy.to_string()).unwrap();
+
+    let check = orbit(&home, &root, &["index", "migrate", "--check"]);
+    assert!(
+        !check.status.success(),
+        "check should fail before migrating"
+    );
+    assert!(String::from_utf8_lossy(&check.stderr).contains("need migration"));
+
+    let run = orbit(&home, &root, &["--json", "index", "migrate"]);
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&run.stdout).unwrap();
+    assert_eq!(report["files"][0]["status"], "migrated");
+    assert_eq!(report["files"][0]["from"], "0.5");
+    assert_eq!(backups(&orbit_dir), 1);
+
+    let shared: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(orbit_dir.join("index.json")).unwrap())
+            .unwrap();
+    assert_eq!(shared["version"], "0.6");
+    let key = root.canonicalize().unwrap().display().to_string();
+    assert_eq!(
+        shared["roots"][&key]["projects"][0]["ecosystems"][0],
+        "rust"
+    );
+
+    assert!(orbit(&home, &root, &["index", "migrate", "--check"])
+        .status
+        .success());
+}
+
+#[test]
+fn unreadable_index_is_an_error_with_a_backup() {
+    let td = TempDir::new().expect("tempdir");
+    let (home, root) = (td.path().join("home"), td.path().join("ws"));
+    let orbit_dir = home.join(".orbit");
+    std::fs::create_dir_all(&orbit_dir).unwrap();
+    std::fs::create_dir_all(root.join("app")).unwrap();
+    std::fs::write(root.join("app").join("README.md"), "hi\n").unwrap();
+    std::fs::write(orbit_dir.join("index.json"), "{ truncated").unwrap();
+
+    let out = orbit(&home, &root, &["census"]);
+    assert!(
+        !out.status.success(),
+        "census must not overwrite a broken index"
+    );
+    assert!(String::from_utf8_lossy(&out.stderr).contains("a copy was saved to"));
+    assert_eq!(backups(&orbit_dir), 1);
+    assert!(!orbit(&home, &root, &["census"]).status.success());
+    assert_eq!(
+        backups(&orbit_dir),
+        1,
+        "the same broken index is backed up once"
+    );
+    assert_eq!(
+        std::fs::read_to_string(orbit_dir.join("index.json")).unwrap(),
+        "{ truncated"
+    );
+}
+
+#[test]
+fn relative_legacy_root_is_only_claimed_by_an_explicit_migrate() {
+    let td = TempDir::new().expect("tempdir");
+    let (home, root) = (td.path().join("home"), td.path().join("ws"));
+    let orbit_dir = home.join(".orbit");
+    std::fs::create_dir_all(&orbit_dir).unwrap();
+    std::fs::create_dir_all(root.join("api")).unwrap();
+    let legacy = serde_json::json!({
+        "version": "0.5",
+        "root": ".",
+        "generated_at": null,
+        "projects": [{ "path": "api", "kind": "standalone", "pinned": false, "has_git": false }]
+    });
+    std::fs::write(orbit_dir.join("index.json"), legacy.to_string()).unwrap();
+
+    let other = td.path().join("other");
+    std::fs::create_dir_all(&other).unwrap();
+    let out = orbit(&home, &other, &["status"]);
+    assert!(!out.status.success());
+    assert!(
+        String::from_utf8_lossy(&out.stderr).contains("orbit --root <dir> index migrate"),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let run = orbit(&home, &root, &["index", "migrate"]);
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+    let shared: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(orbit_dir.join("index.json")).unwrap())
+            .unwrap();
+    let key = root.canonicalize().unwrap().display().to_string();
+    let roots: Vec<&String> = shared["roots"].as_object().unwrap().keys().collect();
+    assert_eq!(roots, vec![&key]);
+    assert_eq!(shared["roots"][&key]["projects"][0]["path"], "api");
+}
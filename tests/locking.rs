@@ -0,0 +1,148 @@
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::process::{Child, Command};
+
+use tempfile::TempDir;
+
+fn orbit(home: &Path, root: &Path, args: &[&str]) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_orbit"));
+    cmd.env("HOME", home).arg("--root").arg(root).args(args);
+    cmd
+}
+
+fn setup(n: usize) -> (TempDir, std::path::PathBuf, std::path::PathBuf) {
+    let td = TempDir::new().expect("tempdir");
+    let (home, root) = (td.path().join("home"), td.path().join("ws"));
+    std::fs::create_dir_all(home.join(".orbit")).unwrap();
+    for i in 0..n {
+        std::fs::create_dir_all(root.join(format!("p{i}"))).unwrap();
+    }
+    (td, home, root)
+}
+
+#[test]
+fn concurrent_focus_updates_are_not_lost() {
+    let n = 8;
+    let (_td, home, root) = setup(n);
+    let children: Vec<Child> = (0..n)
+        .map(|i| {
+            orbit(&home, &root, &["focus", "--add", &format!("p{i}")])
+                .spawn()
+                .expect("spawn orbit")
+        })
+        .collect();
+    for mut c in children {
+        assert!(c.wait().unwrap().success());
+    }
+
+    let focus: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(home.join(".orbit").join("focus.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(focus["pinned"].as_array().unwrap().len(), n, "{focus}");
+    let leftovers: Vec<_> = std::fs::read_dir(home.join(".orbit"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|n| n.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+}
+
+#[test]
+fn held_lock_times_out_with_a_clear_error() {
+    let (_td, home, root) = setup(1);
+    let lock: File = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(home.join(".orbit").join("focus.json.lock"))
+        .unwrap();
+    lock.lock().unwrap();
+
+    let out = orbit(&home, &root, &["focus", "--add", "p0"])
+        .env("ORBIT_LOCK_TIMEOUT_SECS", "0.3")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("Timed out after 0.3s waiting for"),
+        "{stderr}"
+    );
+    assert!(!home.join(".orbit").join("focus.json").exists());
+
+    lock.unlock().unwrap();
+    let out = orbit(&home, &root, &["focus", "--add", "p0"])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+#[test]
+fn watch_logs_failed_updates_and_keeps_going() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let (_td, home, root) = setup(1);
+    std::fs::write(root.join("p0").join("Cargo.toml"), "[package]\n").unwrap();
+    assert!(orbit(&home, &root, &["census"])
+        .output()
+        .unwrap()
+        .status
+        .success());
+    let journal = root.join(".orbit").join("journal.jsonl");
+    let journaled = || std::fs::read_to_string(&journal).unwrap().lines().count();
+    assert_eq!(journaled(), 1);
+
+    let lock: File = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(home.join(".orbit").join("index.json.lock"))
+        .unwrap();
+    lock.lock().unwrap();
+    let mut watch = orbit(&home, &root, &["--json", "watch", "--debounce-ms", "100"])
+        .env("ORBIT_LOCK_TIMEOUT_SECS", "0.2")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+    let stdout = watch.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let _ = tx.send(line);
+        }
+    });
+    let next = || {
+        rx.recv_timeout(Duration::from_secs(20))
+            .expect("watch output")
+    };
+    // New project → rescan. The pause lets the watcher pick up the new
+    // directory before the marker appears in it.
+    let add_project = |name: &str| {
+        std::fs::create_dir_all(root.join(name)).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        std::fs::write(root.join(name).join("Cargo.toml"), "[package]\n").unwrap();
+    };
+    std::thread::sleep(Duration::from_millis(500));
+
+    add_project("p1");
+    let failed = next();
+    assert!(failed.contains("Timed out"), "{failed}");
+
+    lock.unlock().unwrap();
+    add_project("p2");
+    let applied = next();
+    let _ = watch.kill();
+    let _ = watch.wait();
+    assert!(applied.contains("\"rescanned\":true"), "{applied}");
+    assert!(applied.contains("p2"), "{applied}");
+    assert_eq!(journaled(), 1, "watch rescans are not journaled");
+}
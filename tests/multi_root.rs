@@ -0,0 +1,53 @@
+use std::path::Path;
+use std::process::Command;
+
+use tempfile::TempDir;
+
+fn orbit(home: &Path, root: &Path, args: &[&str]) -> serde_json::Value {
+    let out = Command::new(env!("CARGO_BIN_EXE_orbit"))
+        .env("HOME", home)
+        .arg("--root")
+        .arg(root)
+        .arg("--json")
+        .args(args)
+        .output()
+        .expect("run orbit");
+    assert!(
+        out.status.success(),
+        "orbit {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    serde_json::from_slice(&out.stdout).expect("json output")
+}
+
+#[test]
+fn census_of_one_root_keeps_the_other() {
+    let td = TempDir::new().expect("tempdir");
+    let home = td.path().join("home");
+    let (work, oss) = (td.path().join("work"), td.path().join("oss"));
+    for p in [work.join("api"), work.join("web"), oss.join("lib")] {
+        std::fs::create_dir_all(&p).unwrap();
+        std::fs::write(p.join("README.md"), "hi\n").unwrap();
+    }
+    std::fs::create_dir_all(&home).unwrap();
+
+    orbit(&home, &work, &["census"]);
+    orbit(&home, &oss, &["census"]);
+    std::fs::remove_dir_all(work.join("web")).unwrap();
+    orbit(&home, &work, &["census"]);
+
+    let shared: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(home.join(".orbit").join("index.json")).unwrap(),
+    )
+    .unwrap();
+    let roots = shared["roots"].as_object().expect("roots map");
+    assert_eq!(roots.len(), 2, "{shared}");
+    let key = |p: &Path| p.canonicalize().unwrap().display().to_string();
+    assert_eq!(roots[&key(&work)]["projects"].as_array().unwrap().len(), 1);
+    assert_eq!(roots[&key(&oss)]["projects"][0]["path"], "lib");
+
+    let status = orbit(&home, &oss, &["status"]);
+    assert_eq!(status["indexed_projects"], 1);
+    assert_eq!(status["current_root"], key(&oss));
+    assert_eq!(status["roots"].as_array().unwrap().len(), 2);
+}
@@ -1,3 +1,6 @@
+#![allow(clippy::field_reassign_with_default)]
+
+use orbit::index::store::OrbitIndex;
 use orbit::index::whitelist::{is_protected, load_whitelist, save_whitelist, Whitelist};
 use orbit::system::{load_metrics, save_metrics, SystemMetrics};
 
@@ -14,9 +17,12 @@ fn whitelist_roundtrip_and_protection() {
     assert_eq!(loaded.paths, wl.paths);
 
     let protected_path = root.join("protected").join("nested");
-    assert!(is_protected(&protected_path, &loaded));
+    assert!(is_protected(root, &protected_path, &loaded));
     let other = root.join("other");
-    assert!(!is_protected(&other, &loaded));
+    assert!(!is_protected(root, &other, &loaded));
+    // Entries name a root-relative path, not a component anywhere below it
+    let elsewhere = root.join("other").join("protected");
+    assert!(!is_protected(root, &elsewhere, &loaded));
 }
 
 #[test]
@@ -31,3 +37,37 @@ fn metrics_roundtrip_tolerates_partial() {
     // missing fields should remain None
     assert!(loaded.memory.is_none());
 }
+
+#[test]
+fn index_reads_legacy_language_flags_and_writes_them_back() {
+    let legacy = serde_json::json!({
+        "version": "0.5",
+        "root": ".",
+        "generated_at": null,
+        "projects": [{
+            "path": "api",
+            "kind": "standalone",
+            "latest_mtime": null,
+            "size_bytes": 10,
+            "artifact_count": 2,
+            "has_git": false,
+            "has_rust": true,
+            "has_node": false,
+            "has_python": true,
+            "fingerprint": null
+        }]
+    });
+    let idx: OrbitIndex = serde_json::from_value(legacy).unwrap();
+    assert_eq!(idx.projects[0].ecosystems, vec!["python", "rust"]);
+    assert_eq!(idx.projects[0].artifacts.get("other"), Some(&2));
+
+    let mut p = idx.projects[0].clone();
+    p.ecosystems = vec!["go".into(), "node".into()];
+    let out = serde_json::to_value(&p).unwrap();
+    assert_eq!(out["ecosystems"], serde_json::json!(["go", "node"]));
+    assert_eq!(out["has_node"], true);
+    assert_eq!(out["has_rust"], false);
+    assert_eq!(out["has_python"], false);
+    assert_eq!(out["artifacts"], serde_json::json!({ "other": 2 }));
+    assert_eq!(out["artifact_count"], 2);
+}